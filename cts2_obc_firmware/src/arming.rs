@@ -0,0 +1,44 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::arming::{ArmError, CommandArm};
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::dictionary::CommandInfo;
+use cts2_obc_telecommands::get_config_store;
+
+use crate::timekeeping::uptime_ms;
+
+static COMMAND_ARM: Mutex<RefCell<CommandArm>> = Mutex::new(RefCell::new(CommandArm::new()));
+
+fn arm_window_ms() -> u64 {
+    match get_config_store().get(ConfigVariableName::ArmWindowS) {
+        ConfigValue::U32(s) => s as u64 * 1000,
+        _ => 0,
+    }
+}
+
+/// Allow `command` to run once, within the arm window.
+pub fn arm(command: &'static CommandInfo, nonce: u32) -> Result<(), ArmError> {
+    critical_section(|cs| {
+        COMMAND_ARM
+            .borrow(cs)
+            .borrow_mut()
+            .arm(command, nonce, uptime_ms())
+    })
+}
+
+/// Check that a telecommand from the ground may run. Uses up the arm if it is hazardous.
+pub fn check_armed(name: &str) -> Result<(), ArmError> {
+    let now_ms = uptime_ms();
+    let window_ms = arm_window_ms();
+    critical_section(|cs| {
+        COMMAND_ARM
+            .borrow(cs)
+            .borrow_mut()
+            .check(name, now_ms, window_ms)
+    })
+}
+
+/// Clear any arm. Called on a mode change.
+pub fn disarm() {
+    critical_section(|cs| COMMAND_ARM.borrow(cs).borrow_mut().disarm());
+}
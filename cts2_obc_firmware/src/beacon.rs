@@ -0,0 +1,55 @@
+use core::cell::Cell;
+use core::fmt::Write;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+
+use crate::radio::radio_transmit;
+use crate::telemetry::find_telemetry_point;
+use crate::timekeeping::uptime_ms;
+
+/// Time between beacons.
+const BEACON_PERIOD_MS: u64 = 30_000;
+
+/// Telemetry points sent in each beacon, in order.
+const BEACON_POINTS: &[&str] = &[
+    "mode",
+    "met_ms",
+    "boot_count",
+    "antennas_deployed",
+    "event_count",
+    "commands_received",
+    "commands_ok",
+    "commands_failed",
+];
+
+static NEXT_BEACON_MS: Mutex<Cell<u64>> = Mutex::new(Cell::new(0));
+
+/// Send a beacon on the radio when one is due. Call periodically from the main loop.
+///
+/// A beacon is a JSON object of `BEACON_POINTS`, e.g.
+/// `BEACON {"mode":"nominal","met_ms":1234,...}`. Like any radio transmission, it is dropped
+/// during the post-deployment silence period and while the transmitter is inhibited.
+pub fn poll_beacon() {
+    let now = uptime_ms();
+    let due = critical_section(|cs| {
+        let next = NEXT_BEACON_MS.borrow(cs);
+        if now < next.get() {
+            return false;
+        }
+        next.set(now + BEACON_PERIOD_MS);
+        true
+    });
+    if !due {
+        return;
+    }
+
+    let mut buffer = heapless::String::<256>::new();
+    let _ = buffer.push_str("BEACON {");
+    for (i, name) in BEACON_POINTS.iter().enumerate() {
+        if let Some(point) = find_telemetry_point(name) {
+            let separator = if i == 0 { "" } else { "," };
+            let _ = write!(buffer, "{}\"{}\":{}", separator, name, (point.read)());
+        }
+    }
+    let _ = buffer.push_str("}\r\n");
+    let _ = radio_transmit(buffer.as_bytes());
+}
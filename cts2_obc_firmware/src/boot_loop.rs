@@ -0,0 +1,76 @@
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use cts2_obc_logic::boot_loop::{
+    BOOT_RECORD_WORDS, BootRecord, MAX_FAST_RESETS, STABLE_UPTIME_MS, on_boot,
+};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use cts2_obc_telecommands::mode::OperatingMode;
+
+use crate::timekeeping::uptime_ms;
+
+/// Boot record, in RAM that cortex-m-rt does not initialize, so that it survives a reset.
+#[unsafe(link_section = ".uninit.BOOT_RECORD")]
+static mut BOOT_RECORD: MaybeUninit<[u32; BOOT_RECORD_WORDS]> = MaybeUninit::uninit();
+
+static BOOT_COUNT: AtomicU32 = AtomicU32::new(0);
+static FAST_RESET_COUNT: AtomicU32 = AtomicU32::new(0);
+static MARKED_STABLE: AtomicBool = AtomicBool::new(false);
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "boot_count",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(BOOT_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "fast_reset_count",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(FAST_RESET_COUNT.load(Ordering::Relaxed)),
+    },
+];
+
+fn read_boot_record() -> Option<BootRecord> {
+    let words = &raw const BOOT_RECORD as *const u32;
+    let mut out = [0u32; BOOT_RECORD_WORDS];
+    for (i, word) in out.iter_mut().enumerate() {
+        // SAFETY: The record is plain words in RAM. After a power cycle it holds random
+        // data, which `from_words` rejects.
+        *word = unsafe { core::ptr::read_volatile(words.add(i)) };
+    }
+    BootRecord::from_words(&out)
+}
+
+fn write_boot_record(record: &BootRecord) {
+    let words = &raw mut BOOT_RECORD as *mut u32;
+    for (i, word) in record.to_words().into_iter().enumerate() {
+        // SAFETY: Only this module accesses the record, from the main thread.
+        unsafe { core::ptr::write_volatile(words.add(i), word) };
+    }
+}
+
+/// Count this boot, and decide which mode to start in. Call once, early during startup.
+///
+/// Returns the mode, and the boot record for this boot.
+pub fn check_boot_loop() -> (OperatingMode, BootRecord) {
+    let (record, mode) = on_boot(read_boot_record(), MAX_FAST_RESETS);
+    write_boot_record(&record);
+    BOOT_COUNT.store(record.boot_count, Ordering::Relaxed);
+    FAST_RESET_COUNT.store(record.fast_reset_count, Ordering::Relaxed);
+    (mode, record)
+}
+
+/// Once this boot has been up long enough, stop counting it as a fast reset.
+/// Call periodically from the main loop.
+pub fn poll_boot_stability() {
+    if MARKED_STABLE.load(Ordering::Relaxed) || uptime_ms() < STABLE_UPTIME_MS {
+        return;
+    }
+
+    if let Some(record) = read_boot_record() {
+        write_boot_record(&record.mark_stable());
+    }
+    FAST_RESET_COUNT.store(0, Ordering::Relaxed);
+    MARKED_STABLE.store(true, Ordering::Relaxed);
+}
@@ -0,0 +1,61 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::command_log::{CommandLog, CommandRecord, CommandSource};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+
+use crate::timekeeping::uptime_ms;
+
+/// Number of telecommands kept in RAM. Older ones are dropped.
+pub const COMMAND_HISTORY_LEN: usize = 16;
+
+static COMMAND_LOG: Mutex<RefCell<CommandLog<COMMAND_HISTORY_LEN>>> =
+    Mutex::new(RefCell::new(CommandLog::new()));
+
+/// Record a telecommand in the command log. `started_ms` is the uptime when it was received.
+pub fn record_command(
+    started_ms: u64,
+    source: CommandSource,
+    command: Option<u8>,
+    result: u8,
+    cycles: u32,
+) {
+    let record = CommandRecord {
+        uptime_ms: started_ms,
+        source,
+        command,
+        result,
+        cycles,
+    };
+    critical_section(|cs| COMMAND_LOG.borrow(cs).borrow_mut().record(record));
+}
+
+/// Record a telecommand that didn't parse, so didn't run.
+pub fn record_rejected(source: CommandSource, command: Option<u8>, result: u8) {
+    record_command(uptime_ms(), source, command, result, 0);
+}
+
+/// Run `f` with access to the command log.
+pub fn with_command_log<R>(f: impl FnOnce(&CommandLog<COMMAND_HISTORY_LEN>) -> R) -> R {
+    critical_section(|cs| f(&COMMAND_LOG.borrow(cs).borrow()))
+}
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "commands_received",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(with_command_log(|log| log.totals().received)),
+    },
+    TelemetryPoint {
+        name: "commands_ok",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(with_command_log(|log| log.totals().succeeded)),
+    },
+    TelemetryPoint {
+        name: "commands_failed",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(with_command_log(|log| log.totals().failed)),
+    },
+];
@@ -0,0 +1,90 @@
+use core::cell::{Cell, RefCell};
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::config_audit::{
+    CONFIG_AUDIT_PAGE_WORDS, ConfigAuditLog, ConfigAuditRecord, ConfigChangeSource,
+};
+use cts2_obc_logic::page_flash::PageFlash;
+use cts2_obc_telecommands::config::{ConfigAuditHook, ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::get_config_store;
+use rtt_target::rprintln;
+
+use crate::flash::{self, FlashError};
+use crate::mission_time::mission_elapsed_ms;
+
+/// First of the flash pages that hold the audit trail, reserved in `memory.x`.
+const CONFIG_AUDIT_ADDR: u32 = 0x080F_B000;
+
+/// The audit trail's pages of bank 1.
+#[derive(Debug)]
+pub struct AuditPages;
+
+impl AuditPages {
+    const fn addr(page: usize, offset: usize) -> u32 {
+        CONFIG_AUDIT_ADDR + ((page * CONFIG_AUDIT_PAGE_WORDS + offset) * 4) as u32
+    }
+}
+
+impl PageFlash for AuditPages {
+    type Error = FlashError;
+
+    fn read(&self, page: usize, offset: usize, out: &mut [u32]) {
+        flash::read(Self::addr(page, offset), out);
+    }
+
+    fn erase(&mut self, page: usize) -> Result<(), FlashError> {
+        flash::erase_page(Self::addr(page, 0))
+    }
+
+    fn program(&mut self, page: usize, offset: usize, words: &[u32]) -> Result<(), FlashError> {
+        flash::program(Self::addr(page, offset), words)
+    }
+}
+
+/// The audit trail. `None` until `init()`, or if the flash couldn't be set up.
+static CONFIG_AUDIT: Mutex<RefCell<Option<ConfigAuditLog<AuditPages>>>> =
+    Mutex::new(RefCell::new(None));
+
+/// What is changing the config right now. Outside a telecommand, it's the OBC itself.
+static CHANGE_SOURCE: Mutex<Cell<ConfigChangeSource>> =
+    Mutex::new(Cell::new(ConfigChangeSource::Onboard));
+
+static AUDIT_HOOK: ConfigAuditHook = ConfigAuditHook(record_change);
+
+/// Run `f` with access to the audit trail, if there is one.
+pub fn with_config_audit<R>(f: impl FnOnce(&mut ConfigAuditLog<AuditPages>) -> R) -> Option<R> {
+    critical_section(|cs| CONFIG_AUDIT.borrow(cs).borrow_mut().as_mut().map(f))
+}
+
+/// Pick up the trail in flash, and start recording. Call once during startup, after
+/// `mission_time::init()`.
+pub fn init() {
+    match ConfigAuditLog::open(AuditPages) {
+        Ok(log) => {
+            rprintln!("Config audit trail: {} changes recorded.", log.total());
+            critical_section(|cs| CONFIG_AUDIT.borrow(cs).replace(Some(log)));
+        }
+        Err(e) => rprintln!("Config audit trail unavailable: {}", e),
+    }
+    get_config_store().set_audit_hook(&AUDIT_HOOK);
+}
+
+/// Run `f`, recording any config change it makes as coming from `source`.
+pub fn with_change_source<R>(source: ConfigChangeSource, f: impl FnOnce() -> R) -> R {
+    let previous = critical_section(|cs| CHANGE_SOURCE.borrow(cs).replace(source));
+    let result = f();
+    critical_section(|cs| CHANGE_SOURCE.borrow(cs).set(previous));
+    result
+}
+
+fn record_change(name: ConfigVariableName, old: ConfigValue, new: ConfigValue) {
+    let record = ConfigAuditRecord {
+        met_ms: mission_elapsed_ms(),
+        name,
+        old,
+        new,
+        source: critical_section(|cs| CHANGE_SOURCE.borrow(cs).get()),
+    };
+    if let Some(Err(e)) = with_config_audit(|log| log.push(&record)) {
+        rprintln!("Config audit record not written: {}", e);
+    }
+}
@@ -0,0 +1,37 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::config_observers::ConfigObservers;
+use cts2_obc_telecommands::get_config_store;
+use rtt_target::rprintln;
+
+use crate::heartbeat;
+
+/// Most config observers that can be registered.
+pub const MAX_CONFIG_OBSERVERS: usize = 8;
+
+static CONFIG_OBSERVERS: Mutex<RefCell<ConfigObservers<MAX_CONFIG_OBSERVERS>>> =
+    Mutex::new(RefCell::new(ConfigObservers::new()));
+
+/// Register the config observers of every module. Call once during startup.
+pub fn init() {
+    critical_section(|cs| {
+        let mut observers = CONFIG_OBSERVERS.borrow(cs).borrow_mut();
+        if let Err(e) = observers.register_all(heartbeat::CONFIG_OBSERVERS) {
+            rprintln!("Config observer registration error: {}", e);
+        }
+    });
+}
+
+/// Call the observers of the config variables that changed. Call periodically from the main
+/// loop. The first call gives every observer its starting value.
+pub fn poll_config_observers() {
+    let due = critical_section(|cs| {
+        CONFIG_OBSERVERS
+            .borrow(cs)
+            .borrow_mut()
+            .poll(get_config_store())
+    });
+    for (observer, value) in due {
+        (observer.on_change)(value);
+    }
+}
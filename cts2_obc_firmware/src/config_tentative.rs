@@ -0,0 +1,52 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::config_tentative::{TentativeChange, TentativeConfig, TentativeError};
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::get_config_store;
+
+use crate::events::raise_event;
+use crate::timekeeping::uptime_ms;
+
+static TENTATIVE_CONFIG: Mutex<RefCell<TentativeConfig>> =
+    Mutex::new(RefCell::new(TentativeConfig::new()));
+
+fn with_tentative_config<R>(f: impl FnOnce(&mut TentativeConfig) -> R) -> R {
+    critical_section(|cs| f(&mut TENTATIVE_CONFIG.borrow(cs).borrow_mut()))
+}
+
+/// Set a config variable, to be reverted after `timeout_s` unless confirmed.
+pub fn set_tentative(
+    name: ConfigVariableName,
+    value: ConfigValue,
+    timeout_s: u32,
+) -> Result<TentativeChange, TentativeError> {
+    let now_ms = uptime_ms();
+    with_tentative_config(|tentative| {
+        tentative.set(
+            get_config_store(),
+            name,
+            value,
+            timeout_s as u64 * 1000,
+            now_ms,
+        )
+    })
+}
+
+pub fn confirm() -> Result<TentativeChange, TentativeError> {
+    with_tentative_config(TentativeConfig::confirm)
+}
+
+/// Forget any tentative change, e.g. when safe mode resets the config to defaults.
+pub fn cancel() {
+    with_tentative_config(TentativeConfig::cancel);
+}
+
+/// Revert a tentative change whose time is up. Called from the main loop, in every mode.
+pub fn poll_tentative_config() {
+    let now_ms = uptime_ms();
+    let reverted = with_tentative_config(|tentative| tentative.poll(get_config_store(), now_ms));
+    if let Some(change) = reverted {
+        raise_event(EventId::ConfigReverted, [change.name.index() as u32, 0]);
+    }
+}
@@ -0,0 +1,133 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::deployment::{
+    DeploymentConfig, DeploymentEvent, DeploymentHardware, DeploymentPhase, DeploymentSequencer,
+    tx_allowed,
+};
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::get_config_store;
+use stm32l4xx_hal::gpio::{ErasedPin, Input, Output, PullDown, PushPull};
+
+use crate::events::raise_event;
+use crate::mission_time::mission_elapsed_ms;
+
+/// Number of deployable antennas.
+pub const ANTENNA_COUNT: usize = 2;
+
+/// Burn-wire outputs (high heats the wire) and feedback switch inputs (high once deployed).
+pub struct AntennaPins {
+    pub burn_wires: [ErasedPin<Output<PushPull>>; ANTENNA_COUNT],
+    pub feedback_switches: [ErasedPin<Input<PullDown>>; ANTENNA_COUNT],
+}
+
+impl DeploymentHardware for AntennaPins {
+    fn antenna_count(&self) -> usize {
+        ANTENNA_COUNT
+    }
+
+    fn set_burn_wire(&mut self, antenna: usize, on: bool) {
+        if on {
+            self.burn_wires[antenna].set_high();
+        } else {
+            self.burn_wires[antenna].set_low();
+        }
+    }
+
+    fn is_deployed(&self, antenna: usize) -> bool {
+        self.feedback_switches[antenna].is_high()
+    }
+}
+
+struct DeploymentState {
+    sequencer: DeploymentSequencer,
+    pins: Option<AntennaPins>,
+}
+
+static DEPLOYMENT_STATE: Mutex<RefCell<DeploymentState>> =
+    Mutex::new(RefCell::new(DeploymentState {
+        sequencer: DeploymentSequencer::new(),
+        pins: None,
+    }));
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "deployment_phase",
+        value_type: TelemetryType::Str,
+        units: "",
+        read: || TelemetryValue::Str(deployment_phase().as_str()),
+    },
+    TelemetryPoint {
+        name: "antennas_deployed",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(antennas_deployed()),
+    },
+];
+
+fn config_u32(name: ConfigVariableName) -> u32 {
+    match get_config_store().get(name) {
+        ConfigValue::U32(value) => value,
+        _ => 0,
+    }
+}
+
+fn deployment_config() -> DeploymentConfig {
+    DeploymentConfig {
+        silence_ms: config_u32(ConfigVariableName::DeploySilenceS) as u64 * 1000,
+        burn_ms: config_u32(ConfigVariableName::DeployBurnMs) as u64,
+        retry_delay_ms: config_u32(ConfigVariableName::DeployRetryDelayMs) as u64,
+        max_attempts: config_u32(ConfigVariableName::DeployMaxAttempts),
+    }
+}
+
+/// Take ownership of the antenna pins. Call once during startup, with the burn wires off.
+pub fn init(pins: AntennaPins) {
+    critical_section(|cs| DEPLOYMENT_STATE.borrow(cs).borrow_mut().pins = Some(pins));
+}
+
+/// Whether the post-deployment silence period has ended. The radio must not transmit before.
+pub fn silence_period_over() -> bool {
+    tx_allowed(mission_elapsed_ms(), &deployment_config())
+}
+
+pub fn deployment_phase() -> DeploymentPhase {
+    critical_section(|cs| DEPLOYMENT_STATE.borrow(cs).borrow().sequencer.phase())
+}
+
+fn antennas_deployed() -> u32 {
+    critical_section(|cs| {
+        let state = DEPLOYMENT_STATE.borrow(cs).borrow();
+        (0..ANTENNA_COUNT)
+            .filter(|&antenna| state.sequencer.is_deployed(antenna))
+            .count() as u32
+    })
+}
+
+/// Advance the antenna deployment sequence, and raise events for its progress.
+/// Call periodically from the main loop.
+pub fn poll_deployment() {
+    let now = mission_elapsed_ms();
+    let config = deployment_config();
+
+    let event = critical_section(|cs| {
+        let mut state = DEPLOYMENT_STATE.borrow(cs).borrow_mut();
+        let DeploymentState { sequencer, pins } = &mut *state;
+        pins.as_mut()
+            .and_then(|pins| sequencer.step(now, &config, pins))
+    });
+
+    match event {
+        Some(DeploymentEvent::SilenceEnded) => {
+            raise_event(EventId::DeploymentSilenceEnded, [(now / 1000) as u32, 0]);
+        }
+        Some(DeploymentEvent::AntennaDeployed { antenna, attempts }) => {
+            raise_event(EventId::AntennaDeployed, [antenna as u32, attempts]);
+        }
+        Some(DeploymentEvent::AntennaFailed { antenna, attempts }) => {
+            raise_event(EventId::AntennaDeployFailed, [antenna as u32, attempts]);
+        }
+        None => {}
+    }
+}
@@ -1,6 +1,16 @@
+use cts2_obc_logic::arming::ArmError;
+use cts2_obc_logic::config_blob::ConfigBlobError;
+use cts2_obc_logic::config_tentative::TentativeError;
+use cts2_obc_logic::fdir::FdirError;
+use cts2_obc_logic::firmware_image::ImageVerifyError;
+use cts2_obc_logic::memory_access::MemoryAccessError;
+use cts2_obc_logic::subscriptions::SubscriptionError;
+use cts2_obc_logic::telemetry::TelemetryError;
 use cts2_obc_telecommands::error::{ConfigError, ParsedTelecommandErr};
 use thiserror::Error;
 
+use crate::nv_store::NvStoreError;
+
 #[derive(Debug, Error)]
 pub enum DispatchCommandErr {
     #[error("Parsed telecommand error")]
@@ -14,4 +24,31 @@ pub enum DispatchCommandErr {
 pub enum ExecuteCommandErr {
     #[error("Config operation error")]
     ConfigError(#[from] ConfigError),
+
+    #[error("Firmware image verification failed")]
+    FirmwareImage(#[from] ImageVerifyError),
+
+    #[error("Memory access error")]
+    MemoryAccess(#[from] MemoryAccessError),
+
+    #[error("Telemetry error")]
+    Telemetry(#[from] TelemetryError),
+
+    #[error("Subscription error")]
+    Subscription(#[from] SubscriptionError),
+
+    #[error("FDIR rule error")]
+    Fdir(#[from] FdirError),
+
+    #[error("Nonvolatile store error")]
+    NvStore(#[from] NvStoreError),
+
+    #[error("Hazardous telecommand not armed")]
+    Arm(#[from] ArmError),
+
+    #[error("Invalid config blob")]
+    ConfigBlob(#[from] ConfigBlobError),
+
+    #[error("Tentative config change error")]
+    Tentative(#[from] TentativeError),
 }
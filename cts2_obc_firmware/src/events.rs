@@ -0,0 +1,41 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::event_log::{Event, EventId, EventLog};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use rtt_target::rprintln;
+
+use crate::timekeeping::uptime_ms;
+
+/// Number of events kept in RAM. Older events are dropped.
+pub const EVENT_LOG_LEN: usize = 32;
+
+static EVENT_LOG: Mutex<RefCell<EventLog<EVENT_LOG_LEN>>> =
+    Mutex::new(RefCell::new(EventLog::new()));
+
+/// Record an event in the event log.
+pub fn raise_event(id: EventId, data: [u32; 2]) {
+    let event = Event {
+        uptime_ms: uptime_ms(),
+        id,
+        data,
+    };
+    rprintln!(
+        "EVENT: {} data=[{:#x}, {:#x}]",
+        id.as_str(),
+        data[0],
+        data[1]
+    );
+    critical_section(|cs| EVENT_LOG.borrow(cs).borrow_mut().push(event));
+}
+
+/// Run `f` with access to the event log.
+pub fn with_event_log<R>(f: impl FnOnce(&EventLog<EVENT_LOG_LEN>) -> R) -> R {
+    critical_section(|cs| f(&EVENT_LOG.borrow(cs).borrow()))
+}
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[TelemetryPoint {
+    name: "event_count",
+    value_type: TelemetryType::U32,
+    units: "",
+    read: || TelemetryValue::U32(with_event_log(|log| log.total_count())),
+}];
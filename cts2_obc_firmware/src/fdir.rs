@@ -0,0 +1,103 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::command_log::CommandSource;
+use cts2_obc_logic::config_audit::ConfigChangeSource;
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::fdir::{FdirEngine, FdirRule};
+use cts2_obc_logic::telemetry::TelemetryValue;
+use cts2_obc_telecommands::fdir::{FdirAction, LimitCheck};
+use cts2_obc_telecommands::mode::OperatingMode;
+use rtt_target::rprintln;
+
+use crate::config_audit::with_change_source;
+use crate::events::raise_event;
+use crate::mode::set_mode;
+use crate::telemetry::find_telemetry_point;
+use crate::umbilical_uart::dispatch_command;
+
+/// Number of FDIR rule slots.
+pub const MAX_FDIR_RULES: usize = 16;
+
+/// Rules loaded at startup, into the first slots. Ground can change them by telecommand.
+const DEFAULT_FDIR_RULES: &[FdirRule] = &[
+    FdirRule {
+        channel: "uart_rx_overflow_bytes",
+        check: LimitCheck::Above(0.0),
+        persistence: 1,
+        action: FdirAction::EventOnly,
+        enabled: true,
+    },
+    // An uncorrectable flash error may mean corrupted code.
+    FdirRule {
+        channel: "seu_flash_ecc_detected",
+        check: LimitCheck::Above(0.0),
+        persistence: 1,
+        action: FdirAction::EnterMode(OperatingMode::Safe),
+        enabled: true,
+    },
+];
+
+static FDIR_ENGINE: Mutex<RefCell<FdirEngine<MAX_FDIR_RULES>>> =
+    Mutex::new(RefCell::new(FdirEngine::new()));
+
+/// Load the default rules. Call once during startup.
+pub fn init() {
+    critical_section(|cs| {
+        if let Err(e) = FDIR_ENGINE.borrow(cs).borrow_mut().load(DEFAULT_FDIR_RULES) {
+            rprintln!("FDIR rule load error: {}", e);
+        }
+    });
+}
+
+/// Run `f` with access to the FDIR rules.
+pub fn with_fdir_engine<R>(f: impl FnOnce(&mut FdirEngine<MAX_FDIR_RULES>) -> R) -> R {
+    critical_section(|cs| f(&mut FDIR_ENGINE.borrow(cs).borrow_mut()))
+}
+
+/// Check the FDIR rules against the current telemetry, and carry out the actions of any
+/// rules that trip. Call periodically from the main loop.
+pub fn poll_fdir() {
+    // Read the telemetry first, so that read callbacks run outside the critical section.
+    let channels: heapless::Vec<&'static str, MAX_FDIR_RULES> = with_fdir_engine(|engine| {
+        engine
+            .rules()
+            .filter(|(_, state)| state.rule.enabled)
+            .map(|(_, state)| state.rule.channel)
+            .collect()
+    });
+    let values: heapless::Vec<(&'static str, Option<TelemetryValue>), MAX_FDIR_RULES> = channels
+        .iter()
+        .map(|&channel| (channel, find_telemetry_point(channel).map(|p| (p.read)())))
+        .collect();
+
+    let trips = with_fdir_engine(|engine| {
+        engine.evaluate(|name| {
+            values
+                .iter()
+                .find(|(channel, _)| *channel == name)
+                .and_then(|(_, value)| *value)
+        })
+    });
+
+    for trip in trips {
+        rprintln!(
+            "FDIR rule {} tripped: {} = {}",
+            trip.slot,
+            trip.channel,
+            trip.value
+        );
+        raise_event(
+            EventId::FdirRuleTripped,
+            [trip.slot as u32, (trip.value as f32).to_bits()],
+        );
+
+        with_change_source(ConfigChangeSource::Fdir, || match trip.action {
+            FdirAction::EventOnly => {}
+            FdirAction::EnterMode(mode) => set_mode(mode),
+            FdirAction::RunCommand(command) => {
+                // The command reports its own errors.
+                let _ = dispatch_command(&command, CommandSource::Onboard);
+            }
+        });
+    }
+}
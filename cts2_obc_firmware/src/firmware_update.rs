@@ -0,0 +1,68 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use cts2_obc_logic::firmware_image::{
+    ImageManifest, ImageVerifyError, MANIFEST_LEN, PUBLIC_KEY_LEN, verify_image,
+};
+
+/// Public key that firmware images must be signed with.
+///
+/// This is the development key. It must be replaced with the flight key before launch.
+/// Generate a key pair and print this array with `cts2_obc_image_tool keygen`.
+const FIRMWARE_SIGNING_PUBLIC_KEY: [u8; PUBLIC_KEY_LEN] = [
+    0x04, 0x64, 0x20, 0x2c, 0xb1, 0x74, 0xce, 0x0e, //
+    0xb2, 0xb2, 0xf3, 0x9d, 0x19, 0x1f, 0x68, 0x2d, //
+    0x6c, 0x2e, 0xc4, 0xd8, 0xe7, 0xbe, 0xc9, 0xf0, //
+    0x5b, 0xef, 0xc0, 0x81, 0x65, 0x4f, 0xd2, 0x25, //
+];
+
+/// Start of the staging slot (flash bank 2), which holds the manifest.
+pub const STAGED_MANIFEST_ADDR: usize = 0x0810_0000;
+
+/// Start of the staged image, one 8 KiB flash page after the manifest.
+pub const STAGED_IMAGE_ADDR: usize = 0x0810_2000;
+
+/// Largest image that fits in the staging slot (end of flash bank 2).
+pub const STAGED_IMAGE_MAX_SIZE: usize = 0x0820_0000 - STAGED_IMAGE_ADDR;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StagedImageState {
+    /// No verification has been attempted since boot.
+    Unverified = 0,
+    /// The staged image passed signature verification and may be booted.
+    Bootable = 1,
+    /// The staged image failed verification and must not be booted.
+    Rejected = 2,
+}
+
+// TODO: Persist the bootable mark in a boot-control record once a bootloader exists.
+static STAGED_IMAGE_STATE: AtomicU8 = AtomicU8::new(StagedImageState::Unverified as u8);
+
+/// Verify the image in the staging slot against its manifest and the signing key.
+///
+/// The image is only marked bootable if verification succeeds. Returns the verified manifest.
+pub fn verify_staged_image() -> Result<ImageManifest, ImageVerifyError> {
+    let result = verify_staged_image_inner();
+    let state = match result {
+        Ok(_) => StagedImageState::Bootable,
+        Err(_) => StagedImageState::Rejected,
+    };
+    STAGED_IMAGE_STATE.store(state as u8, Ordering::Release);
+    result
+}
+
+fn verify_staged_image_inner() -> Result<ImageManifest, ImageVerifyError> {
+    // SAFETY: The staging slot is memory-mapped flash, which is always readable.
+    let manifest_bytes =
+        unsafe { core::slice::from_raw_parts(STAGED_MANIFEST_ADDR as *const u8, MANIFEST_LEN) };
+    let manifest = ImageManifest::from_bytes(manifest_bytes)?;
+
+    // Don't trust the size until the signature has been checked, but don't read past the slot.
+    let image_size = (manifest.image_size as usize).min(STAGED_IMAGE_MAX_SIZE);
+
+    // SAFETY: As above; the length is clamped to the staging slot.
+    let image = unsafe { core::slice::from_raw_parts(STAGED_IMAGE_ADDR as *const u8, image_size) };
+    verify_image(&manifest, image, &FIRMWARE_SIGNING_PUBLIC_KEY)?;
+
+    Ok(manifest)
+}
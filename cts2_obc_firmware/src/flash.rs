@@ -0,0 +1,147 @@
+use cortex_m::interrupt::free as critical_section;
+use thiserror::Error;
+
+/// Start of flash bank 1.
+const BANK1_ADDR: u32 = 0x0800_0000;
+
+/// Page size in dual-bank mode.
+pub const FLASH_PAGE_LEN: u32 = 4096;
+
+const FLASH_KEYR: *mut u32 = 0x4002_2008 as *mut u32;
+const FLASH_SR: *mut u32 = 0x4002_2010 as *mut u32;
+const FLASH_CR: *mut u32 = 0x4002_2014 as *mut u32;
+const FLASH_OPTR: *const u32 = 0x4002_2020 as *const u32;
+
+const FLASH_KEY1: u32 = 0x4567_0123;
+const FLASH_KEY2: u32 = 0xCDEF_89AB;
+
+/// FLASH_SR: operation in progress.
+const SR_BSY: u32 = 1 << 16;
+/// FLASH_SR: every error flag (write 1 to clear).
+const SR_ERRORS: u32 = 0xC3FA;
+
+const CR_PG: u32 = 1 << 0;
+const CR_PER: u32 = 1 << 1;
+const CR_PNB_SHIFT: u32 = 3;
+const CR_STRT: u32 = 1 << 16;
+const CR_LOCK: u32 = 1 << 31;
+
+/// FLASH_OPTR: dual-bank mode.
+const OPTR_DBANK: u32 = 1 << 22;
+
+#[derive(Debug, Error)]
+pub enum FlashError {
+    #[error("Flash is not in dual-bank mode")]
+    NotDualBank,
+
+    #[error("Address is not a whole page or double-word in bank 1")]
+    BadAddress,
+
+    #[error("Flash operation failed (FLASH_SR = {0:#x})")]
+    Operation(u32),
+}
+
+/// Read words from memory-mapped flash.
+pub fn read(addr: u32, out: &mut [u32]) {
+    for (i, word) in out.iter_mut().enumerate() {
+        // SAFETY: Callers only read flash regions reserved in `memory.x`.
+        *word = unsafe { core::ptr::read_volatile((addr as usize + i * 4) as *const u32) };
+    }
+}
+
+fn check_dual_bank() -> Result<(), FlashError> {
+    // SAFETY: FLASH_OPTR is always readable.
+    if unsafe { core::ptr::read_volatile(FLASH_OPTR) } & OPTR_DBANK == 0 {
+        // In single-bank mode the pages are 8 KiB, and erasing one would take out its
+        // neighbour too.
+        return Err(FlashError::NotDualBank);
+    }
+    Ok(())
+}
+
+/// Erase the bank 1 page at `addr`.
+///
+/// The CPU stalls while the page is erased, as the firmware runs from the same bank.
+pub fn erase_page(addr: u32) -> Result<(), FlashError> {
+    check_dual_bank()?;
+    let offset = addr.wrapping_sub(BANK1_ADDR);
+    if !offset.is_multiple_of(FLASH_PAGE_LEN) || offset >= 256 * FLASH_PAGE_LEN {
+        return Err(FlashError::BadAddress);
+    }
+    let page = offset / FLASH_PAGE_LEN;
+
+    // SAFETY: Raw accesses to the FLASH registers. Done in a critical section, so that
+    // nothing else uses the flash controller meanwhile.
+    critical_section(|_| unsafe {
+        unlock();
+        // Clear errors left over from earlier operations.
+        wait_done().ok();
+        core::ptr::write_volatile(FLASH_CR, CR_PER | (page << CR_PNB_SHIFT));
+        core::ptr::write_volatile(FLASH_CR, CR_PER | (page << CR_PNB_SHIFT) | CR_STRT);
+        let result = wait_done();
+        core::ptr::write_volatile(FLASH_CR, 0);
+        lock();
+        result
+    })
+}
+
+/// Program `words` into erased flash at `addr`, one double-word at a time. Double-words that
+/// are all ones are left erased.
+pub fn program(addr: u32, words: &[u32]) -> Result<(), FlashError> {
+    check_dual_bank()?;
+    if !addr.is_multiple_of(8) || !words.len().is_multiple_of(2) {
+        return Err(FlashError::BadAddress);
+    }
+
+    // SAFETY: As in `erase_page`. Callers only program flash regions reserved in `memory.x`.
+    critical_section(|_| unsafe {
+        unlock();
+        wait_done().ok();
+        core::ptr::write_volatile(FLASH_CR, CR_PG);
+        let mut result = Ok(());
+        for (i, pair) in words.chunks_exact(2).enumerate() {
+            if pair == [0xFFFF_FFFF; 2] {
+                continue;
+            }
+            let ptr = (addr as usize + i * 8) as *mut u32;
+            core::ptr::write_volatile(ptr, pair[0]);
+            core::ptr::write_volatile(ptr.add(1), pair[1]);
+            result = wait_done();
+            if result.is_err() {
+                break;
+            }
+        }
+        core::ptr::write_volatile(FLASH_CR, 0);
+        lock();
+        result
+    })
+}
+
+unsafe fn unlock() {
+    unsafe {
+        if core::ptr::read_volatile(FLASH_CR) & CR_LOCK != 0 {
+            core::ptr::write_volatile(FLASH_KEYR, FLASH_KEY1);
+            core::ptr::write_volatile(FLASH_KEYR, FLASH_KEY2);
+        }
+    }
+}
+
+unsafe fn lock() {
+    unsafe {
+        let cr = core::ptr::read_volatile(FLASH_CR);
+        core::ptr::write_volatile(FLASH_CR, cr | CR_LOCK);
+    }
+}
+
+/// Wait for the current operation, then check and clear its error flags.
+unsafe fn wait_done() -> Result<(), FlashError> {
+    unsafe {
+        while core::ptr::read_volatile(FLASH_SR) & SR_BSY != 0 {}
+        let errors = core::ptr::read_volatile(FLASH_SR) & SR_ERRORS;
+        if errors != 0 {
+            core::ptr::write_volatile(FLASH_SR, errors);
+            return Err(FlashError::Operation(errors));
+        }
+    }
+    Ok(())
+}
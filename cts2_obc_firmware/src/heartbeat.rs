@@ -0,0 +1,72 @@
+use core::cell::Cell;
+use core::sync::atomic::{AtomicU32, Ordering};
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::config_observers::ConfigObserver;
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use rtt_target::rprintln;
+
+use crate::timekeeping::uptime_ms;
+use crate::umbilical_uart::send_umbilical_uart;
+
+/// Longest the main loop sleeps between passes, so that the other pollers keep their pace
+/// when the heartbeat is slow.
+const MAX_LOOP_DELAY_MS: u64 = 500;
+
+/// Time between heartbeats, from `heartbeat_ms`. 0 turns the heartbeat off.
+static HEARTBEAT_PERIOD_MS: AtomicU32 = AtomicU32::new(0);
+
+static NEXT_HEARTBEAT_MS: Mutex<Cell<u64>> = Mutex::new(Cell::new(0));
+
+static HEARTBEAT_COUNT: AtomicU32 = AtomicU32::new(0);
+
+pub const CONFIG_OBSERVERS: &[ConfigObserver] = &[ConfigObserver {
+    name: ConfigVariableName::HeartbeatMs,
+    on_change: set_heartbeat_period,
+}];
+
+/// Use a new `heartbeat_ms`. The next heartbeat goes out straight away.
+fn set_heartbeat_period(value: ConfigValue) {
+    if let ConfigValue::U32(period_ms) = value {
+        HEARTBEAT_PERIOD_MS.store(period_ms, Ordering::Relaxed);
+        critical_section(|cs| NEXT_HEARTBEAT_MS.borrow(cs).set(0));
+        rprintln!("Heartbeat period {} ms", period_ms);
+    }
+}
+
+/// Send a heartbeat on the umbilical UART when one is due. Call periodically from the main
+/// loop.
+pub fn poll_heartbeat() {
+    let period_ms = HEARTBEAT_PERIOD_MS.load(Ordering::Relaxed) as u64;
+    if period_ms == 0 {
+        return;
+    }
+    let now = uptime_ms();
+    let due = critical_section(|cs| {
+        let next = NEXT_HEARTBEAT_MS.borrow(cs);
+        if now < next.get() {
+            return false;
+        }
+        next.set(now + period_ms);
+        true
+    });
+    if !due {
+        return;
+    }
+
+    let count = HEARTBEAT_COUNT.fetch_add(1, Ordering::Relaxed);
+    rprintln!("Heartbeat {} uptime {} ms", count, now);
+    send_umbilical_uart(b"HEARTBEAT\r\n");
+}
+
+/// How long the main loop can sleep: until the next heartbeat, but at most
+/// `MAX_LOOP_DELAY_MS`.
+pub fn loop_delay_ms() -> u16 {
+    let delay = match HEARTBEAT_PERIOD_MS.load(Ordering::Relaxed) {
+        0 => MAX_LOOP_DELAY_MS,
+        _ => {
+            let next = critical_section(|cs| NEXT_HEARTBEAT_MS.borrow(cs).get());
+            next.saturating_sub(uptime_ms()).min(MAX_LOOP_DELAY_MS)
+        }
+    };
+    delay as u16
+}
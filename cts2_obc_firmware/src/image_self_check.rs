@@ -0,0 +1,92 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::image_crc::{
+    IMAGE_INFO_FLASH_OFFSET, ImageCrcMonitor, ImageCrcReport, ImageCrcStatus,
+};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+
+use crate::events::raise_event;
+use crate::timekeeping::uptime_ms;
+
+/// Bytes of flash fed into the image CRC per call to `poll_image_crc`.
+const IMAGE_CRC_BYTES_PER_POLL: usize = 4096;
+
+/// Start of the application image (the vector table).
+const FLASH_ORIGIN: usize = 0x0800_0000;
+
+unsafe extern "C" {
+    /// Image info block placed by `memory.x`: `[image length, expected CRC-32]`.
+    static __image_info: [u32; 2];
+}
+
+static IMAGE_CRC_MONITOR: Mutex<RefCell<ImageCrcMonitor>> =
+    Mutex::new(RefCell::new(ImageCrcMonitor::new()));
+
+/// Read the image length and the expected CRC from the image info block.
+fn read_image_info() -> (usize, u32) {
+    let info = &raw const __image_info as *const u32;
+    // SAFETY: The linker script always places the image info block in flash.
+    // The reads are volatile because the CRC is patched in after the build, so the
+    // compiler must not assume it still holds the placeholder value.
+    let (image_len, expected_crc) = unsafe {
+        (
+            core::ptr::read_volatile(info),
+            core::ptr::read_volatile(info.add(1)),
+        )
+    };
+    (
+        (image_len as usize).min(IMAGE_INFO_FLASH_OFFSET),
+        expected_crc,
+    )
+}
+
+/// Advance the image CRC self-check by one step. Call periodically from the main loop.
+///
+/// When a pass finds that the image differs from the build, an event is raised.
+pub fn poll_image_crc() {
+    let (image_len, expected_crc) = read_image_info();
+    // SAFETY: The image lies in memory-mapped flash, and `read_image_info` caps the length at
+    // the image info block, so the slice stays within the FLASH region and the flash pages
+    // reserved after it (the config audit trail and the NV store).
+    let image = unsafe { core::slice::from_raw_parts(FLASH_ORIGIN as *const u8, image_len) };
+
+    let now_ms = uptime_ms();
+    let report = critical_section(|cs| {
+        IMAGE_CRC_MONITOR.borrow(cs).borrow_mut().step(
+            image,
+            expected_crc,
+            IMAGE_CRC_BYTES_PER_POLL,
+            now_ms,
+        )
+    });
+
+    if let Some(report) = report
+        && report.status == ImageCrcStatus::Mismatch
+    {
+        raise_event(
+            EventId::ImageCrcMismatch,
+            [report.expected_crc, report.computed_crc],
+        );
+    }
+}
+
+/// Result of the most recently completed image CRC pass.
+pub fn last_image_crc_report() -> ImageCrcReport {
+    critical_section(|cs| *IMAGE_CRC_MONITOR.borrow(cs).borrow().last_report())
+}
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "image_crc_status",
+        value_type: TelemetryType::Str,
+        units: "",
+        read: || TelemetryValue::Str(last_image_crc_report().status.as_str()),
+    },
+    TelemetryPoint {
+        name: "image_crc_passes",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(last_image_crc_report().pass_count),
+    },
+];
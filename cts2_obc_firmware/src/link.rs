@@ -0,0 +1,17 @@
+use cts2_obc_logic::link::Link;
+
+use crate::radio::radio_transmit;
+use crate::umbilical_uart::send_umbilical_uart;
+
+/// Send data on `link`. Blocks during transmission.
+///
+/// Data for the radio is dropped while it may not transmit (see `radio_transmit`). The
+/// umbilical UART is always allowed.
+pub fn send_on_link(link: Link, data: &[u8]) {
+    match link {
+        Link::Umbilical => send_umbilical_uart(data),
+        Link::Radio => {
+            let _ = radio_transmit(data);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use core::cell::RefCell;
+use core::mem::MaybeUninit;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::link_loss::{
+    LINK_LOSS_RECORD_WORDS, LinkLossAction, LinkLossConfig, LinkLossWatchdog,
+};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::get_config_store;
+use rtt_target::rprintln;
+
+use crate::events::raise_event;
+use crate::mission_time::mission_elapsed_ms;
+use crate::radio;
+
+/// Saved watchdog, in RAM that cortex-m-rt does not initialize, so that it survives a reset.
+#[unsafe(link_section = ".uninit.LINK_LOSS_RECORD")]
+static mut LINK_LOSS_RECORD: MaybeUninit<[u32; LINK_LOSS_RECORD_WORDS]> = MaybeUninit::uninit();
+
+/// Replaced by the saved watchdog in `init`.
+static LINK_LOSS_WATCHDOG: Mutex<RefCell<LinkLossWatchdog>> =
+    Mutex::new(RefCell::new(LinkLossWatchdog::new(0)));
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[TelemetryPoint {
+    name: "command_silence_ms",
+    value_type: TelemetryType::U64,
+    units: "ms",
+    read: || {
+        let now = mission_elapsed_ms();
+        TelemetryValue::U64(critical_section(|cs| {
+            LINK_LOSS_WATCHDOG.borrow(cs).borrow().silence_ms(now)
+        }))
+    },
+}];
+
+fn config_ms(name: ConfigVariableName) -> u64 {
+    match get_config_store().get(name) {
+        ConfigValue::U32(seconds) => seconds as u64 * 1000,
+        _ => 0,
+    }
+}
+
+fn link_loss_config() -> LinkLossConfig {
+    LinkLossConfig {
+        reset_radio_ms: config_ms(ConfigVariableName::LinkLossResetRadioS),
+        backup_frequency_ms: config_ms(ConfigVariableName::LinkLossBackupFrequencyS),
+        reboot_ms: config_ms(ConfigVariableName::LinkLossRebootS),
+    }
+}
+
+fn read_link_loss_record() -> Option<LinkLossWatchdog> {
+    let words = &raw const LINK_LOSS_RECORD as *const u32;
+    let mut out = [0u32; LINK_LOSS_RECORD_WORDS];
+    for (i, word) in out.iter_mut().enumerate() {
+        // SAFETY: The record is plain words in RAM. After a power cycle it holds random
+        // data, which `from_words` rejects.
+        *word = unsafe { core::ptr::read_volatile(words.add(i)) };
+    }
+    LinkLossWatchdog::from_words(&out)
+}
+
+fn write_link_loss_record(watchdog: &LinkLossWatchdog) {
+    let words = &raw mut LINK_LOSS_RECORD as *mut u32;
+    for (i, word) in watchdog.to_words().into_iter().enumerate() {
+        // SAFETY: Only this module accesses the record, inside a critical section.
+        unsafe { core::ptr::write_volatile(words.add(i), word) };
+    }
+}
+
+/// Pick up the watchdog left by the previous boot, so that the silence and the actions already
+/// run carry over. Call once during startup, after `mission_time::init`.
+///
+/// After a power cycle there is none, and the timer starts now.
+pub fn init() {
+    let watchdog =
+        read_link_loss_record().unwrap_or_else(|| LinkLossWatchdog::new(mission_elapsed_ms()));
+    if watchdog.uses_backup_frequency() {
+        radio::use_backup_frequency();
+    }
+    critical_section(|cs| {
+        LINK_LOSS_WATCHDOG.borrow(cs).replace(watchdog);
+        write_link_loss_record(&watchdog);
+    });
+}
+
+/// Restart the link-loss timer. Call for every accepted telecommand.
+pub fn command_received() {
+    let now = mission_elapsed_ms();
+    critical_section(|cs| {
+        let mut watchdog = LINK_LOSS_WATCHDOG.borrow(cs).borrow_mut();
+        watchdog.command_received(now);
+        write_link_loss_record(&watchdog);
+    });
+}
+
+/// Run the next recovery action, if no telecommand has arrived for long enough.
+/// Call periodically from the main loop.
+pub fn poll_link_loss() {
+    let now = mission_elapsed_ms();
+    let config = link_loss_config();
+    let (action, silence_ms) = critical_section(|cs| {
+        let mut watchdog = LINK_LOSS_WATCHDOG.borrow(cs).borrow_mut();
+        let action = watchdog.poll(now, &config);
+        // Saved before the action runs, so that the reboot action doesn't run again.
+        write_link_loss_record(&watchdog);
+        (action, watchdog.silence_ms(now))
+    });
+    let Some(action) = action else {
+        return;
+    };
+
+    rprintln!("Link loss: {}", action.as_str());
+    raise_event(
+        EventId::LinkLossAction,
+        [action as u32, (silence_ms / 1000) as u32],
+    );
+    match action {
+        LinkLossAction::ResetRadio => radio::reset_radio(),
+        LinkLossAction::SwitchToBackupFrequency => radio::use_backup_frequency(),
+        LinkLossAction::RebootObc => cortex_m::peripheral::SCB::sys_reset(),
+    }
+}
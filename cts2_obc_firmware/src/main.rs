@@ -7,18 +7,44 @@ use core::cell::RefCell;
 use cortex_m::interrupt::Mutex;
 use cortex_m::interrupt::free as critical_section;
 use cortex_m::peripheral::NVIC;
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_telecommands::mode::OperatingMode;
 use rtt_target::{rprintln, rtt_init_print};
 use static_cell::StaticCell;
 use stm32l4xx_hal::{
     self as stm32_hal,
     dma::CircReadDma as _,
-    gpio::{Output, PushPull, gpioc::PC7},
+    gpio::{Output, PinState, PushPull, gpioc::PC7},
     prelude::*,
 };
 
+mod arming;
+mod beacon;
+mod boot_loop;
+mod command_log;
+mod config_audit;
+mod config_observers;
+mod config_tentative;
+mod deployment;
 mod error;
+mod events;
+mod fdir;
+mod firmware_update;
+mod flash;
+mod heartbeat;
+mod image_self_check;
+mod link;
+mod link_loss;
+mod memory_scrub;
+mod mission_time;
+mod mode;
+mod nv_store;
+mod radio;
+mod subscriptions;
 mod telecommand_implementation;
+mod telemetry;
 mod timekeeping;
+mod tx_inhibit;
 mod umbilical_uart;
 
 use umbilical_uart::{process_umbilical_commands, send_umbilical_uart};
@@ -43,6 +69,14 @@ fn entry_point() -> ! {
     rtt_init_print!();
     rprintln!("System startup...");
 
+    // Count this boot first, so that a crash anywhere in startup counts as a fast reset.
+    let (boot_mode, boot_record) = boot_loop::check_boot_loop();
+    rprintln!(
+        "Boot {} ({} fast resets in a row).",
+        boot_record.boot_count,
+        boot_record.fast_reset_count
+    );
+
     let cortex_peripherals = cortex_m::Peripherals::take().unwrap();
     let peripheral = stm32_hal::stm32::Peripherals::take().unwrap();
 
@@ -68,15 +102,58 @@ fn entry_point() -> ! {
         rprintln!("Timekeeping initialized.");
     }
 
+    mission_time::init();
+    config_audit::init();
+    nv_store::init();
+    tx_inhibit::init();
+    link_loss::init();
+
+    if boot_mode == OperatingMode::Safe {
+        rprintln!("Boot loop detected. Starting in safe mode.");
+        events::raise_event(
+            EventId::BootLoopDetected,
+            [boot_record.fast_reset_count, boot_record.boot_count],
+        );
+        mode::set_mode(OperatingMode::Safe);
+    }
+
+    telemetry::init();
+    config_observers::init();
+
     let timer = stm32_hal::delay::Delay::new(cortex_peripherals.SYST, clocks);
 
     // --- GPIO ---
     let mut gpioc = peripheral.GPIOC.split(&mut rcc.ahb2);
     let mut gpiod = peripheral.GPIOD.split(&mut rcc.ahb2);
+    let mut gpioe = peripheral.GPIOE.split(&mut rcc.ahb2);
     let led = gpioc
         .pc7
         .into_push_pull_output(&mut gpioc.moder, &mut gpioc.otyper);
 
+    // TODO: Confirm the burn-wire and feedback switch pins against the OBC schematic.
+    deployment::init(deployment::AntennaPins {
+        burn_wires: [
+            gpioe
+                .pe2
+                .into_push_pull_output_in_state(&mut gpioe.moder, &mut gpioe.otyper, PinState::Low)
+                .erase(),
+            gpioe
+                .pe3
+                .into_push_pull_output_in_state(&mut gpioe.moder, &mut gpioe.otyper, PinState::Low)
+                .erase(),
+        ],
+        feedback_switches: [
+            gpioe
+                .pe4
+                .into_pull_down_input(&mut gpioe.moder, &mut gpioe.pupdr)
+                .erase(),
+            gpioe
+                .pe5
+                .into_pull_down_input(&mut gpioe.moder, &mut gpioe.pupdr)
+                .erase(),
+        ],
+    });
+
     // --- Move peripherals into global statics ---
     critical_section(|cs| {
         PERIPHERAL_GREEN_LED.borrow(cs).replace(Some(led));
@@ -123,7 +200,7 @@ fn entry_point() -> ! {
     send_umbilical_uart(b"USART2 ready. Buffered RX active.\r\n");
 
     // --- Main loop ---
-    let mut i = 0u32;
+    let mut nominal_services_started = false;
     loop {
         toggle_led();
 
@@ -132,16 +209,59 @@ fn entry_point() -> ! {
         // Periodically check for incoming commands
         process_umbilical_commands();
 
-        // Heartbeat message
-        let uptime = get_sys_uptime_ms();
-        rprintln!("Heartbeat {} uptime {} ms", i, uptime);
-        send_umbilical_uart(b"HEARTBEAT\r\n");
+        boot_loop::poll_boot_stability();
+
+        mission_time::poll_mission_time();
+
+        // Runs in safe mode too, as the ground cannot reach the OBC until the antennas are out.
+        deployment::poll_deployment();
+
+        beacon::poll_beacon();
+
+        // Let modules pick up config changes.
+        config_observers::poll_config_observers();
+
+        // Put back a tentative config change that the ground didn't confirm in time.
+        config_tentative::poll_tentative_config();
+
+        // Try to recover the link if no telecommand has arrived for a long time.
+        link_loss::poll_link_loss();
 
-        timer_delay_ms(500_u16);
-        i = i.wrapping_add(1);
+        // Safe mode keeps only the command path.
+        if mode::current_mode() == OperatingMode::Nominal {
+            if !nominal_services_started {
+                start_nominal_services();
+                nominal_services_started = true;
+            }
+
+            // Check a slice of the firmware image for corruption.
+            image_self_check::poll_image_crc();
+
+            // Read a slice of flash and SRAM2, so that bit flips are found early.
+            memory_scrub::poll_memory_scrub();
+
+            // Send any subscribed telemetry that is due.
+            subscriptions::poll_subscriptions();
+
+            // Check telemetry against the FDIR limits.
+            fdir::poll_fdir();
+        }
+
+        heartbeat::poll_heartbeat();
+
+        timer_delay_ms(heartbeat::loop_delay_ms());
     }
 }
 
+/// Start the services that only run in nominal mode. Skipped while in safe mode, in case one
+/// of them is what crashed.
+fn start_nominal_services() {
+    memory_scrub::init();
+    rprintln!("Memory error reporting enabled.");
+
+    fdir::init();
+}
+
 fn toggle_led() {
     critical_section(|cs| {
         if let Some(ref mut led) = *PERIPHERAL_GREEN_LED.borrow(cs).borrow_mut() {
@@ -158,10 +278,6 @@ fn timer_delay_ms(ms: u16) {
     });
 }
 
-pub fn get_sys_uptime_ms() -> u64 {
-    timekeeping::uptime_ms()
-}
-
 #[inline(never)]
 #[panic_handler]
 fn panic(info: &core::panic::PanicInfo) -> ! {
@@ -0,0 +1,278 @@
+use core::cell::RefCell;
+use core::sync::atomic::{AtomicU32, Ordering};
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cortex_m::peripheral::NVIC;
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::memory_scrub::{
+    FlashEccStatus, MemoryErrorCount, ScrubCursor, ScrubRegion, SeuStats,
+};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use stm32l4xx_hal::{self as stm32_hal, interrupt};
+
+use crate::events::raise_event;
+
+/// Bytes of memory scrubbed per call to `poll_memory_scrub`.
+const SCRUB_BYTES_PER_POLL: u32 = 4096;
+
+const FLASH_ECCR: *mut u32 = 0x4002_2018 as *mut u32;
+const FLASH_OPTR: *const u32 = 0x4002_2020 as *const u32;
+const SYSCFG_CFGR2: *mut u32 = 0x4001_001C as *mut u32;
+const RCC_APB2ENR: *mut u32 = 0x4002_1060 as *mut u32;
+
+/// FLASH_ECCR: ECC correction interrupt enable.
+const ECCR_ECCCIE: u32 = 1 << 24;
+/// FLASH_OPTR: SRAM2 parity check disable (the check is on when this bit is clear).
+const OPTR_SRAM2_PE: u32 = 1 << 24;
+/// SYSCFG_CFGR2: SRAM2 parity error flag (write 1 to clear).
+const CFGR2_SPF: u32 = 1 << 8;
+/// RCC_APB2ENR: SYSCFG clock enable.
+const APB2ENR_SYSCFGEN: u32 = 1 << 0;
+
+const SRAM2_START: u32 = 0x1000_0000;
+const SRAM2_LEN: u32 = 0x0001_0000;
+
+/// Memory that the scrubber walks. SRAM1 and SRAM3 have no parity or ECC.
+const SCRUB_REGIONS: &[ScrubRegion] = &[
+    ScrubRegion {
+        name: "flash_bank1",
+        start: 0x0800_0000,
+        len: 0x0010_0000,
+        write_back: false,
+    },
+    ScrubRegion {
+        name: "flash_bank2",
+        start: 0x0810_0000,
+        len: 0x0010_0000,
+        write_back: false,
+    },
+    // SRAM2 is not used by the linker script, so rewriting it in place is safe.
+    ScrubRegion {
+        name: "sram2",
+        start: SRAM2_START,
+        len: SRAM2_LEN,
+        write_back: true,
+    },
+];
+
+// Counters are atomics rather than `Mutex`es because they are updated from the NMI handler,
+// which a critical section does not mask.
+static FLASH_ECC_CORRECTED_COUNT: AtomicU32 = AtomicU32::new(0);
+static FLASH_ECC_CORRECTED_ADDR: AtomicU32 = AtomicU32::new(0);
+static FLASH_ECC_DETECTED_COUNT: AtomicU32 = AtomicU32::new(0);
+static FLASH_ECC_DETECTED_ADDR: AtomicU32 = AtomicU32::new(0);
+static SRAM2_PARITY_COUNT: AtomicU32 = AtomicU32::new(0);
+static SRAM2_PARITY_ADDR: AtomicU32 = AtomicU32::new(0);
+static UNKNOWN_NMI_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Start of the chunk being scrubbed, to attribute SRAM2 parity errors to.
+static SCRUB_CHUNK_ADDR: AtomicU32 = AtomicU32::new(0);
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "seu_flash_ecc_corrected",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(FLASH_ECC_CORRECTED_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "seu_flash_ecc_detected",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(FLASH_ECC_DETECTED_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "seu_sram2_parity",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(SRAM2_PARITY_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "scrub_passes",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(seu_stats().scrub_pass_count),
+    },
+];
+
+struct ScrubState {
+    cursor: ScrubCursor,
+    /// Counts at the time events were last raised.
+    reported: [u32; 3],
+}
+
+static SCRUB_STATE: Mutex<RefCell<ScrubState>> = Mutex::new(RefCell::new(ScrubState {
+    cursor: ScrubCursor::new(),
+    reported: [0; 3],
+}));
+
+/// Enable the flash ECC and SRAM2 parity error reporting. Call once during startup.
+pub fn init() {
+    // SAFETY: Raw accesses to the SYSCFG, FLASH and RCC registers. The read-modify-writes are
+    // done in a critical section, and no other code touches these bits.
+    critical_section(|_| unsafe {
+        let apb2enr = core::ptr::read_volatile(RCC_APB2ENR);
+        core::ptr::write_volatile(RCC_APB2ENR, apb2enr | APB2ENR_SYSCFGEN);
+
+        // SRAM2 content is random after power-on, with invalid parity. Write it all once so
+        // that scrubbing it doesn't report an error for every word.
+        for i in 0..(SRAM2_LEN / 4) {
+            core::ptr::write_volatile((SRAM2_START + i * 4) as *mut u32, 0);
+        }
+        core::ptr::write_volatile(SYSCFG_CFGR2, CFGR2_SPF);
+    });
+
+    // Errors found while booting are kept.
+    handle_flash_ecc();
+    // SAFETY: As above. Writing 0 to the flags has no effect.
+    unsafe { core::ptr::write_volatile(FLASH_ECCR, ECCR_ECCCIE) };
+
+    // SAFETY: The FLASH handler only touches atomics.
+    unsafe { NVIC::unmask(stm32_hal::stm32::Interrupt::FLASH) };
+}
+
+/// Record and clear any flash ECC error. Returns true if there was one.
+fn handle_flash_ecc() -> bool {
+    // SAFETY: Raw access to FLASH_ECCR. The flags are write-1-to-clear, so writing back only
+    // the flags that were read cannot lose a newer error.
+    let eccr = unsafe { core::ptr::read_volatile(FLASH_ECCR) };
+    let status = FlashEccStatus::from_eccr(eccr);
+    if !status.corrected && !status.detected {
+        return false;
+    }
+
+    if status.corrected {
+        FLASH_ECC_CORRECTED_ADDR.store(status.address, Ordering::Relaxed);
+        FLASH_ECC_CORRECTED_COUNT.fetch_add(1, Ordering::Release);
+    }
+    if status.detected {
+        FLASH_ECC_DETECTED_ADDR.store(status.address, Ordering::Relaxed);
+        FLASH_ECC_DETECTED_COUNT.fetch_add(1, Ordering::Release);
+    }
+
+    unsafe {
+        core::ptr::write_volatile(
+            FLASH_ECCR,
+            (eccr & ECCR_ECCCIE) | FlashEccStatus::clear_flags_mask(eccr),
+        );
+    }
+    true
+}
+
+/// Record and clear an SRAM2 parity error. Returns true if there was one.
+fn handle_sram2_parity() -> bool {
+    // SAFETY: Raw access to SYSCFG_CFGR2. Writing 0 to the other bits has no effect.
+    let cfgr2 = unsafe { core::ptr::read_volatile(SYSCFG_CFGR2) };
+    if cfgr2 & CFGR2_SPF == 0 {
+        return false;
+    }
+
+    SRAM2_PARITY_ADDR.store(SCRUB_CHUNK_ADDR.load(Ordering::Relaxed), Ordering::Relaxed);
+    SRAM2_PARITY_COUNT.fetch_add(1, Ordering::Release);
+    unsafe { core::ptr::write_volatile(SYSCFG_CFGR2, CFGR2_SPF) };
+    true
+}
+
+/// Single-bit flash ECC errors are corrected, and reported through the FLASH interrupt.
+#[interrupt]
+fn FLASH() {
+    handle_flash_ecc();
+}
+
+/// Double-bit flash ECC errors and SRAM2 parity errors raise an NMI.
+///
+/// The NMI can preempt critical sections, so this must only touch atomics and the error
+/// flag registers.
+#[cortex_m_rt::exception]
+unsafe fn NonMaskableInt() {
+    let flash = handle_flash_ecc();
+    let sram2 = handle_sram2_parity();
+    if !flash && !sram2 {
+        UNKNOWN_NMI_COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Read (and, where enabled, rewrite) a chunk so that the hardware checks every word.
+///
+/// # Safety
+/// The chunk must lie in readable memory, and writable memory when `write_back` is set.
+unsafe fn scrub_words(addr: u32, len: u32, write_back: bool) {
+    for word_addr in (addr..addr + len).step_by(4) {
+        let ptr = word_addr as *mut u32;
+        unsafe {
+            let word = core::ptr::read_volatile(ptr);
+            if write_back {
+                core::ptr::write_volatile(ptr, word);
+            }
+        }
+    }
+}
+
+/// Scrub the next chunk of memory, and raise events for any new memory errors.
+/// Call periodically from the main loop.
+pub fn poll_memory_scrub() {
+    let chunk = critical_section(|cs| {
+        SCRUB_STATE
+            .borrow(cs)
+            .borrow_mut()
+            .cursor
+            .next_chunk(SCRUB_REGIONS, SCRUB_BYTES_PER_POLL)
+    });
+
+    if let Some(chunk) = chunk {
+        SCRUB_CHUNK_ADDR.store(chunk.addr, Ordering::Relaxed);
+        // The read and write-back of each word must not be split by code that writes SRAM2.
+        // SAFETY: The scrub regions are mapped memory, and only SRAM2 is written back.
+        critical_section(|_| unsafe {
+            scrub_words(chunk.addr, chunk.len, chunk.region.write_back)
+        });
+    }
+
+    report_new_errors();
+}
+
+/// Raise an event for each kind of memory error whose count went up since the last call.
+fn report_new_errors() {
+    let stats = seu_stats();
+    let counts = [
+        (EventId::FlashEccCorrected, stats.flash_ecc_corrected),
+        (EventId::FlashEccDetected, stats.flash_ecc_detected),
+        (EventId::Sram2ParityError, stats.sram2_parity),
+    ];
+
+    let reported = critical_section(|cs| {
+        let mut state = SCRUB_STATE.borrow(cs).borrow_mut();
+        let reported = state.reported;
+        for (i, (_, count)) in counts.iter().enumerate() {
+            state.reported[i] = count.count;
+        }
+        reported
+    });
+
+    for ((id, count), reported_count) in counts.into_iter().zip(reported) {
+        if count.count != reported_count {
+            raise_event(id, [count.last_address, count.count]);
+        }
+    }
+}
+
+/// Snapshot of the single-event-upset statistics since boot.
+pub fn seu_stats() -> SeuStats {
+    let count = |count: &AtomicU32, addr: &AtomicU32| MemoryErrorCount {
+        count: count.load(Ordering::Acquire),
+        last_address: addr.load(Ordering::Relaxed),
+    };
+
+    // SAFETY: FLASH_OPTR is always readable.
+    let optr = unsafe { core::ptr::read_volatile(FLASH_OPTR) };
+
+    SeuStats {
+        flash_ecc_corrected: count(&FLASH_ECC_CORRECTED_COUNT, &FLASH_ECC_CORRECTED_ADDR),
+        flash_ecc_detected: count(&FLASH_ECC_DETECTED_COUNT, &FLASH_ECC_DETECTED_ADDR),
+        sram2_parity: count(&SRAM2_PARITY_COUNT, &SRAM2_PARITY_ADDR),
+        unknown_nmi_count: UNKNOWN_NMI_COUNT.load(Ordering::Relaxed),
+        sram2_parity_check_enabled: optr & OPTR_SRAM2_PE == 0,
+        scrub_pass_count: critical_section(|cs| {
+            SCRUB_STATE.borrow(cs).borrow().cursor.pass_count()
+        }),
+    }
+}
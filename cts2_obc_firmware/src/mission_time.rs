@@ -0,0 +1,63 @@
+use core::cell::Cell;
+use core::mem::MaybeUninit;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::deployment::{MET_RECORD_WORDS, MetRecord};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+
+use crate::timekeeping::uptime_ms;
+
+/// Mission-elapsed time record, in RAM that cortex-m-rt does not initialize, so that it
+/// survives a reset.
+#[unsafe(link_section = ".uninit.MET_RECORD")]
+static mut MET_RECORD: MaybeUninit<[u32; MET_RECORD_WORDS]> = MaybeUninit::uninit();
+
+/// Mission-elapsed time when this boot started.
+static MET_AT_BOOT_MS: Mutex<Cell<u64>> = Mutex::new(Cell::new(0));
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[TelemetryPoint {
+    name: "met_ms",
+    value_type: TelemetryType::U64,
+    units: "ms",
+    read: || TelemetryValue::U64(mission_elapsed_ms()),
+}];
+
+fn read_met_record() -> Option<MetRecord> {
+    let words = &raw const MET_RECORD as *const u32;
+    let mut out = [0u32; MET_RECORD_WORDS];
+    for (i, word) in out.iter_mut().enumerate() {
+        // SAFETY: The record is plain words in RAM. After a power cycle it holds random
+        // data, which `from_words` rejects.
+        *word = unsafe { core::ptr::read_volatile(words.add(i)) };
+    }
+    MetRecord::from_words(&out)
+}
+
+fn write_met_record(record: &MetRecord) {
+    let words = &raw mut MET_RECORD as *mut u32;
+    for (i, word) in record.to_words().into_iter().enumerate() {
+        // SAFETY: Only this module accesses the record, from the main thread.
+        unsafe { core::ptr::write_volatile(words.add(i), word) };
+    }
+}
+
+/// Pick up the mission-elapsed time left by the previous boot. Call once during startup.
+///
+/// After a power cycle there is none, and the mission clock starts from zero.
+pub fn init() {
+    let met_at_boot = read_met_record().map_or(0, |record| record.mission_elapsed_ms);
+    critical_section(|cs| MET_AT_BOOT_MS.borrow(cs).set(met_at_boot));
+    poll_mission_time();
+}
+
+/// Time since the first boot after the last power cycle, across resets.
+pub fn mission_elapsed_ms() -> u64 {
+    critical_section(|cs| MET_AT_BOOT_MS.borrow(cs).get()) + uptime_ms()
+}
+
+/// Save the mission-elapsed time, so that it carries over a reset.
+/// Call periodically from the main loop.
+pub fn poll_mission_time() {
+    write_met_record(&MetRecord {
+        mission_elapsed_ms: mission_elapsed_ms(),
+    });
+}
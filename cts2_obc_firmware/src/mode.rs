@@ -0,0 +1,40 @@
+use core::sync::atomic::{AtomicU8, Ordering};
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use cts2_obc_telecommands::get_config_store;
+use cts2_obc_telecommands::mode::OperatingMode;
+
+use crate::arming;
+use crate::config_tentative;
+use crate::events::raise_event;
+
+static OPERATING_MODE: AtomicU8 = AtomicU8::new(OperatingMode::Nominal as u8);
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[TelemetryPoint {
+    name: "mode",
+    value_type: TelemetryType::Str,
+    units: "",
+    read: || TelemetryValue::Str(current_mode().as_str()),
+}];
+
+pub fn current_mode() -> OperatingMode {
+    OperatingMode::from_u8(OPERATING_MODE.load(Ordering::Acquire)).unwrap_or(OperatingMode::Safe)
+}
+
+/// Switch to `mode`. Raises an event if the mode changed.
+///
+/// Entering safe mode resets the configuration to defaults, in case a bad value caused the
+/// fault. Only the command path runs in safe mode (see the main loop).
+pub fn set_mode(mode: OperatingMode) {
+    let old = OPERATING_MODE.swap(mode as u8, Ordering::AcqRel);
+    if old == mode as u8 {
+        return;
+    }
+
+    if mode == OperatingMode::Safe {
+        get_config_store().reset_to_defaults();
+        config_tentative::cancel();
+    }
+    arming::disarm();
+    raise_event(EventId::ModeChanged, [old as u32, mode as u32]);
+}
@@ -0,0 +1,100 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::nv_store::{NV_SLOT_WORDS, NvRecord, NvStore};
+use cts2_obc_logic::page_flash::{FLASH_PAGE_WORDS, PageFlash};
+use rtt_target::rprintln;
+use thiserror::Error;
+
+use crate::flash::{self, FlashError};
+
+/// First of the flash pages that hold the nonvolatile records, reserved in `memory.x`.
+const NV_STORE_ADDR: u32 = 0x080F_D000;
+
+/// A record kept in the nonvolatile store.
+#[derive(Debug, Clone, Copy)]
+pub enum NvSlot {
+    TxInhibit = 0,
+}
+
+#[derive(Debug, Error)]
+pub enum NvStoreError {
+    #[error("Record does not fit in its slot")]
+    RecordTooLong,
+
+    #[error("Nonvolatile store unavailable")]
+    Unavailable,
+
+    #[error("{0}")]
+    Flash(#[from] FlashError),
+}
+
+/// The store's pages of bank 1.
+#[derive(Debug)]
+pub struct NvPages;
+
+impl NvPages {
+    const fn addr(page: usize, offset: usize) -> u32 {
+        NV_STORE_ADDR + ((page * FLASH_PAGE_WORDS + offset) * 4) as u32
+    }
+}
+
+impl PageFlash for NvPages {
+    type Error = FlashError;
+
+    fn read(&self, page: usize, offset: usize, out: &mut [u32]) {
+        flash::read(Self::addr(page, offset), out);
+    }
+
+    fn erase(&mut self, page: usize) -> Result<(), FlashError> {
+        flash::erase_page(Self::addr(page, 0))
+    }
+
+    fn program(&mut self, page: usize, offset: usize, words: &[u32]) -> Result<(), FlashError> {
+        flash::program(Self::addr(page, offset), words)
+    }
+}
+
+/// The store. `None` until `init()`, or if the flash couldn't be set up.
+static NV_STORE: Mutex<RefCell<Option<NvStore<NvPages>>>> = Mutex::new(RefCell::new(None));
+
+/// Pick up the stored records. Call once during startup, before anything reads them.
+pub fn init() {
+    match NvStore::open(NvPages) {
+        Ok(store) => {
+            critical_section(|cs| NV_STORE.borrow(cs).replace(Some(store)));
+        }
+        Err(e) => rprintln!("Nonvolatile store unavailable: {}", e),
+    }
+}
+
+/// Read a slot. Reads all ones if it was never written.
+///
+/// Also reads all ones if the store couldn't be opened: that only happens when it held no
+/// records and couldn't be started.
+pub fn read_slot(slot: NvSlot) -> NvRecord {
+    critical_section(|cs| {
+        NV_STORE
+            .borrow(cs)
+            .borrow()
+            .as_ref()
+            .map(|store| store.read(slot as usize))
+    })
+    .unwrap_or([0xFFFF_FFFF; NV_SLOT_WORDS])
+}
+
+/// Write `record` to a slot. Unused words of the slot read all ones.
+///
+/// The old record is kept until the new one is fully written, so a reset meanwhile leaves one
+/// or the other.
+pub fn write_slot(slot: NvSlot, record: &[u32]) -> Result<(), NvStoreError> {
+    if record.len() > NV_SLOT_WORDS {
+        return Err(NvStoreError::RecordTooLong);
+    }
+    let mut padded = [0xFFFF_FFFF; NV_SLOT_WORDS];
+    padded[..record.len()].copy_from_slice(record);
+
+    critical_section(|cs| match NV_STORE.borrow(cs).borrow_mut().as_mut() {
+        Some(store) => Ok(store.write(slot as usize, &padded)?),
+        None => Err(NvStoreError::Unavailable),
+    })
+}
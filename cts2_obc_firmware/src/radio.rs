@@ -0,0 +1,89 @@
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use thiserror::Error;
+
+use crate::{deployment, tx_inhibit};
+
+static RADIO_TX_BYTES: AtomicU32 = AtomicU32::new(0);
+static RADIO_TX_BLOCKED_COUNT: AtomicU32 = AtomicU32::new(0);
+static RADIO_RESET_COUNT: AtomicU32 = AtomicU32::new(0);
+static USING_BACKUP_FREQUENCY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Error)]
+pub enum RadioTxError {
+    #[error("Post-deployment silence period has not ended")]
+    SilencePeriod,
+
+    #[error("Transmitter is disabled by command")]
+    Inhibited,
+}
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "radio_tx_allowed",
+        value_type: TelemetryType::Bool,
+        units: "",
+        read: || TelemetryValue::Bool(check_tx_allowed().is_ok()),
+    },
+    TelemetryPoint {
+        name: "radio_tx_bytes",
+        value_type: TelemetryType::U32,
+        units: "bytes",
+        read: || TelemetryValue::U32(RADIO_TX_BYTES.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "radio_tx_blocked",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(RADIO_TX_BLOCKED_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "radio_resets",
+        value_type: TelemetryType::U32,
+        units: "",
+        read: || TelemetryValue::U32(RADIO_RESET_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "radio_backup_frequency",
+        value_type: TelemetryType::Bool,
+        units: "",
+        read: || TelemetryValue::Bool(USING_BACKUP_FREQUENCY.load(Ordering::Relaxed)),
+    },
+];
+
+/// Whether the radio may transmit now.
+fn check_tx_allowed() -> Result<(), RadioTxError> {
+    if !deployment::silence_period_over() {
+        return Err(RadioTxError::SilencePeriod);
+    }
+    if !tx_inhibit::tx_enabled() {
+        return Err(RadioTxError::Inhibited);
+    }
+    Ok(())
+}
+
+/// Transmit `data` on the radio. Every radio transmission must go through here.
+///
+/// Fails, without transmitting, during the post-deployment silence period or while the
+/// transmitter is inhibited.
+pub fn radio_transmit(data: &[u8]) -> Result<(), RadioTxError> {
+    check_tx_allowed().inspect_err(|_| {
+        RADIO_TX_BLOCKED_COUNT.fetch_add(1, Ordering::Relaxed);
+    })?;
+
+    // TODO: Hand the data to the radio driver, once there is one.
+    RADIO_TX_BYTES.fetch_add(data.len() as u32, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Power-cycle the radio.
+pub fn reset_radio() {
+    // TODO: Reset the radio through its driver, once there is one.
+    RADIO_RESET_COUNT.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Switch the radio to its backup frequency configuration, until the next reboot.
+pub fn use_backup_frequency() {
+    // TODO: Reconfigure the radio through its driver, once there is one.
+    USING_BACKUP_FREQUENCY.store(true, Ordering::Relaxed);
+}
@@ -0,0 +1,62 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::link::Link;
+use cts2_obc_logic::subscriptions::{Subscription, SubscriptionError, SubscriptionTable};
+use cts2_obc_logic::telemetry::TelemetryPoint;
+
+use crate::telemetry::send_telemetry_value;
+use crate::timekeeping::uptime_ms;
+
+/// Most telemetry subscriptions that can be active at once, across all links.
+pub const MAX_SUBSCRIPTIONS: usize = 8;
+
+static SUBSCRIPTIONS: Mutex<RefCell<SubscriptionTable<MAX_SUBSCRIPTIONS>>> =
+    Mutex::new(RefCell::new(SubscriptionTable::new()));
+
+pub fn subscribe(
+    point: &TelemetryPoint,
+    period_ms: u32,
+    link: Link,
+) -> Result<(), SubscriptionError> {
+    let now_ms = uptime_ms();
+    critical_section(|cs| {
+        SUBSCRIPTIONS
+            .borrow(cs)
+            .borrow_mut()
+            .subscribe(point.name, period_ms, link, now_ms)
+    })
+}
+
+pub fn unsubscribe(channel: &str, link: Link) -> Result<(), SubscriptionError> {
+    critical_section(|cs| {
+        SUBSCRIPTIONS
+            .borrow(cs)
+            .borrow_mut()
+            .unsubscribe(channel, link)
+    })
+}
+
+/// Copy of the active subscriptions.
+pub fn subscriptions() -> heapless::Vec<Subscription, MAX_SUBSCRIPTIONS> {
+    critical_section(|cs| {
+        SUBSCRIPTIONS
+            .borrow(cs)
+            .borrow()
+            .subscriptions()
+            .iter()
+            .copied()
+            .collect()
+    })
+}
+
+/// Send every subscribed value that is due. Call periodically from the main loop.
+///
+/// Periods are only as precise as the main loop.
+pub fn poll_subscriptions() {
+    let now_ms = uptime_ms();
+    let due = critical_section(|cs| SUBSCRIPTIONS.borrow(cs).borrow_mut().take_due(now_ms));
+    for subscription in due {
+        // Channels were checked when subscribing, and points are never unregistered.
+        let _ = send_telemetry_value(subscription.channel, subscription.link);
+    }
+}
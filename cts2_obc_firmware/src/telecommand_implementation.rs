@@ -4,9 +4,25 @@ use crate::error::ExecuteCommandErr;
 use crate::timekeeping::uptime_ms;
 use crate::umbilical_uart::send_umbilical_uart;
 use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::dictionary::{self, MAX_ENTRY_LEN};
 use cts2_obc_telecommands::get_config_store;
 
+pub mod arm_commands;
+pub mod config_commands;
 pub mod demo_commands;
+pub mod fdir_commands;
+pub mod firmware_update_commands;
+pub mod health_commands;
+pub mod memory_commands;
+pub mod telemetry_commands;
+pub mod tx_commands;
+
+/// Send `ERR: <e>`, for a telecommand that failed.
+pub(crate) fn send_error(e: &dyn core::fmt::Display) {
+    let mut buffer = heapless::String::<96>::new();
+    let _ = write!(buffer, "ERR: {}\r\n", e);
+    send_umbilical_uart(buffer.as_bytes());
+}
 
 pub fn get_sys_uptime_ms_telecommand() -> Result<(), ExecuteCommandErr> {
     let sys_time = uptime_ms();
@@ -41,3 +57,13 @@ pub fn set_config_variable(
     send_umbilical_uart(buffer.as_bytes());
     Ok(())
 }
+
+/// Send the command dictionary, one JSON object per line.
+pub fn run_get_command_dictionary() -> Result<(), ExecuteCommandErr> {
+    for entry in dictionary::entries() {
+        let mut buffer = heapless::String::<{ MAX_ENTRY_LEN + 2 }>::new();
+        let _ = write!(buffer, "{}\r\n", entry);
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
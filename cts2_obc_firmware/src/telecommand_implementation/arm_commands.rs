@@ -0,0 +1,20 @@
+use core::fmt::Write;
+use cts2_obc_telecommands::dictionary::CommandInfo;
+
+use crate::{arming, error::ExecuteCommandErr, umbilical_uart::send_umbilical_uart};
+
+pub fn run_arm(command: &'static CommandInfo, nonce: u32) -> Result<(), ExecuteCommandErr> {
+    let mut buffer = heapless::String::<96>::new();
+    match arming::arm(command, nonce) {
+        Ok(()) => {
+            let _ = write!(buffer, "Armed: {}\r\n", command.name);
+            send_umbilical_uart(buffer.as_bytes());
+            Ok(())
+        }
+        Err(e) => {
+            let _ = write!(buffer, "ERR: arm refused: {}\r\n", e);
+            send_umbilical_uart(buffer.as_bytes());
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use core::fmt::Write;
+use cts2_obc_logic::config_audit::ConfigAuditRecord;
+use cts2_obc_logic::config_blob::{decode_config, export_config};
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::get_config_store;
+
+use crate::config_audit::with_config_audit;
+use crate::{
+    config_tentative, error::ExecuteCommandErr, telecommand_implementation::send_error,
+    umbilical_uart::send_umbilical_uart,
+};
+
+/// Most records that one `get_config_audit` sends.
+const CONFIG_AUDIT_REPLY_LEN: usize = 32;
+
+pub fn run_get_config_all() -> Result<(), ExecuteCommandErr> {
+    for (name, value) in get_config_store().values() {
+        let mut buffer = heapless::String::<128>::new();
+        let _ = write!(buffer, "Variable: {:?} = {:?}\r\n", name, value);
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
+
+/// Send the blob as hex, as `config_import` takes it.
+pub fn run_config_export() -> Result<(), ExecuteCommandErr> {
+    let mut buffer = heapless::String::<256>::new();
+    let _ = buffer.push_str("CONFIG_BLOB ");
+    for byte in export_config(get_config_store()) {
+        let _ = write!(buffer, "{:02x}", byte);
+    }
+    let _ = buffer.push_str("\r\n");
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_config_import(blob: &[u8]) -> Result<(), ExecuteCommandErr> {
+    let values = decode_config(blob)
+        .inspect_err(|e| send_error(&format_args!("config not imported: {}", e)))?;
+    get_config_store()
+        .set_all(&values)
+        .inspect_err(|e| send_error(&format_args!("config not imported: {}", e)))?;
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Config imported: {} variables\r\n", values.len());
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_config_reset(name: Option<ConfigVariableName>) -> Result<(), ExecuteCommandErr> {
+    let store = get_config_store();
+    match name {
+        Some(name) => {
+            store.reset(name);
+            let mut buffer = heapless::String::<128>::new();
+            let _ = write!(
+                buffer,
+                "Variable: {:?} set to {:?}\r\n",
+                name,
+                store.get(name)
+            );
+            send_umbilical_uart(buffer.as_bytes());
+        }
+        None => {
+            store.reset_to_defaults();
+            send_umbilical_uart(b"Config reset to defaults\r\n");
+        }
+    }
+    Ok(())
+}
+
+pub fn run_config_diff() -> Result<(), ExecuteCommandErr> {
+    let mut count = 0;
+    for (name, value, default) in get_config_store().diff() {
+        let mut buffer = heapless::String::<128>::new();
+        let _ = write!(
+            buffer,
+            "Variable: {:?} = {:?}, default {:?}\r\n",
+            name, value, default
+        );
+        send_umbilical_uart(buffer.as_bytes());
+        count += 1;
+    }
+    if count == 0 {
+        send_umbilical_uart(b"All config variables are at their defaults\r\n");
+    }
+    Ok(())
+}
+
+pub fn run_set_config_tentative(
+    name: ConfigVariableName,
+    value: ConfigValue,
+    timeout_s: u32,
+) -> Result<(), ExecuteCommandErr> {
+    let change = config_tentative::set_tentative(name, value, timeout_s)
+        .inspect_err(|e| send_error(&format_args!("config not set: {}", e)))?;
+
+    let mut buffer = heapless::String::<128>::new();
+    let _ = write!(
+        buffer,
+        "Variable: {:?} set to {:?}, reverts to {:?} in {} s unless confirmed\r\n",
+        name, value, change.previous, timeout_s
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_config_confirm() -> Result<(), ExecuteCommandErr> {
+    let change = config_tentative::confirm()
+        .inspect_err(|e| send_error(&format_args!("config_confirm: {}", e)))?;
+
+    let mut buffer = heapless::String::<128>::new();
+    let _ = write!(
+        buffer,
+        "Variable: {:?} confirmed as {:?}\r\n",
+        change.name, change.value
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_get_config_audit(count: u32) -> Result<(), ExecuteCommandErr> {
+    let (total, records) = with_config_audit(|log| {
+        let records: heapless::Vec<ConfigAuditRecord, CONFIG_AUDIT_REPLY_LEN> = log
+            .newest((count as usize).min(CONFIG_AUDIT_REPLY_LEN))
+            .collect();
+        (log.total(), records)
+    })
+    .unwrap_or_default();
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Config changes: {} recorded\r\n", total);
+    send_umbilical_uart(buffer.as_bytes());
+
+    for record in records {
+        let mut buffer = heapless::String::<160>::new();
+        let _ = write!(
+            buffer,
+            "CONFIG t={} source={} var={} old={} new={}\r\n",
+            record.met_ms,
+            record.source.as_str(),
+            record.name.as_str(),
+            record.old,
+            record.new
+        );
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use core::fmt::Write;
+use cts2_obc_logic::fdir::{FdirEngine, FdirError, FdirRule};
+use cts2_obc_logic::telemetry::TelemetryError;
+use cts2_obc_telecommands::fdir::FdirRuleArgs;
+use cts2_obc_telecommands::mode::OperatingMode;
+
+use crate::{
+    error::ExecuteCommandErr,
+    fdir::{MAX_FDIR_RULES, with_fdir_engine},
+    mode::set_mode,
+    telecommand_implementation::send_error,
+    telemetry::find_telemetry_point,
+    umbilical_uart::send_umbilical_uart,
+};
+
+pub fn run_fdir_list_rules() -> Result<(), ExecuteCommandErr> {
+    let lines = with_fdir_engine(|engine| {
+        let mut lines = heapless::Vec::<heapless::String<192>, MAX_FDIR_RULES>::new();
+        for (slot, state) in engine.rules() {
+            let mut line = heapless::String::new();
+            let _ = write!(
+                line,
+                "RULE {} {} {} persistence={} action={} enabled={} tripped={} trips={}\r\n",
+                slot,
+                state.rule.channel,
+                state.rule.check,
+                state.rule.persistence,
+                state.rule.action,
+                state.rule.enabled,
+                state.tripped,
+                state.trip_count
+            );
+            let _ = lines.push(line);
+        }
+        lines
+    });
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "FDIR rules: {}\r\n", lines.len());
+    send_umbilical_uart(buffer.as_bytes());
+    for line in lines {
+        send_umbilical_uart(line.as_bytes());
+    }
+    Ok(())
+}
+
+pub fn run_fdir_set_rule(args: FdirRuleArgs) -> Result<(), ExecuteCommandErr> {
+    let point = find_telemetry_point(&args.channel)
+        .ok_or(TelemetryError::UnknownPoint)
+        .inspect_err(|e| send_error(e))?;
+    let rule = FdirRule {
+        channel: point.name,
+        check: args.check,
+        persistence: args.persistence,
+        action: args.action,
+        enabled: true,
+    };
+    with_fdir_engine(|engine| engine.set_rule(args.slot as usize, rule))
+        .inspect_err(|e| send_error(e))?;
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "FDIR rule {} set\r\n", args.slot);
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+fn edit_rule(
+    slot: u8,
+    verb: &str,
+    edit: impl FnOnce(&mut FdirEngine<MAX_FDIR_RULES>) -> Result<(), FdirError>,
+) -> Result<(), ExecuteCommandErr> {
+    with_fdir_engine(edit).inspect_err(|e| send_error(e))?;
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "FDIR rule {} {}\r\n", slot, verb);
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_fdir_enable_rule(slot: u8) -> Result<(), ExecuteCommandErr> {
+    edit_rule(slot, "enabled", |engine| {
+        engine.set_enabled(slot as usize, true)
+    })
+}
+
+pub fn run_fdir_disable_rule(slot: u8) -> Result<(), ExecuteCommandErr> {
+    edit_rule(slot, "disabled", |engine| {
+        engine.set_enabled(slot as usize, false)
+    })
+}
+
+pub fn run_fdir_delete_rule(slot: u8) -> Result<(), ExecuteCommandErr> {
+    edit_rule(slot, "deleted", |engine| engine.delete_rule(slot as usize))
+}
+
+pub fn run_set_mode(mode: OperatingMode) -> Result<(), ExecuteCommandErr> {
+    set_mode(mode);
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Mode: {}\r\n", mode.as_str());
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
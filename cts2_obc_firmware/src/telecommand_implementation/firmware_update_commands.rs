@@ -0,0 +1,23 @@
+use core::fmt::Write;
+
+use crate::{error::ExecuteCommandErr, firmware_update, umbilical_uart::send_umbilical_uart};
+
+pub fn run_verify_staged_firmware() -> Result<(), ExecuteCommandErr> {
+    let mut buffer = heapless::String::<128>::new();
+    match firmware_update::verify_staged_image() {
+        Ok(manifest) => {
+            let _ = write!(
+                buffer,
+                "Staged firmware version {:#010x} ({} bytes) verified. Marked bootable.\r\n",
+                manifest.firmware_version, manifest.image_size
+            );
+            send_umbilical_uart(buffer.as_bytes());
+            Ok(())
+        }
+        Err(e) => {
+            let _ = write!(buffer, "ERR: staged firmware rejected: {}\r\n", e);
+            send_umbilical_uart(buffer.as_bytes());
+            Err(e.into())
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use core::fmt::Write;
+use cts2_obc_logic::command_log::CommandRecord;
+use cts2_obc_logic::event_log::Event;
+use cts2_obc_telecommands::dictionary;
+
+use crate::{
+    command_log::{COMMAND_HISTORY_LEN, with_command_log},
+    error::ExecuteCommandErr,
+    events::{EVENT_LOG_LEN, with_event_log},
+    image_self_check::last_image_crc_report,
+    memory_scrub::seu_stats,
+    umbilical_uart::send_umbilical_uart,
+};
+
+pub fn run_get_image_crc_status() -> Result<(), ExecuteCommandErr> {
+    let report = last_image_crc_report();
+
+    let mut buffer = heapless::String::<160>::new();
+    let _ = write!(
+        buffer,
+        "Image CRC: status={} expected=0x{:08x} computed=0x{:08x} last_check_ms={} passes={}\r\n",
+        report.status.as_str(),
+        report.expected_crc,
+        report.computed_crc,
+        report.completed_at_ms,
+        report.pass_count
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_get_events(count: u32) -> Result<(), ExecuteCommandErr> {
+    // Copy the events out first, so that interrupts aren't held off while transmitting.
+    let (total_count, events) = with_event_log(|log| {
+        let events: heapless::Vec<Event, EVENT_LOG_LEN> =
+            log.newest(count as usize).copied().collect();
+        (log.total_count(), events)
+    });
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Events: {} since boot\r\n", total_count);
+    send_umbilical_uart(buffer.as_bytes());
+
+    for event in events {
+        let mut buffer = heapless::String::<96>::new();
+        let _ = write!(
+            buffer,
+            "EVENT t={} id={} data=[0x{:08x}, 0x{:08x}]\r\n",
+            event.uptime_ms,
+            event.id.as_str(),
+            event.data[0],
+            event.data[1]
+        );
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
+
+pub fn run_get_seu_stats() -> Result<(), ExecuteCommandErr> {
+    let stats = seu_stats();
+
+    let mut buffer = heapless::String::<256>::new();
+    let _ = write!(
+        buffer,
+        "SEU: flash_ecc_corrected={} (last 0x{:08x}) flash_ecc_detected={} (last 0x{:08x}) \
+         sram2_parity={} (last 0x{:08x}) sram2_parity_check={} unknown_nmi={} scrub_passes={}\r\n",
+        stats.flash_ecc_corrected.count,
+        stats.flash_ecc_corrected.last_address,
+        stats.flash_ecc_detected.count,
+        stats.flash_ecc_detected.last_address,
+        stats.sram2_parity.count,
+        stats.sram2_parity.last_address,
+        if stats.sram2_parity_check_enabled {
+            "on"
+        } else {
+            "off"
+        },
+        stats.unknown_nmi_count,
+        stats.scrub_pass_count
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_get_cmd_history(count: u32) -> Result<(), ExecuteCommandErr> {
+    let (totals, records) = with_command_log(|log| {
+        let records: heapless::Vec<CommandRecord, COMMAND_HISTORY_LEN> =
+            log.newest(count as usize).copied().collect();
+        (log.totals(), records)
+    });
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Commands: {} since boot\r\n", totals.received);
+    send_umbilical_uart(buffer.as_bytes());
+
+    for record in records {
+        let mut buffer = heapless::String::<128>::new();
+        let _ = write!(
+            buffer,
+            "CMD t={} source={} cmd={} result={} cycles={}\r\n",
+            record.uptime_ms,
+            record.source.as_str(),
+            record.name(),
+            record.result,
+            record.cycles
+        );
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
+
+pub fn run_get_cmd_stats() -> Result<(), ExecuteCommandErr> {
+    let (totals, stats) = with_command_log(|log| {
+        let stats: heapless::Vec<_, { dictionary::COMMANDS.len() }> = log.stats().collect();
+        (log.totals(), stats)
+    });
+
+    for (name, stats) in core::iter::once(("total", totals)).chain(stats) {
+        let mut buffer = heapless::String::<128>::new();
+        let _ = write!(
+            buffer,
+            "CMD_STATS {} received={} ok={} failed={}\r\n",
+            name, stats.received, stats.succeeded, stats.failed
+        );
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
@@ -0,0 +1,263 @@
+use core::cell::RefCell;
+use core::fmt::Write;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::crc32::Crc32;
+use cts2_obc_logic::memory_access::{
+    MAX_MEM_CRC_LEN, MAX_MEM_READ_LEN, MemoryAccessError, MemoryRegion, MemoryRegions,
+    write_hex_dump,
+};
+use cts2_obc_telecommands::{MemDumpFormat, MemWriteBytes, MemoryAccess};
+
+use crate::{error::ExecuteCommandErr, umbilical_uart::send_umbilical_uart};
+
+/// Address ranges that the memory telecommands may access, with the most access allowed in
+/// each. The ground can lower the access at runtime with `mem_set_region`.
+///
+/// Keep this list tight: any access to an unmapped address causes a bus fault, and reading
+/// many peripheral registers has side effects (e.g. reading a USART's RDR takes a received
+/// byte, and reading a FIFO pops it). So only the peripherals that are useful for debugging and
+/// can be read freely are listed, and none of them can be written.
+const MEMORY_REGION_LIMITS: [MemoryRegion; 11] = [
+    MemoryRegion {
+        name: "flash",
+        start: 0x0800_0000,
+        len: 0x0020_0000, // 2 MiB (both banks)
+        access: MemoryAccess::ReadOnly,
+    },
+    MemoryRegion {
+        name: "sram2",
+        start: 0x1000_0000,
+        len: 0x0001_0000, // 64 KiB
+        access: MemoryAccess::ReadWrite,
+    },
+    MemoryRegion {
+        name: "sram1",
+        start: 0x2000_0000,
+        len: 0x0003_0000, // 192 KiB
+        access: MemoryAccess::ReadWrite,
+    },
+    MemoryRegion {
+        name: "sram3",
+        start: 0x2004_0000,
+        len: 0x0006_0000, // 384 KiB
+        access: MemoryAccess::ReadWrite,
+    },
+    MemoryRegion {
+        name: "otp_and_device_id",
+        start: 0x1FFF_7000,
+        len: 0x0000_0800,
+        access: MemoryAccess::ReadOnly,
+    },
+    // Peripherals, from the STM32L4R9 memory map (RM0432).
+    MemoryRegion {
+        name: "pwr",
+        start: 0x4000_7000,
+        len: 0x0000_0400,
+        access: MemoryAccess::ReadOnly,
+    },
+    MemoryRegion {
+        name: "syscfg_exti",
+        start: 0x4001_0000,
+        len: 0x0000_0800, // SYSCFG, VREFBUF, COMP and EXTI
+        access: MemoryAccess::ReadOnly,
+    },
+    MemoryRegion {
+        name: "dma",
+        start: 0x4002_0000,
+        len: 0x0000_0C00, // DMA1, DMA2 and DMAMUX1
+        access: MemoryAccess::ReadOnly,
+    },
+    MemoryRegion {
+        name: "rcc",
+        start: 0x4002_1000,
+        len: 0x0000_0400,
+        access: MemoryAccess::ReadOnly,
+    },
+    MemoryRegion {
+        name: "flash_interface",
+        start: 0x4002_2000,
+        len: 0x0000_0400,
+        access: MemoryAccess::ReadOnly,
+    },
+    MemoryRegion {
+        name: "gpio",
+        start: 0x4800_0000,
+        len: 0x0000_2400, // GPIOA to GPIOI
+        access: MemoryAccess::ReadOnly,
+    },
+];
+
+static MEMORY_REGIONS: Mutex<RefCell<MemoryRegions<{ MEMORY_REGION_LIMITS.len() }>>> =
+    Mutex::new(RefCell::new(MemoryRegions::new(&MEMORY_REGION_LIMITS)));
+
+/// Size of the stack buffer used to copy memory out in pieces.
+const CHUNK_LEN: usize = 64;
+
+fn send_memory_error(e: &MemoryAccessError) {
+    let mut buffer = heapless::String::<96>::new();
+    let _ = write!(buffer, "ERR: {}\r\n", e);
+    send_umbilical_uart(buffer.as_bytes());
+}
+
+/// Check `[addr, addr + len)` against the regions, and send any error.
+fn check_memory(addr: u32, len: u32, write: bool) -> Result<MemoryRegion, MemoryAccessError> {
+    critical_section(|cs| MEMORY_REGIONS.borrow(cs).borrow().check(addr, len, write))
+        .inspect_err(send_memory_error)
+}
+
+/// Copy memory into `buf`, using word accesses when the address and length allow it.
+///
+/// Many peripheral registers only support 32-bit access.
+///
+/// # Safety
+/// The whole range must be readable; check it with `check_access` first.
+unsafe fn read_memory(addr: u32, buf: &mut [u8]) {
+    if addr.is_multiple_of(4) && buf.len().is_multiple_of(4) {
+        for (i, word_bytes) in buf.chunks_exact_mut(4).enumerate() {
+            let word = unsafe { core::ptr::read_volatile((addr as usize + i * 4) as *const u32) };
+            word_bytes.copy_from_slice(&word.to_le_bytes());
+        }
+    } else {
+        for (i, b) in buf.iter_mut().enumerate() {
+            *b = unsafe { core::ptr::read_volatile((addr as usize + i) as *const u8) };
+        }
+    }
+}
+
+/// Write `data` to memory, using word accesses when the address and length allow it.
+///
+/// # Safety
+/// The whole range must be writable; check it with `check_access` first.
+unsafe fn write_memory(addr: u32, data: &[u8]) {
+    if addr.is_multiple_of(4) && data.len().is_multiple_of(4) {
+        for (i, word_bytes) in data.chunks_exact(4).enumerate() {
+            let word =
+                u32::from_le_bytes([word_bytes[0], word_bytes[1], word_bytes[2], word_bytes[3]]);
+            unsafe { core::ptr::write_volatile((addr as usize + i * 4) as *mut u32, word) };
+        }
+    } else {
+        for (i, &b) in data.iter().enumerate() {
+            unsafe { core::ptr::write_volatile((addr as usize + i) as *mut u8, b) };
+        }
+    }
+}
+
+pub fn run_mem_read(addr: u32, len: u32, format: MemDumpFormat) -> Result<(), ExecuteCommandErr> {
+    if len > MAX_MEM_READ_LEN {
+        send_memory_error(&MemoryAccessError::LengthTooLarge);
+        return Err(MemoryAccessError::LengthTooLarge.into());
+    }
+    check_memory(addr, len, false)?;
+
+    let mut header = heapless::String::<64>::new();
+    let _ = write!(header, "MEM 0x{:08x} {} bytes\r\n", addr, len);
+    send_umbilical_uart(header.as_bytes());
+
+    let mut chunk = [0u8; CHUNK_LEN];
+    let mut offset = 0u32;
+    while offset < len {
+        let chunk_len = ((len - offset) as usize).min(CHUNK_LEN);
+        let chunk_addr = addr + offset;
+        // SAFETY: The whole range was checked against the allowed regions above.
+        unsafe { read_memory(chunk_addr, &mut chunk[..chunk_len]) };
+
+        match format {
+            MemDumpFormat::Hex => {
+                // CHUNK_LEN is a multiple of the line length, so lines never split across chunks.
+                let mut lines = heapless::String::<{ CHUNK_LEN * 5 }>::new();
+                let _ = write_hex_dump(&mut lines, chunk_addr, &chunk[..chunk_len]);
+                send_umbilical_uart(lines.as_bytes());
+            }
+            MemDumpFormat::Binary => send_umbilical_uart(&chunk[..chunk_len]),
+        }
+        offset += chunk_len as u32;
+    }
+
+    if format == MemDumpFormat::Binary {
+        send_umbilical_uart(b"\r\n");
+    }
+    Ok(())
+}
+
+pub fn run_mem_write(addr: u32, bytes: MemWriteBytes) -> Result<(), ExecuteCommandErr> {
+    check_memory(addr, bytes.len() as u32, true)?;
+
+    // SAFETY: The whole range was checked against the allowed, writable regions above.
+    unsafe { write_memory(addr, &bytes) };
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Wrote {} bytes at 0x{:08x}\r\n", bytes.len(), addr);
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_mem_crc(addr: u32, len: u32) -> Result<(), ExecuteCommandErr> {
+    if len > MAX_MEM_CRC_LEN {
+        send_memory_error(&MemoryAccessError::LengthTooLarge);
+        return Err(MemoryAccessError::LengthTooLarge.into());
+    }
+    check_memory(addr, len, false)?;
+
+    let mut crc = Crc32::new();
+    let mut chunk = [0u8; CHUNK_LEN];
+    let mut offset = 0u32;
+    while offset < len {
+        let chunk_len = ((len - offset) as usize).min(CHUNK_LEN);
+        // SAFETY: The whole range was checked against the allowed regions above.
+        unsafe { read_memory(addr + offset, &mut chunk[..chunk_len]) };
+        crc.update(&chunk[..chunk_len]);
+        offset += chunk_len as u32;
+    }
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(
+        buffer,
+        "CRC32 0x{:08x} {} bytes = 0x{:08x}\r\n",
+        addr,
+        len,
+        crc.finalize()
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_mem_list_regions() -> Result<(), ExecuteCommandErr> {
+    let (regions, limits) = critical_section(|cs| {
+        let regions = MEMORY_REGIONS.borrow(cs).borrow();
+        (regions.regions(), regions.limits())
+    });
+    for (region, limit) in regions.iter().zip(limits) {
+        let mut buffer = heapless::String::<96>::new();
+        let _ = write!(
+            buffer,
+            "REGION {} 0x{:08x} {} bytes {} (max {})\r\n",
+            region.name,
+            region.start,
+            region.len,
+            region.access.as_str(),
+            limit.access.as_str()
+        );
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
+
+pub fn run_mem_set_region(addr: u32, access: MemoryAccess) -> Result<(), ExecuteCommandErr> {
+    let region = critical_section(|cs| {
+        MEMORY_REGIONS
+            .borrow(cs)
+            .borrow_mut()
+            .set_access(addr, access)
+    })
+    .inspect_err(send_memory_error)?;
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(
+        buffer,
+        "Region {} set to {}\r\n",
+        region.name,
+        region.access.as_str()
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use core::fmt::Write;
+use cts2_obc_logic::link::Link;
+use cts2_obc_logic::telemetry::{TelemetryError, write_telemetry_info_json};
+use cts2_obc_telecommands::{TelemetryName, TelemetryNames};
+
+use crate::{
+    error::ExecuteCommandErr,
+    subscriptions::{self, subscriptions},
+    telecommand_implementation::send_error,
+    telemetry::{find_telemetry_point, send_telemetry_value, telemetry_points},
+    umbilical_uart::send_umbilical_uart,
+};
+
+pub fn run_get_telemetry(name: &TelemetryName) -> Result<(), ExecuteCommandErr> {
+    send_telemetry_value(name, Link::Umbilical)?;
+    Ok(())
+}
+
+pub fn run_get_telemetry_many(names: &TelemetryNames) -> Result<(), ExecuteCommandErr> {
+    // Report every point, even after an unknown one, then fail if any were unknown.
+    let mut result = Ok(());
+    for name in names {
+        if let Err(e) = send_telemetry_value(name, Link::Umbilical) {
+            result = Err(e);
+        }
+    }
+    result?;
+    Ok(())
+}
+
+pub fn run_list_telemetry() -> Result<(), ExecuteCommandErr> {
+    for point in telemetry_points() {
+        let mut buffer = heapless::String::<128>::new();
+        let _ = write_telemetry_info_json(&mut buffer, &point);
+        let _ = buffer.push_str("\r\n");
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
+
+pub fn run_subscribe(
+    name: &TelemetryName,
+    period_ms: u32,
+    link: Link,
+) -> Result<(), ExecuteCommandErr> {
+    let point = find_telemetry_point(name)
+        .ok_or(TelemetryError::UnknownPoint)
+        .inspect_err(|e| send_error(e))?;
+    subscriptions::subscribe(&point, period_ms, link).inspect_err(|e| send_error(e))?;
+
+    let mut buffer = heapless::String::<96>::new();
+    let _ = write!(
+        buffer,
+        "Subscribed to {} every {} ms on {}\r\n",
+        point.name,
+        period_ms,
+        link.as_str()
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_unsubscribe(name: &TelemetryName, link: Link) -> Result<(), ExecuteCommandErr> {
+    subscriptions::unsubscribe(name, link).inspect_err(|e| send_error(e))?;
+
+    let mut buffer = heapless::String::<96>::new();
+    let _ = write!(
+        buffer,
+        "Unsubscribed from {} on {}\r\n",
+        name,
+        link.as_str()
+    );
+    send_umbilical_uart(buffer.as_bytes());
+    Ok(())
+}
+
+pub fn run_list_subscriptions() -> Result<(), ExecuteCommandErr> {
+    let subscriptions = subscriptions();
+
+    let mut buffer = heapless::String::<64>::new();
+    let _ = write!(buffer, "Subscriptions: {}\r\n", subscriptions.len());
+    send_umbilical_uart(buffer.as_bytes());
+
+    for subscription in subscriptions {
+        let mut buffer = heapless::String::<96>::new();
+        let _ = write!(
+            buffer,
+            "SUB {} every {} ms on {}\r\n",
+            subscription.channel,
+            subscription.period_ms,
+            subscription.link.as_str()
+        );
+        send_umbilical_uart(buffer.as_bytes());
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use core::fmt::Write;
+use cts2_obc_logic::tx_inhibit::TxInhibit;
+
+use crate::{
+    error::ExecuteCommandErr, mission_time::mission_elapsed_ms,
+    telecommand_implementation::send_error, tx_inhibit::set_tx_inhibit,
+    umbilical_uart::send_umbilical_uart,
+};
+
+fn set_and_report(state: TxInhibit, message: &str) -> Result<(), ExecuteCommandErr> {
+    // The new state is in effect even if storing it fails, so report both.
+    let result = set_tx_inhibit(state).inspect_err(|e| send_error(e));
+    send_umbilical_uart(message.as_bytes());
+    Ok(result?)
+}
+
+pub fn run_tx_disable() -> Result<(), ExecuteCommandErr> {
+    set_and_report(TxInhibit::Disabled, "TX disabled\r\n")
+}
+
+pub fn run_tx_enable() -> Result<(), ExecuteCommandErr> {
+    set_and_report(TxInhibit::Enabled, "TX enabled\r\n")
+}
+
+pub fn run_tx_disable_for(duration_s: u32) -> Result<(), ExecuteCommandErr> {
+    let met_ms = mission_elapsed_ms() + duration_s as u64 * 1000;
+    let mut message = heapless::String::<64>::new();
+    let _ = write!(message, "TX disabled for {} s\r\n", duration_s);
+    set_and_report(TxInhibit::DisabledUntil { met_ms }, &message)
+}
@@ -0,0 +1,96 @@
+use core::cell::RefCell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::link::Link;
+use cts2_obc_logic::telemetry::{
+    TelemetryError, TelemetryPoint, TelemetryRegistry, matches_dictionary,
+    write_telemetry_error_json, write_telemetry_value_json,
+};
+use rtt_target::rprintln;
+
+use crate::link::send_on_link;
+use crate::{
+    boot_loop, command_log, deployment, events, image_self_check, link_loss, memory_scrub,
+    mission_time, mode, radio, timekeeping, tx_inhibit, umbilical_uart,
+};
+
+/// Most telemetry points that can be registered.
+pub const MAX_TELEMETRY_POINTS: usize = 32;
+
+static TELEMETRY_REGISTRY: Mutex<RefCell<TelemetryRegistry<MAX_TELEMETRY_POINTS>>> =
+    Mutex::new(RefCell::new(TelemetryRegistry::new()));
+
+/// The telemetry points of every module, in registration order.
+const POINT_LISTS: &[&[TelemetryPoint]] = &[
+    timekeeping::TELEMETRY_POINTS,
+    mode::TELEMETRY_POINTS,
+    boot_loop::TELEMETRY_POINTS,
+    mission_time::TELEMETRY_POINTS,
+    deployment::TELEMETRY_POINTS,
+    radio::TELEMETRY_POINTS,
+    link_loss::TELEMETRY_POINTS,
+    tx_inhibit::TELEMETRY_POINTS,
+    umbilical_uart::TELEMETRY_POINTS,
+    command_log::TELEMETRY_POINTS,
+    events::TELEMETRY_POINTS,
+    image_self_check::TELEMETRY_POINTS,
+    memory_scrub::TELEMETRY_POINTS,
+];
+
+// Ground software relies on the command dictionary, so don't build if it is out of date.
+const _: () = assert!(
+    matches_dictionary(POINT_LISTS),
+    "telemetry points differ from the command dictionary"
+);
+
+/// Register the telemetry points of every module. Call once during startup.
+pub fn init() {
+    critical_section(|cs| {
+        let mut registry = TELEMETRY_REGISTRY.borrow(cs).borrow_mut();
+        for points in POINT_LISTS {
+            if let Err(e) = registry.register_all(points) {
+                rprintln!("Telemetry registration error: {}", e);
+            }
+        }
+        rprintln!("Registered {} telemetry points.", registry.points().len());
+    });
+}
+
+/// Look up a telemetry point by name.
+///
+/// The point is copied out, so that its read callback runs outside the critical section.
+pub fn find_telemetry_point(name: &str) -> Option<TelemetryPoint> {
+    critical_section(|cs| TELEMETRY_REGISTRY.borrow(cs).borrow().get(name).copied())
+}
+
+/// Copy of all registered telemetry points, in registration order.
+pub fn telemetry_points() -> heapless::Vec<TelemetryPoint, MAX_TELEMETRY_POINTS> {
+    critical_section(|cs| {
+        TELEMETRY_REGISTRY
+            .borrow(cs)
+            .borrow()
+            .points()
+            .iter()
+            .copied()
+            .collect()
+    })
+}
+
+/// Send the current value of a telemetry point on `link`, as a JSON line.
+///
+/// An unknown name is reported on the link too, as a JSON error line.
+pub fn send_telemetry_value(name: &str, link: Link) -> Result<(), TelemetryError> {
+    let mut buffer = heapless::String::<128>::new();
+    let result = match find_telemetry_point(name) {
+        Some(point) => {
+            let _ = write_telemetry_value_json(&mut buffer, &point, &(point.read)());
+            Ok(())
+        }
+        None => {
+            let _ = write_telemetry_error_json(&mut buffer, name, TelemetryError::UnknownPoint);
+            Err(TelemetryError::UnknownPoint)
+        }
+    };
+    let _ = buffer.push_str("\r\n");
+    send_on_link(link, buffer.as_bytes());
+    result
+}
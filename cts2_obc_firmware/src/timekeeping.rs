@@ -1,5 +1,6 @@
 use core::sync::atomic::{AtomicBool, Ordering};
 use cortex_m::interrupt::free as critical_section;
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
 
 /// True after successful init.
 static INIT_DONE: AtomicBool = AtomicBool::new(false);
@@ -59,8 +60,7 @@ pub fn uptime_ms() -> u64 {
         return 0;
     }
 
-    // Read current CYCCNT (32-bit) from DWT->CYCCNT at 0xE0001004
-    let now_lo: u32 = unsafe { core::ptr::read_volatile((0xE000_1000u32 + 0x04) as *const u32) };
+    let now_lo = cycle_count();
 
     // Update accumulated cycles handling wrap-around inside a critical section
     critical_section(|_| {
@@ -87,3 +87,17 @@ pub fn uptime_ms() -> u64 {
     let ms = (cycles128 * 1000u128 + core_hz / 2u128) / core_hz;
     ms as u64
 }
+
+/// The 32-bit DWT cycle counter. Wraps around about once a minute at 64 MHz, so only use it for
+/// short intervals, with `wrapping_sub`.
+pub fn cycle_count() -> u32 {
+    // DWT->CYCCNT is at 0xE0001004.
+    unsafe { core::ptr::read_volatile((0xE000_1000u32 + 0x04) as *const u32) }
+}
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[TelemetryPoint {
+    name: "uptime_ms",
+    value_type: TelemetryType::U64,
+    units: "ms",
+    read: || TelemetryValue::U64(uptime_ms()),
+}];
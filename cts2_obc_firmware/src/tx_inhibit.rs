@@ -0,0 +1,54 @@
+use core::cell::Cell;
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::event_log::EventId;
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use cts2_obc_logic::tx_inhibit::{TX_INHIBIT_RECORD_WORDS, TxInhibit};
+
+use crate::events::raise_event;
+use crate::mission_time::mission_elapsed_ms;
+use crate::nv_store::{self, NvSlot, NvStoreError};
+
+/// Off until `init` has read the stored state.
+static TX_INHIBIT: Mutex<Cell<TxInhibit>> = Mutex::new(Cell::new(TxInhibit::Disabled));
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[TelemetryPoint {
+    name: "tx_inhibit",
+    value_type: TelemetryType::Str,
+    units: "",
+    read: || TelemetryValue::Str(tx_inhibit().as_str()),
+}];
+
+/// Load the stored TX inhibit state. Call once during startup, after `mission_time::init`.
+pub fn init() {
+    let words = nv_store::read_slot(NvSlot::TxInhibit);
+    let mut record = [0u32; TX_INHIBIT_RECORD_WORDS];
+    record.copy_from_slice(&words[..TX_INHIBIT_RECORD_WORDS]);
+    let state = TxInhibit::from_words(&record);
+    critical_section(|cs| TX_INHIBIT.borrow(cs).set(state));
+}
+
+pub fn tx_inhibit() -> TxInhibit {
+    critical_section(|cs| TX_INHIBIT.borrow(cs).get())
+}
+
+/// Whether the ground allows the transmitter to be used now.
+pub fn tx_enabled() -> bool {
+    tx_inhibit().allows_tx(mission_elapsed_ms())
+}
+
+/// Change the TX inhibit state, and store it in flash.
+///
+/// The new state applies right away, even if storing it fails.
+pub fn set_tx_inhibit(state: TxInhibit) -> Result<(), NvStoreError> {
+    critical_section(|cs| TX_INHIBIT.borrow(cs).set(state));
+
+    let duration_s = match state {
+        TxInhibit::DisabledUntil { met_ms } => {
+            (met_ms.saturating_sub(mission_elapsed_ms()) / 1000) as u32
+        }
+        _ => 0,
+    };
+    raise_event(EventId::TxInhibitChanged, [state.code(), duration_s]);
+
+    nv_store::write_slot(NvSlot::TxInhibit, &state.to_words())
+}
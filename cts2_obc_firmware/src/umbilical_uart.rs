@@ -1,12 +1,55 @@
-use core::fmt::Write;
-use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
-use cts2_obc_telecommands::error::{ConfigError, ParsedTelecommandErr};
+use core::cell::RefCell;
+use core::fmt::{self, Write};
+use core::sync::atomic::{AtomicU8, AtomicU32, AtomicUsize, Ordering};
+use cortex_m::interrupt::{Mutex, free as critical_section};
+use cts2_obc_logic::command_log::{
+    CommandSource, RESULT_FAILED, RESULT_OK, command_index, named_command,
+};
+use cts2_obc_logic::config_audit::ConfigChangeSource;
+use cts2_obc_logic::link::Link;
+use cts2_obc_logic::shell::{self, LineEditor, SHELL_COMMAND, ShellLine};
+use cts2_obc_logic::telemetry::{TelemetryPoint, TelemetryType, TelemetryValue};
+use cts2_obc_telecommands::batch::parse_batch;
+use cts2_obc_telecommands::error::{BatchError, ParsedTelecommandErr};
+use cts2_obc_telecommands::grammar::{split_batch, strip_comment};
+use cts2_obc_telecommands::response::{
+    ERROR_PREFIX, write_batch_invalid, write_batch_result, write_batch_too_long, write_parse_error,
+};
 use cts2_obc_telecommands::{Telecommand, parse_telecommand};
 use rtt_target::rprintln;
 use stm32l4xx_hal::{self as stm32_hal};
 
-use crate::error::DispatchCommandErr;
+use crate::arming::check_armed;
+use crate::command_log::{record_command, record_rejected};
+use crate::config_audit::with_change_source;
+use crate::error::{DispatchCommandErr, ExecuteCommandErr};
+use crate::link_loss;
+use crate::telecommand_implementation::arm_commands::run_arm;
+use crate::telecommand_implementation::config_commands::{
+    run_config_confirm, run_config_diff, run_config_export, run_config_import, run_config_reset,
+    run_get_config_all, run_get_config_audit, run_set_config_tentative,
+};
 use crate::telecommand_implementation::demo_commands::run_hello_world_telecommand;
+use crate::telecommand_implementation::fdir_commands::{
+    run_fdir_delete_rule, run_fdir_disable_rule, run_fdir_enable_rule, run_fdir_list_rules,
+    run_fdir_set_rule, run_set_mode,
+};
+use crate::telecommand_implementation::firmware_update_commands::run_verify_staged_firmware;
+use crate::telecommand_implementation::health_commands::{
+    run_get_cmd_history, run_get_cmd_stats, run_get_events, run_get_image_crc_status,
+    run_get_seu_stats,
+};
+use crate::telecommand_implementation::memory_commands::{
+    run_mem_crc, run_mem_list_regions, run_mem_read, run_mem_set_region, run_mem_write,
+};
+use crate::telecommand_implementation::telemetry_commands::{
+    run_get_telemetry, run_get_telemetry_many, run_list_subscriptions, run_list_telemetry,
+    run_subscribe, run_unsubscribe,
+};
+use crate::telecommand_implementation::tx_commands::{
+    run_tx_disable, run_tx_disable_for, run_tx_enable,
+};
+use crate::timekeeping::{cycle_count, uptime_ms};
 
 /// Maximum length of a telecommand string received over the umbilical UART.
 /// Includes the length of the command name, arguments, terminating newline, etc.
@@ -17,6 +60,34 @@ static UART_RX_BUF: [AtomicU8; UART_BUF_SIZE] = [const { AtomicU8::new(0) }; UAR
 static UART_HEAD: AtomicUsize = AtomicUsize::new(0);
 static UART_TAIL: AtomicUsize = AtomicUsize::new(0);
 
+static UART_RX_BYTE_COUNT: AtomicU32 = AtomicU32::new(0);
+static UART_RX_OVERFLOW_COUNT: AtomicU32 = AtomicU32::new(0);
+static UART_TX_BYTE_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Line editor of the interactive shell, or `None` while the umbilical takes plain lines.
+static SHELL: Mutex<RefCell<Option<LineEditor>>> = Mutex::new(RefCell::new(None));
+
+pub const TELEMETRY_POINTS: &[TelemetryPoint] = &[
+    TelemetryPoint {
+        name: "uart_rx_bytes",
+        value_type: TelemetryType::U32,
+        units: "bytes",
+        read: || TelemetryValue::U32(UART_RX_BYTE_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "uart_rx_overflow_bytes",
+        value_type: TelemetryType::U32,
+        units: "bytes",
+        read: || TelemetryValue::U32(UART_RX_OVERFLOW_COUNT.load(Ordering::Relaxed)),
+    },
+    TelemetryPoint {
+        name: "uart_tx_bytes",
+        value_type: TelemetryType::U32,
+        units: "bytes",
+        read: || TelemetryValue::U32(UART_TX_BYTE_COUNT.load(Ordering::Relaxed)),
+    },
+];
+
 /// Poll the UART RX DMA circular buffer and push received bytes into `UART_RX_BUF`.
 ///
 /// This function should be called periodically to process incoming UART data, from the
@@ -49,7 +120,9 @@ fn uart_push_byte(b: u8) {
     if next != UART_TAIL.load(Ordering::Acquire) {
         UART_RX_BUF[head].store(b, Ordering::Release);
         UART_HEAD.store(next, Ordering::Release);
+        UART_RX_BYTE_COUNT.fetch_add(1, Ordering::Relaxed);
     } else {
+        UART_RX_OVERFLOW_COUNT.fetch_add(1, Ordering::Relaxed);
         rprintln!("UART RX buffer overflow, dropping byte {}", b);
     }
 }
@@ -69,9 +142,13 @@ fn uart_pop_byte() -> Option<u8> {
 }
 
 /// Process commands received over the umbilical UART, from the `UART_RX_BUF`.
+///
+/// A line of `shell` alone turns on the interactive shell (see `cts2_obc_logic::shell`), which
+/// echoes and edits what is typed, until `exit`.
 pub fn process_umbilical_commands() {
     let mut cmd = [0u8; MAX_TELECOMMAND_STR_LENGTH];
     let mut idx = 0;
+    let mut shell = critical_section(|cs| SHELL.borrow(cs).take());
 
     rprintln!(
         "Processing UART commands. HEAD={}, TAIL={}",
@@ -80,14 +157,26 @@ pub fn process_umbilical_commands() {
     );
 
     while let Some(b) = uart_pop_byte() {
-        if b == b'\n' || idx >= cmd.len() {
+        if let Some(editor) = shell.as_mut() {
+            if let Ok(Some(line)) = editor.feed(b, &mut UmbilicalWriter) {
+                rprintln!("SHELL: {}", line.as_str());
+                if !run_shell_line(&line) {
+                    shell = None;
+                }
+            }
+        } else if b == b'\n' || idx >= cmd.len() {
             if idx > 0 {
-                if let Ok(cmd_str) = core::str::from_utf8(&cmd[..idx]) {
-                    let trimmed = cmd_str.trim_end();
+                if let Ok(line) = core::str::from_utf8(&cmd[..idx]) {
+                    let trimmed = line.trim_end();
                     rprintln!("CMD: {}", trimmed);
-                    match dispatch_command(trimmed) {
-                        Ok(_) => rprintln!("Command executed successfully"),
-                        Err(_) => rprintln!("Command execution failed"),
+                    if trimmed == SHELL_COMMAND {
+                        send_umbilical_uart(
+                            b"Shell on. Type help for the commands, exit to leave.\r\n",
+                        );
+                        send_umbilical_uart(shell::PROMPT.as_bytes());
+                        shell = Some(LineEditor::new());
+                    } else {
+                        dispatch_batch(trimmed, CommandSource::Ground);
                     }
                 }
                 idx = 0;
@@ -97,59 +186,135 @@ pub fn process_umbilical_commands() {
             idx += 1;
         }
     }
+
+    critical_section(|cs| SHELL.borrow(cs).replace(shell));
 }
 
-// TODO: Make different functions to handle each separate command.
-// TODO: Fix the () error type to be enum or string
-// TODO: Replace with meaningful telecommands.
-fn dispatch_command(cmd_str: &str) -> Result<(), DispatchCommandErr> {
-    let cmd = match parse_telecommand(cmd_str) {
-        Ok(cmd) => cmd,
-        Err(e) => {
-            match e {
-                ParsedTelecommandErr::UnknownCommand => {
-                    send_umbilical_uart(b"ERR: unknown command\r\n");
-                }
-                ParsedTelecommandErr::DeserializationError(_) => {
-                    send_umbilical_uart(b"ERR: failed to deserialize command arguments\r\n");
-                }
-                ParsedTelecommandErr::MissingArgument(idx) => {
-                    let mut msg = heapless::String::<64>::new();
-                    let _ = write!(msg, "ERR: missing required argument at index {}\r\n", idx);
-                    send_umbilical_uart(msg.as_bytes());
-                }
-                ParsedTelecommandErr::ExceededArgumentCount => {
-                    send_umbilical_uart(b"ERR: too many arguments provided\r\n");
-                }
+/// Run a line typed in the shell, then prompt for the next one. Returns `false` on `exit`.
+fn run_shell_line(line: &str) -> bool {
+    match ShellLine::parse(line) {
+        ShellLine::Empty => {}
+        ShellLine::Help(topic) => {
+            let _ = shell::write_help(&mut UmbilicalWriter, topic);
+        }
+        ShellLine::Exit => {
+            send_umbilical_uart(b"Shell off.\r\n");
+            return false;
+        }
+        ShellLine::Telecommands(line) => dispatch_batch(line, CommandSource::Ground),
+    }
+    send_umbilical_uart(shell::PROMPT.as_bytes());
+    true
+}
 
-                // When the errors got bigger, consider move into another function
-                ParsedTelecommandErr::ConfigError(e_conf) => {
-                    send_umbilical_uart(b"ERR: configuration error\r\n");
-                    match e_conf {
-                        ConfigError::ConfigVariableNotFound => {
-                            send_umbilical_uart(b"ERR: configuration variable not found\r\n");
-                        }
-                        ConfigError::ConfigVariableNotThisType => {
-                            send_umbilical_uart(
-                                b"ERR: configuration variable is not this type\r\n",
-                            );
-                        }
-                        ConfigError::ConfigVariableUnknownType => {
-                            send_umbilical_uart(
-                                b"ERR: unknown type for configuration variable\r\n",
-                            );
-                        }
-                        ConfigError::ConfigParseValueTypeError => {
-                            send_umbilical_uart(
-                                b"ERR: cannot parse the type with the value string\r\n",
-                            );
-                        }
-                    }
-                }
+/// Parse and run a line of telecommands separated by `;` (see `cts2_obc_telecommands::batch`).
+///
+/// Nothing runs unless the whole line parses. The telecommands then run in order, stopping at
+/// the first one that fails. When the line has several, each gets a `BATCH:` result line.
+fn dispatch_batch(line: &str, source: CommandSource) {
+    let mut msg = heapless::String::<128>::new();
+    let batch = match parse_batch(line) {
+        Ok(batch) => batch,
+        Err(BatchError::InvalidTelecommand { index, error }) => {
+            let count = split_batch(strip_comment(line)).count();
+            if count > 1 {
+                let _ = write_batch_invalid(&mut msg, index, count);
+                send_umbilical_uart(msg.as_bytes());
             }
-            return Err(e.into());
+            send_parse_error(&error);
+            let text = split_batch(strip_comment(line))
+                .nth(index)
+                .map(|(_, text)| text);
+            record_rejected(source, text.and_then(named_command), error.code());
+            return;
+        }
+        Err(e @ BatchError::TooManyTelecommands) => {
+            let _ = write_batch_too_long(&mut msg);
+            send_umbilical_uart(msg.as_bytes());
+            record_rejected(source, None, e.code());
+            return;
+        }
+    };
+
+    let count = batch.len();
+    for (index, cmd) in batch.iter().enumerate() {
+        let result = run_telecommand(cmd, source);
+        match result {
+            Ok(_) => rprintln!("Command executed successfully"),
+            Err(_) => rprintln!("Command execution failed"),
+        }
+
+        if count > 1 {
+            msg.clear();
+            let _ = write_batch_result(&mut msg, index, count, result.is_ok());
+            send_umbilical_uart(msg.as_bytes());
         }
+        if result.is_err() {
+            break;
+        }
+    }
+}
+
+/// Parse and run a telecommand. Responses are sent on the umbilical UART.
+pub fn dispatch_command(cmd_str: &str, source: CommandSource) -> Result<(), DispatchCommandErr> {
+    match parse_telecommand(cmd_str) {
+        Ok(cmd) => run_telecommand(cmd, source),
+        Err(e) => {
+            send_parse_error(&e);
+            record_rejected(source, named_command(cmd_str), e.code());
+            Err(e.into())
+        }
+    }
+}
+
+/// Report why a telecommand didn't parse.
+fn send_parse_error(e: &ParsedTelecommandErr) {
+    let mut msg = heapless::String::<128>::new();
+    let _ = write_parse_error(&mut msg, e);
+    send_umbilical_uart(msg.as_bytes());
+}
+
+/// Run a parsed telecommand, and record it in the command log with how long it took.
+fn run_telecommand(cmd: Telecommand, source: CommandSource) -> Result<(), DispatchCommandErr> {
+    let command = command_index(cmd.name());
+    let started_ms = uptime_ms();
+    let start = cycle_count();
+    let result = match source {
+        // Ground telecommands only arrive on the umbilical for now. Onboard ones keep the
+        // source of whatever runs them, e.g. FDIR.
+        CommandSource::Ground => with_change_source(ConfigChangeSource::Umbilical, || {
+            execute_telecommand(cmd, source)
+        }),
+        CommandSource::Onboard => execute_telecommand(cmd, source),
     };
+    let cycles = cycle_count().wrapping_sub(start);
+
+    let code = match &result {
+        Ok(()) => RESULT_OK,
+        Err(DispatchCommandErr::ParsedTelecommandError(e)) => e.code(),
+        Err(DispatchCommandErr::ExecuteCommandError(_)) => RESULT_FAILED,
+    };
+    record_command(started_ms, source, command, code, cycles);
+    result
+}
+
+// TODO: Make different functions to handle each separate command.
+// TODO: Fix the () error type to be enum or string
+// TODO: Replace with meaningful telecommands.
+/// Run a parsed telecommand.
+fn execute_telecommand(cmd: Telecommand, source: CommandSource) -> Result<(), DispatchCommandErr> {
+    if source == CommandSource::Ground {
+        // Hazardous telecommands need an `arm` first. Onboard ones were set up by the ground.
+        if let Err(e) = check_armed(cmd.name()) {
+            let mut msg = heapless::String::<96>::new();
+            let _ = write!(msg, "{}{} not run: {}\r\n", ERROR_PREFIX, cmd.name(), e);
+            send_umbilical_uart(msg.as_bytes());
+            return Err(ExecuteCommandErr::from(e).into());
+        }
+
+        // The ground can reach the OBC.
+        link_loss::command_received();
+    }
 
     match cmd {
         Telecommand::hello_world => run_hello_world_telecommand()?,
@@ -165,6 +330,48 @@ fn dispatch_command(cmd_str: &str) -> Result<(), DispatchCommandErr> {
         Telecommand::set_config(name, value) => {
             crate::telecommand_implementation::set_config_variable(name, value)?
         }
+        Telecommand::verify_staged_firmware => run_verify_staged_firmware()?,
+        Telecommand::mem_read(addr, len, format) => run_mem_read(addr, len, format)?,
+        Telecommand::mem_write(addr, bytes) => run_mem_write(addr, bytes)?,
+        Telecommand::mem_crc(addr, len) => run_mem_crc(addr, len)?,
+        Telecommand::mem_list_regions => run_mem_list_regions()?,
+        Telecommand::mem_set_region(addr, access) => run_mem_set_region(addr, access)?,
+        Telecommand::get_image_crc_status => run_get_image_crc_status()?,
+        Telecommand::get_events(count) => run_get_events(count)?,
+        Telecommand::get_seu_stats => run_get_seu_stats()?,
+        Telecommand::get_telemetry(name) => run_get_telemetry(&name)?,
+        Telecommand::list_telemetry => run_list_telemetry()?,
+        Telecommand::get_telemetry_many(names) => run_get_telemetry_many(&names)?,
+        Telecommand::subscribe(name, period_ms) => {
+            run_subscribe(&name, period_ms, Link::Umbilical)?
+        }
+        Telecommand::unsubscribe(name) => run_unsubscribe(&name, Link::Umbilical)?,
+        Telecommand::list_subscriptions => run_list_subscriptions()?,
+        Telecommand::set_mode(mode) => run_set_mode(mode)?,
+        Telecommand::fdir_list_rules => run_fdir_list_rules()?,
+        Telecommand::fdir_set_rule(args) => run_fdir_set_rule(args)?,
+        Telecommand::fdir_enable_rule(slot) => run_fdir_enable_rule(slot)?,
+        Telecommand::fdir_disable_rule(slot) => run_fdir_disable_rule(slot)?,
+        Telecommand::fdir_delete_rule(slot) => run_fdir_delete_rule(slot)?,
+        Telecommand::tx_disable => run_tx_disable()?,
+        Telecommand::tx_enable => run_tx_enable()?,
+        Telecommand::tx_disable_for(duration_s) => run_tx_disable_for(duration_s)?,
+        Telecommand::get_command_dictionary => {
+            crate::telecommand_implementation::run_get_command_dictionary()?
+        }
+        Telecommand::get_cmd_history(count) => run_get_cmd_history(count)?,
+        Telecommand::get_cmd_stats => run_get_cmd_stats()?,
+        Telecommand::arm(command, nonce) => run_arm(command, nonce)?,
+        Telecommand::get_config_all => run_get_config_all()?,
+        Telecommand::config_export => run_config_export()?,
+        Telecommand::config_import(blob) => run_config_import(&blob)?,
+        Telecommand::config_reset(name) => run_config_reset(name)?,
+        Telecommand::config_diff => run_config_diff()?,
+        Telecommand::set_config_tentative(name, value, timeout_s) => {
+            run_set_config_tentative(name, value, timeout_s)?
+        }
+        Telecommand::config_confirm => run_config_confirm()?,
+        Telecommand::get_config_audit(count) => run_get_config_audit(count)?,
     };
 
     Ok(())
@@ -175,8 +382,19 @@ fn dispatch_command(cmd_str: &str) -> Result<(), DispatchCommandErr> {
 /// Blocks during transmission.
 pub fn send_umbilical_uart(data: &[u8]) {
     let usart2 = unsafe { &*stm32_hal::stm32::USART2::ptr() };
+    UART_TX_BYTE_COUNT.fetch_add(data.len() as u32, Ordering::Relaxed);
     for &b in data {
         while usart2.isr.read().txe().bit_is_clear() {}
         usart2.tdr.write(|w| w.tdr().bits(b as u16));
     }
 }
+
+/// Writes text straight to the umbilical UART, e.g. for responses too long to build in a buffer.
+pub struct UmbilicalWriter;
+
+impl fmt::Write for UmbilicalWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        send_umbilical_uart(s.as_bytes());
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+//! Sending telecommands to the OBC and reading its responses, over any `Read + Write`
+//! transport: a serial port, a TCP bridge, or a stand-in for tests.
+//!
+//! The umbilical framing is one line per telecommand, ending with `\n`, and responses are
+//! lines ending with `\r\n`. A response has no end marker, so the transport should have a read
+//! timeout. A read that times out (or reaches the end of the stream) ends the response.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cts2_obc_telecommands::Telecommand;
+use cts2_obc_telecommands::batch::MAX_BATCH_LEN;
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::dictionary::{self, CommandInfo};
+use cts2_obc_telecommands::error::ParsedTelecommandErr;
+use cts2_obc_telecommands::format::MAX_TELECOMMAND_LEN;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::response::Response;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Transport error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Telecommand rejected: {0}")]
+    Rejected(ParsedTelecommandErr),
+
+    #[error("Telecommand failed: {0}")]
+    Failed(String),
+
+    #[error("Telecommands too long to send in one line")]
+    TooLong,
+
+    #[error("Unexpected response")]
+    UnexpectedResponse(Vec<Response>),
+}
+
+pub struct Client<T> {
+    transport: T,
+    /// Received bytes that don't make a whole line yet.
+    pending: Vec<u8>,
+    /// Nonce for the next `arm`. The OBC refuses the nonce of its last arm, so this starts
+    /// from the clock rather than 0, in case an earlier client already used 0.
+    next_nonce: u32,
+}
+
+impl<T: Read + Write> Client<T> {
+    pub fn new(transport: T) -> Self {
+        Self {
+            transport,
+            pending: Vec::new(),
+            next_nonce: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |t| t.subsec_nanos() ^ t.as_secs() as u32),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.transport
+    }
+
+    /// Send one line of text, as typed in a terminal, e.g. `hello_world; get_sys_uptime`.
+    pub fn send_text(&mut self, line: &str) -> Result<(), ClientError> {
+        if line.len() > MAX_TELECOMMAND_LEN || line.contains('\n') {
+            return Err(ClientError::TooLong);
+        }
+        self.transport.write_all(line.as_bytes())?;
+        self.transport.write_all(b"\n")?;
+        self.transport.flush()?;
+        Ok(())
+    }
+
+    /// Send a telecommand, in its canonical form.
+    pub fn send(&mut self, telecommand: &Telecommand) -> Result<(), ClientError> {
+        let text = telecommand
+            .to_canonical()
+            .map_err(|_| ClientError::TooLong)?;
+        self.send_text(&text)
+    }
+
+    /// Send several telecommands in one line. The OBC checks them all before running any.
+    pub fn send_batch(&mut self, telecommands: &[Telecommand]) -> Result<(), ClientError> {
+        if telecommands.len() > MAX_BATCH_LEN {
+            return Err(ClientError::TooLong);
+        }
+        let texts: Vec<String> = telecommands.iter().map(Telecommand::to_string).collect();
+        self.send_text(&texts.join("; "))
+    }
+
+    /// Read the next line. Returns `None` if no line arrives before the transport times out.
+    pub fn read_response(&mut self) -> Result<Option<Response>, ClientError> {
+        loop {
+            if let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                return Ok(Some(Response::parse(line.trim_end_matches(['\r', '\n']))));
+            }
+
+            let mut chunk = [0u8; 256];
+            match self.transport.read(&mut chunk) {
+                Ok(0) => return Ok(None),
+                Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+                Err(e) if matches!(e.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) => {
+                    return Ok(None);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Read lines until the transport times out.
+    pub fn read_responses(&mut self) -> Result<Vec<Response>, ClientError> {
+        let mut responses = Vec::new();
+        while let Some(response) = self.read_response()? {
+            responses.push(response);
+        }
+        Ok(responses)
+    }
+
+    /// Send a telecommand and read its response. An error line makes this fail.
+    pub fn command(&mut self, telecommand: &Telecommand) -> Result<Vec<Response>, ClientError> {
+        self.send(telecommand)?;
+        check(self.read_responses()?)
+    }
+
+    /// Send several telecommands in one line, and read their responses. A hazardous
+    /// telecommand in the line needs an [`Client::arm`] first.
+    pub fn batch(&mut self, telecommands: &[Telecommand]) -> Result<Vec<Response>, ClientError> {
+        self.send_batch(telecommands)?;
+        check(self.read_responses()?)
+    }
+
+    /// Allow the hazardous telecommand `command` to run once, within the OBC's arm window.
+    pub fn arm(&mut self, command: &'static CommandInfo) -> Result<(), ClientError> {
+        let nonce = self.next_nonce;
+        self.next_nonce = nonce.wrapping_add(1);
+        self.command(&Telecommand::arm(command, nonce))?;
+        Ok(())
+    }
+
+    /// Like [`Client::command`], but arms the telecommand first if it is hazardous.
+    pub fn command_armed(
+        &mut self,
+        telecommand: &Telecommand,
+    ) -> Result<Vec<Response>, ClientError> {
+        if let Some(command) =
+            dictionary::find_command(telecommand.name()).filter(|command| command.hazardous)
+        {
+            self.arm(command)?;
+        }
+        self.command(telecommand)
+    }
+
+    pub fn get_config(&mut self, name: ConfigVariableName) -> Result<ConfigValue, ClientError> {
+        let responses = self.command(&Telecommand::get_config(name))?;
+        find_config(&responses, name).ok_or(ClientError::UnexpectedResponse(responses))
+    }
+
+    pub fn set_config(
+        &mut self,
+        name: ConfigVariableName,
+        value: ConfigValue,
+    ) -> Result<(), ClientError> {
+        let responses = self.command_armed(&Telecommand::set_config(name, value))?;
+        match find_config(&responses, name) {
+            Some(set) if set == value => Ok(()),
+            _ => Err(ClientError::UnexpectedResponse(responses)),
+        }
+    }
+
+    /// Read a telemetry point. The name is checked by the OBC.
+    pub fn get_telemetry(&mut self, name: &str) -> Result<Value, ClientError> {
+        let name = name
+            .try_into()
+            .map_err(|_| ClientError::Rejected(ParsedTelecommandErr::InvalidArgument(0)))?;
+        let responses = self.command(&Telecommand::get_telemetry(name))?;
+        let found = responses.iter().find_map(|response| match response {
+            Response::Telemetry { value, .. } => Some(Ok(value.clone())),
+            Response::TelemetryError { error, .. } => Some(Err(error.clone())),
+            _ => None,
+        });
+        match found {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(error)) => Err(ClientError::Failed(error)),
+            None => Err(ClientError::UnexpectedResponse(responses)),
+        }
+    }
+}
+
+/// Fail if the responses say the telecommand was rejected or failed.
+fn check(responses: Vec<Response>) -> Result<Vec<Response>, ClientError> {
+    if responses.iter().any(|r| matches!(r, Response::Rejected(_))) {
+        let rejected = responses.into_iter().find_map(|response| match response {
+            Response::Rejected(e) => Some(e),
+            _ => None,
+        });
+        return Err(ClientError::Rejected(rejected.unwrap()));
+    }
+    let failed = responses.iter().find_map(|response| match response {
+        Response::Error(text) => Some(text.clone()),
+        Response::Batch { ok: false, .. } => Some("batch stopped".to_string()),
+        _ => None,
+    });
+    match failed {
+        Some(text) => Err(ClientError::Failed(text)),
+        None => Ok(responses),
+    }
+}
+
+fn find_config(responses: &[Response], name: ConfigVariableName) -> Option<ConfigValue> {
+    responses.iter().find_map(|response| match response {
+        Response::Config { name: n, value } if *n == name => Some(*value),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stand_in::StandInObc;
+    use cts2_obc_telecommands::mode::OperatingMode;
+
+    fn client() -> Client<StandInObc> {
+        Client::new(StandInObc::new())
+    }
+
+    #[test]
+    fn test_commands_end_to_end() {
+        let mut client = client();
+        assert_eq!(
+            client.command(&Telecommand::hello_world).unwrap(),
+            [Response::Text("HELLO WORLD".to_string())]
+        );
+
+        let name = ConfigVariableName::HeartbeatMs;
+        assert_eq!(client.get_config(name).unwrap(), ConfigValue::U32(1000));
+        client.set_config(name, ConfigValue::U32(250)).unwrap();
+        assert_eq!(client.get_config(name).unwrap(), ConfigValue::U32(250));
+        // Each arm has a fresh nonce.
+        client.set_config(name, ConfigValue::U32(500)).unwrap();
+        assert_eq!(client.get_config(name).unwrap(), ConfigValue::U32(500));
+
+        assert_eq!(
+            client.get_telemetry("uptime_ms").unwrap(),
+            Value::from(1234)
+        );
+        assert!(matches!(
+            client.get_telemetry("no_such_point"),
+            Err(ClientError::Failed(e)) if e == "Unknown telemetry point"
+        ));
+    }
+
+    #[test]
+    fn test_errors_end_to_end() {
+        let mut client = client();
+        // The OBC doesn't reply when a value has the wrong type.
+        assert!(matches!(
+            client.set_config(ConfigVariableName::HeartbeatMs, ConfigValue::Bool(true)),
+            Err(ClientError::UnexpectedResponse(responses)) if responses.is_empty()
+        ));
+
+        // Hazardous telecommands don't run without an arm.
+        assert!(matches!(
+            client.command(&Telecommand::set_config(
+                ConfigVariableName::HeartbeatMs,
+                ConfigValue::U32(250)
+            )),
+            Err(ClientError::Failed(e)) if e == "set_config not run: not armed"
+        ));
+        assert_eq!(
+            client.get_config(ConfigVariableName::HeartbeatMs).unwrap(),
+            ConfigValue::U32(1000)
+        );
+
+        client.send_text("get_events(x)").unwrap();
+        assert!(matches!(
+            check(client.read_responses().unwrap()),
+            Err(ClientError::Rejected(
+                ParsedTelecommandErr::InvalidArgument(0)
+            ))
+        ));
+
+        client.send_text("get_config(no_such_variable)").unwrap();
+        assert!(matches!(
+            check(client.read_responses().unwrap()),
+            Err(ClientError::Rejected(ParsedTelecommandErr::ConfigError(_)))
+        ));
+    }
+
+    #[test]
+    fn test_batch_end_to_end() {
+        let mut client = client();
+        let name = ConfigVariableName::LinkLossRebootS;
+        client
+            .arm(dictionary::find_command("set_config").unwrap())
+            .unwrap();
+        let responses = client
+            .batch(&[
+                Telecommand::set_config(name, ConfigValue::U32(60)),
+                Telecommand::get_config(name),
+            ])
+            .unwrap();
+        assert_eq!(
+            responses,
+            [
+                Response::Config {
+                    name,
+                    value: ConfigValue::U32(60)
+                },
+                Response::Batch {
+                    index: 0,
+                    count: 2,
+                    ok: true
+                },
+                Response::Config {
+                    name,
+                    value: ConfigValue::U32(60)
+                },
+                Response::Batch {
+                    index: 1,
+                    count: 2,
+                    ok: true
+                },
+            ]
+        );
+
+        // A bad telecommand rejects the whole line.
+        client
+            .send_text("set_config(link_loss_reboot_s, u32(5)); get_events(x)")
+            .unwrap();
+        assert!(matches!(
+            check(client.read_responses().unwrap()),
+            Err(ClientError::Rejected(
+                ParsedTelecommandErr::InvalidArgument(0)
+            ))
+        ));
+        assert_eq!(client.get_config(name).unwrap(), ConfigValue::U32(60));
+    }
+
+    #[test]
+    fn test_beacon_and_partial_lines() {
+        let mut obc = StandInObc::new();
+        obc.queue_output(
+            b"BEACON {\"mode\":\"nominal\",\"met_ms\":5000,\"boot_count\":2}\r\nHEART",
+        );
+        let mut client = Client::new(obc);
+
+        let Some(Response::Beacon(beacon)) = client.read_response().unwrap() else {
+            panic!("not a beacon");
+        };
+        assert_eq!(beacon.mode(), Some(OperatingMode::Nominal));
+        assert_eq!(beacon.met_ms(), Some(5000));
+
+        // The rest of a line can arrive later.
+        assert_eq!(client.read_response().unwrap(), None);
+        client.transport.queue_output(b"BEAT\r\n");
+        assert_eq!(
+            client.read_response().unwrap(),
+            Some(Response::Text("HEARTBEAT".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_too_long() {
+        let mut client = client();
+        let many = vec![Telecommand::hello_world; MAX_BATCH_LEN + 1];
+        assert!(matches!(
+            client.send_batch(&many),
+            Err(ClientError::TooLong)
+        ));
+        assert!(matches!(
+            client.send_text(&"x".repeat(MAX_TELECOMMAND_LEN + 1)),
+            Err(ClientError::TooLong)
+        ));
+    }
+}
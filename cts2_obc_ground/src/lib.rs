@@ -0,0 +1,26 @@
+//! Ground-side client for the OBC.
+//!
+//! Builds telecommands with the types from `cts2_obc_telecommands`, sends them over the
+//! umbilical framing on any `Read + Write` transport, and decodes the responses and beacons.
+//!
+//! ```no_run
+//! use cts2_obc_ground::Client;
+//! use cts2_obc_ground::telecommands::config::{ConfigValue, ConfigVariableName};
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let port = std::net::TcpStream::connect("localhost:4000")?;
+//! let mut client = Client::new(port);
+//! client.set_config(ConfigVariableName::HeartbeatMs, ConfigValue::U32(500))?;
+//! let uptime_ms = client.get_telemetry("uptime_ms")?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod client;
+pub mod response;
+
+#[cfg(test)]
+mod stand_in;
+
+pub use client::{Client, ClientError};
+pub use cts2_obc_telecommands as telecommands;
+pub use response::{Beacon, Response};
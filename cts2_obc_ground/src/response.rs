@@ -0,0 +1,279 @@
+//! Decoding the lines that the OBC sends on the umbilical UART.
+
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::error::{
+    ConfigError, ParsedTelecommandErr, SyntaxError, SyntaxErrorKind,
+};
+use cts2_obc_telecommands::mode::OperatingMode;
+use cts2_obc_telecommands::response::{BATCH_PREFIX, ERROR_PREFIX, config_error_text};
+use serde_json::{Map, Value};
+
+/// Start of a beacon line.
+const BEACON_PREFIX: &str = "BEACON ";
+
+const SYNTAX_ERROR_KINDS: [SyntaxErrorKind; 8] = [
+    SyntaxErrorKind::UnexpectedCharacter,
+    SyntaxErrorKind::UnexpectedEnd,
+    SyntaxErrorKind::UnterminatedString,
+    SyntaxErrorKind::InvalidEscape,
+    SyntaxErrorKind::MismatchedBracket,
+    SyntaxErrorKind::NestingTooDeep,
+    SyntaxErrorKind::EmptyArgument,
+    SyntaxErrorKind::PositionalAfterKeyword,
+];
+
+const CONFIG_ERRORS: [ConfigError; 4] = [
+    ConfigError::ConfigVariableNotFound,
+    ConfigError::ConfigParseValueTypeError,
+    ConfigError::ConfigVariableNotThisType,
+    ConfigError::ConfigVariableUnknownType,
+];
+
+/// One line from the OBC.
+#[derive(Debug, PartialEq)]
+pub enum Response {
+    /// The telecommand didn't parse, so it didn't run.
+    Rejected(ParsedTelecommandErr),
+    /// Any other error line, without the `ERR: ` prefix.
+    Error(String),
+    /// Result of telecommand `index` (from 0) of a line of `count` telecommands.
+    Batch {
+        index: usize,
+        count: usize,
+        ok: bool,
+    },
+    /// Value of a config variable, from `get_config` or `set_config`.
+    Config {
+        name: ConfigVariableName,
+        value: ConfigValue,
+    },
+    /// Value of a telemetry point.
+    Telemetry {
+        name: String,
+        value: Value,
+        units: String,
+    },
+    /// A telemetry point that could not be read.
+    TelemetryError {
+        name: String,
+        error: String,
+    },
+    Beacon(Beacon),
+    /// Any other JSON line, e.g. from `get_command_dictionary`.
+    Json(Value),
+    /// Any other line.
+    Text(String),
+}
+
+impl Response {
+    /// Decode a line, without its line ending.
+    pub fn parse(line: &str) -> Response {
+        if let Some(text) = line.strip_prefix(ERROR_PREFIX) {
+            return match parse_error(text) {
+                Some(e) => Response::Rejected(e),
+                None => Response::Error(text.to_string()),
+            };
+        }
+        if let Some(response) = line.strip_prefix(BATCH_PREFIX).and_then(parse_batch) {
+            return response;
+        }
+        if let Some(response) = line.strip_prefix("Variable: ").and_then(parse_config) {
+            return response;
+        }
+        if let Some(Ok(Value::Object(fields))) = line
+            .strip_prefix(BEACON_PREFIX)
+            .map(serde_json::from_str::<Value>)
+        {
+            return Response::Beacon(Beacon(fields));
+        }
+        match serde_json::from_str::<Value>(line) {
+            Ok(json) => parse_telemetry(&json).unwrap_or(Response::Json(json)),
+            Err(_) => Response::Text(line.to_string()),
+        }
+    }
+}
+
+/// Decode the text of an error line, as written by
+/// [`write_parse_error`](cts2_obc_telecommands::response::write_parse_error).
+///
+/// The details of a JSON deserialization error aren't sent, so it decodes as `None`.
+fn parse_error(text: &str) -> Option<ParsedTelecommandErr> {
+    let index = |prefix: &str| text.strip_prefix(prefix)?.parse::<u8>().ok();
+
+    match text {
+        "unknown command" => return Some(ParsedTelecommandErr::UnknownCommand),
+        "too many arguments provided" => return Some(ParsedTelecommandErr::ExceededArgumentCount),
+        _ => {}
+    }
+    if let Some(idx) = index("missing required argument at index ") {
+        return Some(ParsedTelecommandErr::MissingArgument(idx));
+    }
+    if let Some(idx) = index("invalid argument at index ") {
+        return Some(ParsedTelecommandErr::InvalidArgument(idx));
+    }
+    if let Some(idx) = index("unexpected keyword argument at index ") {
+        return Some(ParsedTelecommandErr::UnexpectedKeyword(idx));
+    }
+    if let Some(syntax) = text.strip_prefix("syntax error: ") {
+        let (kind, offset) = syntax.rsplit_once(" at offset ")?;
+        let kind = SYNTAX_ERROR_KINDS
+            .into_iter()
+            .find(|k| k.to_string() == kind)?;
+        let offset = offset.parse().ok()?;
+        return Some(ParsedTelecommandErr::Syntax(SyntaxError { offset, kind }));
+    }
+    CONFIG_ERRORS
+        .into_iter()
+        .find(|e| config_error_text(e) == text)
+        .map(ParsedTelecommandErr::ConfigError)
+}
+
+/// Decode `2/3 ok` or `2/3 failed, 1 not run`.
+fn parse_batch(text: &str) -> Option<Response> {
+    let (position, result) = text.split_once(' ')?;
+    let (number, count) = position.split_once('/')?;
+    let index = number.parse::<usize>().ok()?.checked_sub(1)?;
+    let count = count.parse().ok()?;
+    let ok = match result {
+        "ok" => true,
+        _ if result.starts_with("failed") => false,
+        _ => return None,
+    };
+    Some(Response::Batch { index, count, ok })
+}
+
+/// Decode `HeartbeatMs = U32(1000)` or `HeartbeatMs set to U32(1000)`. The firmware writes the
+/// name and value with `Debug`.
+fn parse_config(text: &str) -> Option<Response> {
+    let (name, value) = text
+        .split_once(" = ")
+        .or_else(|| text.split_once(" set to "))?;
+    let name = ConfigVariableName::ALL
+        .into_iter()
+        .find(|n| format!("{n:?}") == name)?;
+    let (value_type, rest) = value.split_once('(')?;
+    let value = format!("{}({rest}", value_type.to_lowercase())
+        .parse()
+        .ok()?;
+    Some(Response::Config { name, value })
+}
+
+/// Decode `{"name":..,"value":..,"units":..}` or `{"name":..,"error":..}`.
+fn parse_telemetry(json: &Value) -> Option<Response> {
+    let name = json.get("name")?.as_str()?.to_string();
+    if let Some(error) = json.get("error") {
+        let error = error.as_str()?.to_string();
+        return Some(Response::TelemetryError { name, error });
+    }
+    let value = json.get("value")?.clone();
+    let units = json.get("units")?.as_str()?.to_string();
+    Some(Response::Telemetry { name, value, units })
+}
+
+/// A beacon, e.g. `BEACON {"mode":"nominal","met_ms":1234,...}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Beacon(pub Map<String, Value>);
+
+impl Beacon {
+    /// Value of a telemetry point in the beacon.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    pub fn mode(&self) -> Option<OperatingMode> {
+        self.get("mode")?.as_str()?.parse().ok()
+    }
+
+    pub fn met_ms(&self) -> Option<u64> {
+        self.get("met_ms")?.as_u64()
+    }
+
+    pub fn boot_count(&self) -> Option<u64> {
+        self.get("boot_count")?.as_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cts2_obc_telecommands::response::write_parse_error;
+
+    #[test]
+    fn test_parse_errors_round_trip() {
+        let mut errors = vec![
+            ParsedTelecommandErr::UnknownCommand,
+            ParsedTelecommandErr::MissingArgument(1),
+            ParsedTelecommandErr::ExceededArgumentCount,
+            ParsedTelecommandErr::InvalidArgument(2),
+            ParsedTelecommandErr::UnexpectedKeyword(3),
+        ];
+        errors.extend(
+            SYNTAX_ERROR_KINDS
+                .map(|kind| ParsedTelecommandErr::Syntax(SyntaxError { offset: 12, kind })),
+        );
+        errors.extend(CONFIG_ERRORS.map(ParsedTelecommandErr::ConfigError));
+
+        for e in errors {
+            let mut text = String::new();
+            write_parse_error(&mut text, &e).unwrap();
+            // Config errors come after a `configuration error` line.
+            let last = text.trim_end().lines().last().unwrap();
+            assert_eq!(Response::parse(last), Response::Rejected(e));
+        }
+    }
+
+    #[test]
+    fn test_parse_responses() {
+        assert_eq!(
+            Response::parse("ERR: configuration error"),
+            Response::Error("configuration error".to_string())
+        );
+        assert_eq!(
+            Response::parse("BATCH: 2/3 failed, 1 not run"),
+            Response::Batch {
+                index: 1,
+                count: 3,
+                ok: false
+            }
+        );
+        assert_eq!(
+            Response::parse("Variable: LinkLossRebootS set to U32(7)"),
+            Response::Config {
+                name: ConfigVariableName::LinkLossRebootS,
+                value: ConfigValue::U32(7)
+            }
+        );
+        assert_eq!(
+            Response::parse(r#"{"name":"uptime_ms","value":1234,"units":"ms"}"#),
+            Response::Telemetry {
+                name: "uptime_ms".to_string(),
+                value: Value::from(1234),
+                units: "ms".to_string()
+            }
+        );
+        assert_eq!(
+            Response::parse(r#"{"name":"foo","error":"Unknown telemetry point"}"#),
+            Response::TelemetryError {
+                name: "foo".to_string(),
+                error: "Unknown telemetry point".to_string()
+            }
+        );
+        assert!(matches!(
+            Response::parse(r#"{"kind":"error","code":1}"#),
+            Response::Json(_)
+        ));
+        assert_eq!(
+            Response::parse("HELLO WORLD"),
+            Response::Text("HELLO WORLD".to_string())
+        );
+
+        let Response::Beacon(beacon) =
+            Response::parse(r#"BEACON {"mode":"safe","met_ms":1234,"boot_count":3}"#)
+        else {
+            panic!("not a beacon");
+        };
+        assert_eq!(beacon.mode(), Some(OperatingMode::Safe));
+        assert_eq!(beacon.met_ms(), Some(1234));
+        assert_eq!(beacon.boot_count(), Some(3));
+    }
+}
@@ -0,0 +1,183 @@
+//! An in-process stand-in for the OBC's umbilical UART, for testing the client end to end.
+//!
+//! It parses lines with the same code as the firmware and sends the same responses, but only
+//! runs a few telecommands. Hazardous telecommands need an `arm` first, as on the OBC; its
+//! clock stands still, so an arm doesn't expire.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{self, ErrorKind, Read, Write};
+
+use cts2_obc_logic::arming::CommandArm;
+use cts2_obc_telecommands::Telecommand;
+use cts2_obc_telecommands::batch::parse_batch;
+use cts2_obc_telecommands::config::{ConfigStore, ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::error::BatchError;
+use cts2_obc_telecommands::grammar::{split_batch, strip_comment};
+use cts2_obc_telecommands::response::{
+    ERROR_PREFIX, write_batch_invalid, write_batch_result, write_batch_too_long, write_parse_error,
+};
+
+pub struct StandInObc {
+    config: ConfigStore,
+    arm: CommandArm,
+    /// Received bytes that don't make a whole line yet.
+    line: Vec<u8>,
+    output: VecDeque<u8>,
+}
+
+impl StandInObc {
+    pub fn new() -> Self {
+        Self {
+            config: ConfigStore::new(),
+            arm: CommandArm::new(),
+            line: Vec::new(),
+            output: VecDeque::new(),
+        }
+    }
+
+    /// Send bytes to the client, e.g. a beacon.
+    pub fn queue_output(&mut self, data: &[u8]) {
+        self.output.extend(data);
+    }
+
+    fn send(&mut self, text: &str) {
+        self.queue_output(text.as_bytes());
+    }
+
+    /// Like the firmware's `dispatch_batch`.
+    fn dispatch_line(&mut self, line: &str) {
+        let mut msg = String::new();
+        let batch = match parse_batch(line) {
+            Ok(batch) => batch,
+            Err(BatchError::InvalidTelecommand { index, error }) => {
+                let count = split_batch(strip_comment(line)).count();
+                if count > 1 {
+                    write_batch_invalid(&mut msg, index, count).unwrap();
+                }
+                write_parse_error(&mut msg, &error).unwrap();
+                return self.send(&msg);
+            }
+            Err(BatchError::TooManyTelecommands) => {
+                write_batch_too_long(&mut msg).unwrap();
+                return self.send(&msg);
+            }
+        };
+
+        let count = batch.len();
+        for (index, telecommand) in batch.iter().enumerate() {
+            let ok = self.run(telecommand);
+            if count > 1 {
+                msg.clear();
+                write_batch_result(&mut msg, index, count, ok).unwrap();
+                self.send(&msg);
+            }
+            if !ok {
+                break;
+            }
+        }
+    }
+
+    /// Like the firmware's `check_armed`.
+    fn check_armed(&mut self, telecommand: &Telecommand) -> bool {
+        let window_ms = match self.config.get(ConfigVariableName::ArmWindowS) {
+            ConfigValue::U32(s) => s as u64 * 1000,
+            _ => 0,
+        };
+        match self.arm.check(telecommand.name(), 0, window_ms) {
+            Ok(()) => true,
+            Err(e) => {
+                let name = telecommand.name();
+                self.send(&format!("{}{} not run: {}\r\n", ERROR_PREFIX, name, e));
+                false
+            }
+        }
+    }
+
+    fn run(&mut self, telecommand: Telecommand) -> bool {
+        if !self.check_armed(&telecommand) {
+            return false;
+        }
+        let mut msg = String::new();
+        let ok = match telecommand {
+            Telecommand::hello_world => {
+                msg.push_str("HELLO WORLD\r\n");
+                true
+            }
+            Telecommand::arm(command, nonce) => match self.arm.arm(command, nonce, 0) {
+                Ok(()) => {
+                    write!(msg, "Armed: {}\r\n", command.name).unwrap();
+                    true
+                }
+                Err(e) => {
+                    write!(msg, "{}arm refused: {}\r\n", ERROR_PREFIX, e).unwrap();
+                    false
+                }
+            },
+            Telecommand::get_config(name) => {
+                let value = self.config.get(name);
+                write!(msg, "Variable: {:?} = {:?}\r\n", name, value).unwrap();
+                true
+            }
+            Telecommand::set_config(name, value) => match self.config.set(name, value) {
+                Ok(()) => {
+                    write!(msg, "Variable: {:?} set to {:?}\r\n", name, value).unwrap();
+                    true
+                }
+                Err(_) => false,
+            },
+            Telecommand::get_telemetry(name) if name == "uptime_ms" => {
+                msg.push_str("{\"name\":\"uptime_ms\",\"value\":1234,\"units\":\"ms\"}\r\n");
+                true
+            }
+            Telecommand::get_telemetry(name) => {
+                write!(
+                    msg,
+                    "{{\"name\":\"{}\",\"error\":\"Unknown telemetry point\"}}\r\n",
+                    name
+                )
+                .unwrap();
+                false
+            }
+            _ => {
+                msg.push_str("ERR: not run by the stand-in\r\n");
+                false
+            }
+        };
+        self.send(&msg);
+        ok
+    }
+}
+
+impl Write for StandInObc {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &b in buf {
+            if b == b'\n' {
+                let line = String::from_utf8_lossy(&self.line).into_owned();
+                self.line.clear();
+                self.dispatch_line(line.trim_end());
+            } else {
+                self.line.push(b);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for StandInObc {
+    /// Times out when there is nothing to read, like a serial port with a read timeout.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.output.is_empty() {
+            return Err(ErrorKind::TimedOut.into());
+        }
+        let n = buf.len().min(self.output.len());
+        for (dst, src) in buf.iter_mut().zip(self.output.drain(..n)) {
+            *dst = src;
+        }
+        Ok(n)
+    }
+}
@@ -0,0 +1,235 @@
+//! Host-side tool for producing signed firmware images for the OBC.
+//!
+//! See `docs/Firmware_Image_Signing.md` for the workflow. It also prints the command dictionary
+//! for ground software (see `docs/Command_Dictionary.md`).
+
+use std::process::ExitCode;
+
+use cts2_obc_logic::crc32::crc32;
+use cts2_obc_logic::firmware_image::{ImageManifest, PUBLIC_KEY_LEN, verify_image};
+use cts2_obc_logic::image_crc::IMAGE_INFO_FLASH_OFFSET;
+use cts2_obc_telecommands::dictionary;
+use ed25519_compact::{KeyPair, SecretKey};
+
+const USAGE: &str = "\
+Usage:
+  cts2_obc_image_tool embed-crc <firmware.bin>
+  cts2_obc_image_tool keygen <secret_key_out> <public_key_out>
+  cts2_obc_image_tool sign <secret_key> <image.bin> <firmware_version> <manifest_out>
+  cts2_obc_image_tool verify <public_key> <image.bin> <manifest>
+  cts2_obc_image_tool print-public-key <public_key>
+  cts2_obc_image_tool command-dictionary [dictionary_out]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["embed-crc", image] => embed_crc(image),
+        ["keygen", secret_key_out, public_key_out] => keygen(secret_key_out, public_key_out),
+        ["sign", secret_key, image, version, manifest_out] => {
+            sign(secret_key, image, version, manifest_out)
+        }
+        ["verify", public_key, image, manifest] => verify(public_key, image, manifest),
+        ["print-public-key", public_key] => print_public_key(public_key),
+        ["command-dictionary"] => {
+            print!("{}", command_dictionary());
+            Ok(())
+        }
+        ["command-dictionary", dictionary_out] => {
+            write_file(dictionary_out, command_dictionary().as_bytes())
+        }
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn embed_crc(image_path: &str) -> Result<(), String> {
+    let mut image = read_file(image_path)?;
+    let crc = embed_crc_in_image(&mut image)?;
+    write_file(image_path, &image)?;
+    println!("Embedded image CRC 0x{crc:08x} in {image_path}.");
+    Ok(())
+}
+
+fn keygen(secret_key_out: &str, public_key_out: &str) -> Result<(), String> {
+    let key_pair = KeyPair::generate();
+    write_file(secret_key_out, key_pair.sk.as_ref())?;
+    write_file(public_key_out, key_pair.pk.as_ref())?;
+    println!("Wrote secret key to {secret_key_out} and public key to {public_key_out}.");
+    println!("Keep the secret key off the OBC and out of version control.");
+    print_public_key(public_key_out)
+}
+
+fn sign(secret_key: &str, image: &str, version: &str, manifest_out: &str) -> Result<(), String> {
+    let secret_key = SecretKey::from_slice(&read_file(secret_key)?)
+        .map_err(|e| format!("Invalid secret key: {e}"))?;
+    let firmware_version = parse_version(version)?;
+    let image = read_file(image)?;
+
+    let manifest = sign_image(&secret_key, firmware_version, &image)?;
+    write_file(manifest_out, &manifest.to_bytes())?;
+    println!(
+        "Signed {} byte image as firmware version {:#010x}.",
+        manifest.image_size, manifest.firmware_version
+    );
+    Ok(())
+}
+
+fn verify(public_key: &str, image: &str, manifest: &str) -> Result<(), String> {
+    let public_key = read_public_key(public_key)?;
+    let image = read_file(image)?;
+    let manifest = ImageManifest::from_bytes(&read_file(manifest)?)
+        .map_err(|e| format!("Invalid manifest: {e}"))?;
+
+    verify_image(&manifest, &image, &public_key).map_err(|e| format!("REJECTED: {e}"))?;
+    println!(
+        "OK: firmware version {:#010x}, {} bytes.",
+        manifest.firmware_version, manifest.image_size
+    );
+    Ok(())
+}
+
+fn print_public_key(public_key: &str) -> Result<(), String> {
+    let public_key = read_public_key(public_key)?;
+    let bytes: Vec<String> = public_key.iter().map(|b| format!("0x{b:02x}")).collect();
+    println!("Public key, for `FIRMWARE_SIGNING_PUBLIC_KEY`:");
+    for line in bytes.chunks(8) {
+        println!("    {},", line.join(", "));
+    }
+    Ok(())
+}
+
+/// The command dictionary, one JSON object per line, as `get_command_dictionary` sends it.
+fn command_dictionary() -> String {
+    dictionary::entries()
+        .map(|entry| format!("{entry}\n"))
+        .collect()
+}
+
+/// Compute the CRC-32 of the application image and write it into the image info block.
+///
+/// `image` is a raw binary starting at the beginning of flash (`objcopy -O binary`). The
+/// linker has already written the image length into the block.
+fn embed_crc_in_image(image: &mut [u8]) -> Result<u32, String> {
+    let info = image
+        .get_mut(IMAGE_INFO_FLASH_OFFSET..IMAGE_INFO_FLASH_OFFSET + 8)
+        .ok_or("Binary does not contain the image info block. Is it a full flash image?")?;
+    let image_len = u32::from_le_bytes([info[0], info[1], info[2], info[3]]) as usize;
+    if image_len == 0 || image_len > IMAGE_INFO_FLASH_OFFSET {
+        return Err(format!(
+            "Image info block has an invalid length: {image_len}"
+        ));
+    }
+
+    let crc = crc32(&image[..image_len]);
+    image[IMAGE_INFO_FLASH_OFFSET + 4..IMAGE_INFO_FLASH_OFFSET + 8]
+        .copy_from_slice(&crc.to_le_bytes());
+    Ok(crc)
+}
+
+/// Build and sign a manifest for `image`. The result is verified before being returned.
+fn sign_image(
+    secret_key: &SecretKey,
+    firmware_version: u32,
+    image: &[u8],
+) -> Result<ImageManifest, String> {
+    if u32::try_from(image.len()).is_err() {
+        return Err("Image is too large".to_string());
+    }
+
+    let mut manifest = ImageManifest::new_unsigned(firmware_version, image);
+    manifest.signature = *secret_key.sign(manifest.signed_bytes(), None);
+
+    verify_image(&manifest, image, &secret_key.public_key())
+        .map_err(|e| format!("Self-check of the new signature failed: {e}"))?;
+    Ok(manifest)
+}
+
+/// Parse a firmware version given in decimal or as `0x`-prefixed hex.
+fn parse_version(s: &str) -> Result<u32, String> {
+    let parsed = match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse::<u32>(),
+    };
+    parsed.map_err(|_| format!("Invalid firmware version: {s}"))
+}
+
+fn read_public_key(path: &str) -> Result<[u8; PUBLIC_KEY_LEN], String> {
+    read_file(path)?
+        .try_into()
+        .map_err(|_| format!("{path} is not a {PUBLIC_KEY_LEN} byte public key"))
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|e| format!("Cannot read {path}: {e}"))
+}
+
+fn write_file(path: &str, data: &[u8]) -> Result<(), String> {
+    std::fs::write(path, data).map_err(|e| format!("Cannot write {path}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cts2_obc_logic::firmware_image::ImageVerifyError;
+    use ed25519_compact::Seed;
+
+    #[test]
+    fn test_sign_image_verifies() {
+        let key_pair = KeyPair::from_seed(Seed::new([0x33; 32]));
+        let image = b"firmware image bytes";
+
+        let manifest = sign_image(&key_pair.sk, 7, image).unwrap();
+        assert_eq!(manifest.firmware_version, 7);
+        assert_eq!(verify_image(&manifest, image, &key_pair.pk), Ok(()));
+
+        let other = KeyPair::from_seed(Seed::new([0x44; 32]));
+        assert_eq!(
+            verify_image(&manifest, image, &other.pk),
+            Err(ImageVerifyError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_embed_crc_in_image() {
+        let mut image = vec![0u8; IMAGE_INFO_FLASH_OFFSET + 8];
+        image[..5].copy_from_slice(b"hello");
+        image[IMAGE_INFO_FLASH_OFFSET..IMAGE_INFO_FLASH_OFFSET + 8]
+            .copy_from_slice(&[5, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+
+        let crc = embed_crc_in_image(&mut image).unwrap();
+        assert_eq!(crc, crc32(b"hello"));
+        assert_eq!(
+            image[IMAGE_INFO_FLASH_OFFSET + 4..],
+            crc32(b"hello").to_le_bytes()
+        );
+
+        assert!(embed_crc_in_image(&mut [0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn test_command_dictionary_is_json_lines() {
+        let dictionary = command_dictionary();
+        assert!(dictionary.starts_with(r#"{"kind":"command","name":"hello_world","#));
+        assert!(
+            dictionary
+                .lines()
+                .all(|line| line.starts_with("{\"kind\":"))
+        );
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("42"), Ok(42));
+        assert_eq!(parse_version("0x00010203"), Ok(0x0001_0203));
+        assert!(parse_version("v1").is_err());
+    }
+}
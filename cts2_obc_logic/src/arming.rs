@@ -0,0 +1,204 @@
+//! Arm-then-execute protection for hazardous telecommands.
+//!
+//! A telecommand marked `hazardous` in the command dictionary only runs from the ground if an
+//! `arm(command_name, nonce)` naming it came first, within the arm window. The arm is used up
+//! by the next hazardous telecommand, whether or not it matches, and is cleared when it
+//! expires or the operating mode changes.
+
+use cts2_obc_telecommands::dictionary::{self, CommandInfo};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ArmError {
+    #[error("not armed")]
+    NotArmed,
+
+    #[error("arm expired")]
+    Expired,
+
+    #[error("armed for another telecommand")]
+    WrongCommand,
+
+    #[error("nonce already used")]
+    NonceReused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Armed {
+    command: &'static CommandInfo,
+    armed_at_ms: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct CommandArm {
+    armed: Option<Armed>,
+    /// Nonce of the last arm, which can't be used again, so that a recorded arm can't be
+    /// replayed.
+    last_nonce: Option<u32>,
+}
+
+impl CommandArm {
+    pub const fn new() -> Self {
+        Self {
+            armed: None,
+            last_nonce: None,
+        }
+    }
+
+    /// Arm `command`, replacing any earlier arm. Fails if `nonce` is the last arm's nonce.
+    pub fn arm(
+        &mut self,
+        command: &'static CommandInfo,
+        nonce: u32,
+        now_ms: u64,
+    ) -> Result<(), ArmError> {
+        if self.last_nonce == Some(nonce) {
+            return Err(ArmError::NonceReused);
+        }
+        self.last_nonce = Some(nonce);
+        self.armed = Some(Armed {
+            command,
+            armed_at_ms: now_ms,
+        });
+        Ok(())
+    }
+
+    /// Check that the telecommand named `name` may run. Telecommands that aren't hazardous
+    /// always may, and leave the arm alone. A hazardous one uses up the arm.
+    pub fn check(&mut self, name: &str, now_ms: u64, window_ms: u64) -> Result<(), ArmError> {
+        if !dictionary::find_command(name).is_some_and(|command| command.hazardous) {
+            return Ok(());
+        }
+        let armed = self.armed.take().ok_or(ArmError::NotArmed)?;
+        if now_ms.saturating_sub(armed.armed_at_ms) > window_ms {
+            return Err(ArmError::Expired);
+        }
+        if armed.command.name != name {
+            return Err(ArmError::WrongCommand);
+        }
+        Ok(())
+    }
+
+    /// Clear the arm, e.g. on a mode change.
+    pub fn disarm(&mut self) {
+        self.armed = None;
+    }
+
+    /// The armed telecommand, if the arm hasn't expired.
+    pub fn armed(&self, now_ms: u64, window_ms: u64) -> Option<&'static CommandInfo> {
+        self.armed
+            .filter(|armed| now_ms.saturating_sub(armed.armed_at_ms) <= window_ms)
+            .map(|armed| armed.command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW_MS: u64 = 60_000;
+
+    fn command(name: &str) -> &'static CommandInfo {
+        dictionary::find_command(name).unwrap()
+    }
+
+    #[test]
+    fn test_arm_is_used_once() {
+        let mut arm = CommandArm::new();
+        assert_eq!(
+            arm.check("set_config", 0, WINDOW_MS),
+            Err(ArmError::NotArmed)
+        );
+
+        arm.arm(command("set_config"), 1, 1_000).unwrap();
+        assert_eq!(arm.armed(2_000, WINDOW_MS), Some(command("set_config")));
+        // Telecommands that aren't hazardous don't use up the arm.
+        assert_eq!(arm.check("hello_world", 2_000, WINDOW_MS), Ok(()));
+        assert_eq!(arm.check("set_config", 3_000, WINDOW_MS), Ok(()));
+        assert_eq!(
+            arm.check("set_config", 4_000, WINDOW_MS),
+            Err(ArmError::NotArmed)
+        );
+        assert_eq!(arm.check("hello_world", 4_000, WINDOW_MS), Ok(()));
+    }
+
+    #[test]
+    fn test_arm_expires() {
+        let mut arm = CommandArm::new();
+        arm.arm(command("tx_disable"), 1, 1_000).unwrap();
+        assert_eq!(
+            arm.armed(1_000 + WINDOW_MS, WINDOW_MS),
+            Some(command("tx_disable"))
+        );
+        assert_eq!(arm.armed(1_001 + WINDOW_MS, WINDOW_MS), None);
+        assert_eq!(
+            arm.check("tx_disable", 1_001 + WINDOW_MS, WINDOW_MS),
+            Err(ArmError::Expired)
+        );
+        // The expired arm is gone.
+        assert_eq!(
+            arm.check("tx_disable", 1_002 + WINDOW_MS, WINDOW_MS),
+            Err(ArmError::NotArmed)
+        );
+
+        arm.arm(command("tx_disable"), 2, 100_000).unwrap();
+        assert_eq!(
+            arm.check("tx_disable", 100_000 + WINDOW_MS, WINDOW_MS),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_arm_mismatch() {
+        let mut arm = CommandArm::new();
+        arm.arm(command("tx_disable"), 1, 0).unwrap();
+        assert_eq!(
+            arm.check("set_config", 10, WINDOW_MS),
+            Err(ArmError::WrongCommand)
+        );
+        // A mismatch uses up the arm too.
+        assert_eq!(
+            arm.check("tx_disable", 20, WINDOW_MS),
+            Err(ArmError::NotArmed)
+        );
+
+        // A later arm replaces an earlier one.
+        arm.arm(command("tx_disable"), 2, 30).unwrap();
+        arm.arm(command("set_config"), 3, 40).unwrap();
+        assert_eq!(
+            arm.check("tx_disable", 50, WINDOW_MS),
+            Err(ArmError::WrongCommand)
+        );
+    }
+
+    #[test]
+    fn test_mode_and_firmware_changes_need_arm() {
+        // `set_mode(safe)` resets the config, and `verify_staged_firmware` marks an image
+        // bootable.
+        let mut arm = CommandArm::new();
+        for (nonce, name) in [(1, "set_mode"), (2, "verify_staged_firmware")] {
+            assert_eq!(arm.check(name, 0, WINDOW_MS), Err(ArmError::NotArmed));
+            arm.arm(command(name), nonce, 0).unwrap();
+            assert_eq!(arm.check(name, 10, WINDOW_MS), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_arm_nonce_and_disarm() {
+        let mut arm = CommandArm::new();
+        arm.arm(command("set_config"), 7, 0).unwrap();
+        assert_eq!(
+            arm.arm(command("set_config"), 7, 10),
+            Err(ArmError::NonceReused)
+        );
+        assert_eq!(arm.check("set_config", 20, WINDOW_MS), Ok(()));
+
+        arm.arm(command("set_config"), 8, 30).unwrap();
+        arm.disarm();
+        assert_eq!(arm.armed(40, WINDOW_MS), None);
+        assert_eq!(
+            arm.check("set_config", 40, WINDOW_MS),
+            Err(ArmError::NotArmed)
+        );
+    }
+}
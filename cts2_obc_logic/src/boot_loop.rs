@@ -0,0 +1,146 @@
+//! Boot-loop detection.
+//!
+//! Every boot is counted as a fast reset until it has been up for `STABLE_UPTIME_MS`. After
+//! `MAX_FAST_RESETS` fast resets in a row, the OBC starts in safe mode.
+
+use cts2_obc_telecommands::mode::OperatingMode;
+
+use crate::crc32::crc32;
+
+/// A boot that stays up this long is considered stable.
+pub const STABLE_UPTIME_MS: u64 = 30_000;
+
+/// Number of consecutive fast resets after which the OBC starts in safe mode.
+pub const MAX_FAST_RESETS: u32 = 3;
+
+const BOOT_RECORD_MAGIC: u32 = 0xB007_1005;
+
+/// Number of words in a stored boot record.
+pub const BOOT_RECORD_WORDS: usize = 4;
+
+/// Boot history, kept in memory that survives a reset (but not a power cycle).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BootRecord {
+    /// Boots since the last power cycle, including this one.
+    pub boot_count: u32,
+    /// Consecutive boots, including this one, that have not (yet) been up for
+    /// `STABLE_UPTIME_MS`.
+    pub fast_reset_count: u32,
+}
+
+impl BootRecord {
+    fn check_word(boot_count: u32, fast_reset_count: u32) -> u32 {
+        let mut bytes = [0u8; 12];
+        bytes[..4].copy_from_slice(&BOOT_RECORD_MAGIC.to_le_bytes());
+        bytes[4..8].copy_from_slice(&boot_count.to_le_bytes());
+        bytes[8..].copy_from_slice(&fast_reset_count.to_le_bytes());
+        crc32(&bytes)
+    }
+
+    pub fn to_words(&self) -> [u32; BOOT_RECORD_WORDS] {
+        [
+            BOOT_RECORD_MAGIC,
+            self.boot_count,
+            self.fast_reset_count,
+            Self::check_word(self.boot_count, self.fast_reset_count),
+        ]
+    }
+
+    /// Decode a stored record. Returns `None` if the words don't hold a valid record, such as
+    /// the random RAM contents after a power cycle.
+    pub fn from_words(words: &[u32; BOOT_RECORD_WORDS]) -> Option<Self> {
+        let [magic, boot_count, fast_reset_count, check] = *words;
+        if magic != BOOT_RECORD_MAGIC || check != Self::check_word(boot_count, fast_reset_count) {
+            return None;
+        }
+        Some(Self {
+            boot_count,
+            fast_reset_count,
+        })
+    }
+
+    /// The record once this boot has been up for `STABLE_UPTIME_MS`.
+    pub const fn mark_stable(&self) -> Self {
+        Self {
+            boot_count: self.boot_count,
+            fast_reset_count: 0,
+        }
+    }
+}
+
+/// Decide which mode to start in, given the record left by the previous boot.
+///
+/// Returns the record to store for this boot, and the mode to start in.
+pub fn on_boot(previous: Option<BootRecord>, max_fast_resets: u32) -> (BootRecord, OperatingMode) {
+    let previous = previous.unwrap_or_default();
+    let mode = if previous.fast_reset_count >= max_fast_resets {
+        OperatingMode::Safe
+    } else {
+        OperatingMode::Nominal
+    };
+
+    let record = BootRecord {
+        boot_count: previous.boot_count.wrapping_add(1),
+        fast_reset_count: previous.fast_reset_count.saturating_add(1),
+    };
+    (record, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_on_boot_is_nominal() {
+        let (record, mode) = on_boot(None, MAX_FAST_RESETS);
+        assert_eq!(mode, OperatingMode::Nominal);
+        assert_eq!(
+            record,
+            BootRecord {
+                boot_count: 1,
+                fast_reset_count: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_fast_resets_enter_safe_mode() {
+        let mut record = None;
+        let mut modes = std::vec::Vec::new();
+        for _ in 0..5 {
+            let (new_record, mode) = on_boot(record, 3);
+            record = Some(new_record);
+            modes.push(mode);
+        }
+
+        use OperatingMode::{Nominal, Safe};
+        assert_eq!(modes, [Nominal, Nominal, Nominal, Safe, Safe]);
+        assert_eq!(record.unwrap().boot_count, 5);
+    }
+
+    #[test]
+    fn test_stable_boot_clears_fast_resets() {
+        let (record, _) = on_boot(None, 3);
+        let (record, _) = on_boot(Some(record), 3);
+        let (record, _) = on_boot(Some(record), 3);
+        let (record, mode) = on_boot(Some(record.mark_stable()), 3);
+
+        assert_eq!(mode, OperatingMode::Nominal);
+        assert_eq!(record.fast_reset_count, 1);
+        assert_eq!(record.boot_count, 4);
+    }
+
+    #[test]
+    fn test_record_words_round_trip_and_reject_corruption() {
+        let record = BootRecord {
+            boot_count: 7,
+            fast_reset_count: 2,
+        };
+        let mut words = record.to_words();
+        assert_eq!(BootRecord::from_words(&words), Some(record));
+
+        words[1] ^= 1 << 4;
+        assert_eq!(BootRecord::from_words(&words), None);
+        assert_eq!(BootRecord::from_words(&[0xFFFF_FFFF; 4]), None);
+    }
+}
@@ -0,0 +1,235 @@
+//! Record of the telecommands the OBC was sent: the most recent ones, and counts per command.
+
+use cts2_obc_telecommands::dictionary::COMMANDS;
+use heapless::Deque;
+
+/// Result code of a telecommand that ran without error.
+pub const RESULT_OK: u8 = 0;
+
+/// Result code of a telecommand that parsed, but failed when it ran. A telecommand that didn't
+/// parse has the code of its `ParsedTelecommandErr` instead.
+pub const RESULT_FAILED: u8 = 32;
+
+/// Where a telecommand came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    /// Sent by the ground (or the bench), on a link.
+    Ground,
+    /// Run by the OBC itself, e.g. as an FDIR action.
+    Onboard,
+}
+
+impl CommandSource {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            CommandSource::Ground => "ground",
+            CommandSource::Onboard => "onboard",
+        }
+    }
+}
+
+/// One telecommand that was received.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandRecord {
+    pub uptime_ms: u64,
+    pub source: CommandSource,
+    /// Index in `dictionary::COMMANDS`, or `None` if the name wasn't a telecommand.
+    pub command: Option<u8>,
+    /// [`RESULT_OK`], [`RESULT_FAILED`], or the parse error code.
+    pub result: u8,
+    /// DWT cycles taken to run it. 0 if it didn't run.
+    pub cycles: u32,
+}
+
+impl CommandRecord {
+    /// Name of the telecommand, or `?` if it wasn't one.
+    pub fn name(&self) -> &'static str {
+        self.command
+            .and_then(|index| COMMANDS.get(index as usize))
+            .map_or("?", |command| command.name)
+    }
+}
+
+/// Index in `dictionary::COMMANDS` of the telecommand with this name.
+pub fn command_index(name: &str) -> Option<u8> {
+    COMMANDS
+        .iter()
+        .position(|command| command.name == name)
+        .map(|index| index as u8)
+}
+
+/// Index of the telecommand that a line names, e.g. `get_events` for `get_events(x)`, even if
+/// its arguments are wrong.
+pub fn named_command(text: &str) -> Option<u8> {
+    let name = text
+        .trim_start()
+        .split(|c: char| c == '(' || c.is_whitespace())
+        .next()?;
+    command_index(name)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandStats {
+    pub received: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+}
+
+impl CommandStats {
+    const fn new() -> Self {
+        Self {
+            received: 0,
+            succeeded: 0,
+            failed: 0,
+        }
+    }
+
+    fn count(&mut self, result: u8) {
+        self.received = self.received.wrapping_add(1);
+        if result == RESULT_OK {
+            self.succeeded = self.succeeded.wrapping_add(1);
+        } else {
+            self.failed = self.failed.wrapping_add(1);
+        }
+    }
+}
+
+/// The last `N` telecommands, and counts since boot.
+#[derive(Debug)]
+pub struct CommandLog<const N: usize> {
+    history: Deque<CommandRecord, N>,
+    /// Per telecommand, in the order of `dictionary::COMMANDS`.
+    stats: [CommandStats; COMMANDS.len()],
+    totals: CommandStats,
+}
+
+impl<const N: usize> CommandLog<N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            history: Deque::new(),
+            stats: [CommandStats::new(); COMMANDS.len()],
+            totals: CommandStats::new(),
+        }
+    }
+
+    /// Record a telecommand. The oldest record is dropped when the log is full.
+    pub fn record(&mut self, record: CommandRecord) {
+        if self.history.is_full() {
+            self.history.pop_front();
+        }
+        let _ = self.history.push_back(record);
+
+        if let Some(stats) = record
+            .command
+            .and_then(|index| self.stats.get_mut(index as usize))
+        {
+            stats.count(record.result);
+        }
+        self.totals.count(record.result);
+    }
+
+    /// Up to `n` records, newest first.
+    pub fn newest(&self, n: usize) -> impl Iterator<Item = &CommandRecord> {
+        self.history.iter().rev().take(n)
+    }
+
+    /// Counts of every telecommand that was received at least once, with its name.
+    pub fn stats(&self) -> impl Iterator<Item = (&'static str, CommandStats)> + '_ {
+        COMMANDS
+            .iter()
+            .zip(self.stats)
+            .filter(|(_, stats)| stats.received > 0)
+            .map(|(command, stats)| (command.name, stats))
+    }
+
+    /// Counts of all telecommands, including names that weren't telecommands.
+    pub const fn totals(&self) -> CommandStats {
+        self.totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn record(name: &str, result: u8) -> CommandRecord {
+        CommandRecord {
+            uptime_ms: 0,
+            source: CommandSource::Ground,
+            command: command_index(name),
+            result,
+            cycles: 100,
+        }
+    }
+
+    #[test]
+    fn test_command_log_history() {
+        let mut log = CommandLog::<3>::new();
+        for name in [
+            "hello_world",
+            "get_sys_uptime",
+            "get_events",
+            "get_seu_stats",
+        ] {
+            log.record(record(name, RESULT_OK));
+        }
+        let names: Vec<&str> = log.newest(10).map(CommandRecord::name).collect();
+        assert_eq!(names, ["get_seu_stats", "get_events", "get_sys_uptime"]);
+        assert_eq!(log.newest(1).count(), 1);
+
+        log.record(record("no_such_command", 1));
+        assert_eq!(log.newest(1).next().unwrap().name(), "?");
+    }
+
+    #[test]
+    fn test_named_command() {
+        assert_eq!(named_command(" get_events(x)"), command_index("get_events"));
+        assert_eq!(named_command("hello_world"), command_index("hello_world"));
+        assert!(named_command("hello_world").is_some());
+        assert_eq!(named_command("nope(1)"), None);
+        assert_eq!(named_command(""), None);
+    }
+
+    #[test]
+    fn test_command_log_stats() {
+        let mut log = CommandLog::<3>::new();
+        log.record(record("set_config", RESULT_OK));
+        log.record(record("set_config", RESULT_FAILED));
+        log.record(record("set_config", 16));
+        log.record(record("hello_world", RESULT_OK));
+        log.record(record("no_such_command", 1));
+
+        let stats: Vec<(&str, CommandStats)> = log.stats().collect();
+        assert_eq!(
+            stats,
+            [
+                (
+                    "hello_world",
+                    CommandStats {
+                        received: 1,
+                        succeeded: 1,
+                        failed: 0
+                    }
+                ),
+                (
+                    "set_config",
+                    CommandStats {
+                        received: 3,
+                        succeeded: 1,
+                        failed: 2
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            log.totals(),
+            CommandStats {
+                received: 5,
+                succeeded: 2,
+                failed: 3
+            }
+        );
+    }
+}
@@ -0,0 +1,350 @@
+//! Config audit trail: who changed which config variable, when, and from what to what.
+//!
+//! The trail is kept in flash, so that it survives a power cycle. It takes
+//! [`CONFIG_AUDIT_PAGES`] pages, written in turn: when the page being written is full, the
+//! oldest one is erased and written next. Each page starts with a sequence number, to tell
+//! the newest page, and each record has its own CRC, so that a record cut short by a reset
+//! while it was written is skipped.
+
+use cts2_obc_telecommands::config::{ConfigValue, ConfigVariableName};
+
+use crate::config_blob::{decode_value, encode_value};
+use crate::crc32::crc32;
+use crate::page_flash::{FLASH_PAGE_WORDS, PageFlash};
+
+const CONFIG_AUDIT_MAGIC: u32 = 0xC0F1_A0D1;
+
+/// Number of words in a stored audit record.
+pub const CONFIG_AUDIT_RECORD_WORDS: usize = 6;
+
+/// Words in each page of the trail.
+pub const CONFIG_AUDIT_PAGE_WORDS: usize = FLASH_PAGE_WORDS;
+
+/// Flash pages the trail takes.
+pub const CONFIG_AUDIT_PAGES: usize = 2;
+
+/// Words at the start of a page: its sequence number, then its complement.
+const HEADER_WORDS: usize = 2;
+
+/// Records that fit in a page.
+pub const CONFIG_AUDIT_RECORDS_PER_PAGE: usize =
+    (CONFIG_AUDIT_PAGE_WORDS - HEADER_WORDS) / CONFIG_AUDIT_RECORD_WORDS;
+
+const ERASED: u32 = 0xFFFF_FFFF;
+
+/// What made a config change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ConfigChangeSource {
+    /// A telecommand on the umbilical UART.
+    Umbilical = 0,
+    /// A telecommand on the radio.
+    Radio = 1,
+    /// A stored command sequence.
+    Sequence = 2,
+    /// An FDIR rule, through its action.
+    Fdir = 3,
+    /// The OBC itself, e.g. a tentative change that timed out.
+    Onboard = 4,
+}
+
+impl ConfigChangeSource {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ConfigChangeSource::Umbilical => "umbilical",
+            ConfigChangeSource::Radio => "radio",
+            ConfigChangeSource::Sequence => "sequence",
+            ConfigChangeSource::Fdir => "fdir",
+            ConfigChangeSource::Onboard => "onboard",
+        }
+    }
+
+    const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(ConfigChangeSource::Umbilical),
+            1 => Some(ConfigChangeSource::Radio),
+            2 => Some(ConfigChangeSource::Sequence),
+            3 => Some(ConfigChangeSource::Fdir),
+            4 => Some(ConfigChangeSource::Onboard),
+            _ => None,
+        }
+    }
+}
+
+/// One change of a config variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfigAuditRecord {
+    /// Mission-elapsed time of the change.
+    pub met_ms: u64,
+    pub name: ConfigVariableName,
+    pub old: ConfigValue,
+    pub new: ConfigValue,
+    pub source: ConfigChangeSource,
+}
+
+impl ConfigAuditRecord {
+    fn check_word(words: &[u32]) -> u32 {
+        let mut bytes = [0u8; 4 * CONFIG_AUDIT_RECORD_WORDS];
+        bytes[..4].copy_from_slice(&CONFIG_AUDIT_MAGIC.to_le_bytes());
+        for (chunk, word) in bytes[4..].chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        crc32(&bytes)
+    }
+
+    pub fn to_words(&self) -> [u32; CONFIG_AUDIT_RECORD_WORDS] {
+        let (old_tag, old_bits) = encode_value(self.old);
+        let (new_tag, new_bits) = encode_value(self.new);
+        let packed =
+            u32::from_le_bytes([self.name.index() as u8, old_tag, new_tag, self.source as u8]);
+        let mut words = [
+            self.met_ms as u32,
+            (self.met_ms >> 32) as u32,
+            packed,
+            old_bits,
+            new_bits,
+            0,
+        ];
+        words[5] = Self::check_word(&words[..5]);
+        words
+    }
+
+    /// Decode a stored record. Returns `None` if the words don't hold a valid record.
+    pub fn from_words(words: &[u32; CONFIG_AUDIT_RECORD_WORDS]) -> Option<Self> {
+        let [met_lo, met_hi, packed, old_bits, new_bits, check] = *words;
+        if check != Self::check_word(&words[..5]) {
+            return None;
+        }
+        let [index, old_tag, new_tag, source] = packed.to_le_bytes();
+        Some(Self {
+            met_ms: ((met_hi as u64) << 32) | met_lo as u64,
+            name: *ConfigVariableName::ALL.get(index as usize)?,
+            old: decode_value(old_tag, old_bits).ok()?,
+            new: decode_value(new_tag, new_bits).ok()?,
+            source: ConfigChangeSource::from_u8(source)?,
+        })
+    }
+}
+
+/// The config audit trail, in flash.
+#[derive(Debug)]
+pub struct ConfigAuditLog<F> {
+    flash: F,
+    /// Page being written.
+    page: usize,
+    /// Sequence number of `page`. The page before it has the number before, and so on.
+    seq: u32,
+    /// Slot in `page` that the next record goes in.
+    next: usize,
+}
+
+impl<F: PageFlash> ConfigAuditLog<F> {
+    /// Pick up the trail in `flash` where it left off, or start a new one if there is none.
+    pub fn open(mut flash: F) -> Result<Self, F::Error> {
+        let newest = (0..CONFIG_AUDIT_PAGES)
+            .filter_map(|page| page_seq(&flash, page).map(|seq| (page, seq)))
+            .max_by_key(|(_, seq)| *seq);
+        let Some((page, seq)) = newest else {
+            start_page(&mut flash, 0, 0)?;
+            return Ok(Self {
+                flash,
+                page: 0,
+                seq: 0,
+                next: 0,
+            });
+        };
+
+        // Slots are written in order, so the first erased one is next. A slot that was cut
+        // short isn't erased, so it is skipped.
+        let next = (0..CONFIG_AUDIT_RECORDS_PER_PAGE)
+            .rev()
+            .take_while(|&slot| {
+                read_slot(&flash, page, slot) == [ERASED; CONFIG_AUDIT_RECORD_WORDS]
+            })
+            .last()
+            .unwrap_or(CONFIG_AUDIT_RECORDS_PER_PAGE);
+        Ok(Self {
+            flash,
+            page,
+            seq,
+            next,
+        })
+    }
+
+    /// Add a record, erasing the oldest page first if the current one is full.
+    pub fn push(&mut self, record: &ConfigAuditRecord) -> Result<(), F::Error> {
+        if self.next == CONFIG_AUDIT_RECORDS_PER_PAGE {
+            let page = (self.page + 1) % CONFIG_AUDIT_PAGES;
+            let seq = self.seq.wrapping_add(1);
+            start_page(&mut self.flash, page, seq)?;
+            self.page = page;
+            self.seq = seq;
+            self.next = 0;
+        }
+        let offset = HEADER_WORDS + self.next * CONFIG_AUDIT_RECORD_WORDS;
+        // Move on even if programming fails, as the slot may be partly written.
+        self.next += 1;
+        self.flash.program(self.page, offset, &record.to_words())
+    }
+
+    /// Changes recorded since the trail was started, including erased ones.
+    pub const fn total(&self) -> u32 {
+        self.seq
+            .wrapping_mul(CONFIG_AUDIT_RECORDS_PER_PAGE as u32)
+            .wrapping_add(self.next as u32)
+    }
+
+    /// Up to `n` records, newest first. Records that don't check out are skipped.
+    pub fn newest(&self, n: usize) -> impl Iterator<Item = ConfigAuditRecord> + '_ {
+        (0..CONFIG_AUDIT_PAGES)
+            .flat_map(move |back| {
+                let page = (self.page + CONFIG_AUDIT_PAGES - back) % CONFIG_AUDIT_PAGES;
+                let slots = if back == 0 {
+                    self.next
+                } else if page_seq(&self.flash, page) == Some(self.seq.wrapping_sub(back as u32)) {
+                    CONFIG_AUDIT_RECORDS_PER_PAGE
+                } else {
+                    // Not written since it was erased.
+                    0
+                };
+                (0..slots).rev().map(move |slot| (page, slot))
+            })
+            .filter_map(|(page, slot)| {
+                ConfigAuditRecord::from_words(&read_slot(&self.flash, page, slot))
+            })
+            .take(n)
+    }
+
+    #[cfg(test)]
+    fn flash(&self) -> &F {
+        &self.flash
+    }
+}
+
+/// Sequence number of a page, if it has a valid header.
+fn page_seq<F: PageFlash>(flash: &F, page: usize) -> Option<u32> {
+    let mut header = [0; HEADER_WORDS];
+    flash.read(page, 0, &mut header);
+    (header[0] == !header[1]).then_some(header[0])
+}
+
+fn read_slot<F: PageFlash>(
+    flash: &F,
+    page: usize,
+    slot: usize,
+) -> [u32; CONFIG_AUDIT_RECORD_WORDS] {
+    let mut words = [0; CONFIG_AUDIT_RECORD_WORDS];
+    flash.read(
+        page,
+        HEADER_WORDS + slot * CONFIG_AUDIT_RECORD_WORDS,
+        &mut words,
+    );
+    words
+}
+
+fn start_page<F: PageFlash>(flash: &mut F, page: usize, seq: u32) -> Result<(), F::Error> {
+    flash.erase(page)?;
+    flash.program(page, 0, &[seq, !seq])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    type FakeFlash = crate::page_flash::FakeFlash<CONFIG_AUDIT_PAGES>;
+
+    fn record(met_ms: u64, new: u32) -> ConfigAuditRecord {
+        ConfigAuditRecord {
+            met_ms,
+            name: ConfigVariableName::HeartbeatMs,
+            old: ConfigValue::U32(1000),
+            new: ConfigValue::U32(new),
+            source: ConfigChangeSource::Fdir,
+        }
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let record = ConfigAuditRecord {
+            met_ms: 0x1_2345_6789,
+            name: ConfigVariableName::ArmWindowS,
+            old: ConfigValue::F32(-1.5),
+            new: ConfigValue::Bool(true),
+            source: ConfigChangeSource::Umbilical,
+        };
+        assert_eq!(
+            ConfigAuditRecord::from_words(&record.to_words()),
+            Some(record)
+        );
+
+        let mut words = record.to_words();
+        words[3] ^= 1;
+        assert_eq!(ConfigAuditRecord::from_words(&words), None);
+        assert_eq!(
+            ConfigAuditRecord::from_words(&[0; CONFIG_AUDIT_RECORD_WORDS]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_log_keeps_newest() {
+        let mut log = ConfigAuditLog::open(FakeFlash::new()).unwrap();
+        assert_eq!(log.newest(10).count(), 0);
+        assert_eq!(log.total(), 0);
+        for i in 0..5 {
+            log.push(&record(i, i as u32)).unwrap();
+        }
+        assert_eq!(log.total(), 5);
+        let times: Vec<u64> = log.newest(3).map(|r| r.met_ms).collect();
+        assert_eq!(times, [4, 3, 2]);
+        assert_eq!(log.newest(1).next(), Some(record(4, 4)));
+    }
+
+    #[test]
+    fn test_log_wraps_around_pages() {
+        let mut log = ConfigAuditLog::open(FakeFlash::new()).unwrap();
+        let count = 2 * CONFIG_AUDIT_RECORDS_PER_PAGE as u64 + 10;
+        for i in 0..count {
+            log.push(&record(i, i as u32)).unwrap();
+        }
+        assert_eq!(log.total(), count as u32);
+        assert_eq!(log.flash().erases, 3);
+
+        // The oldest page was erased, so the newest page and the one before it are left.
+        let times: Vec<u64> = log.newest(usize::MAX).map(|r| r.met_ms).collect();
+        assert_eq!(times.len(), CONFIG_AUDIT_RECORDS_PER_PAGE + 10);
+        assert_eq!(times[0], count - 1);
+        assert!(times.windows(2).all(|w| w[0] == w[1] + 1));
+    }
+
+    #[test]
+    fn test_log_reopens_after_reset() {
+        let mut log = ConfigAuditLog::open(FakeFlash::new()).unwrap();
+        for i in 0..CONFIG_AUDIT_RECORDS_PER_PAGE as u64 + 3 {
+            log.push(&record(i, i as u32)).unwrap();
+        }
+        let mut flash = log.flash().clone();
+
+        // A record cut short by a reset, after the last full one.
+        let torn = HEADER_WORDS + 3 * CONFIG_AUDIT_RECORD_WORDS;
+        flash.pages[1][torn..torn + 2].copy_from_slice(&[1, 2]);
+
+        let mut log = ConfigAuditLog::open(flash).unwrap();
+        assert_eq!(log.total(), CONFIG_AUDIT_RECORDS_PER_PAGE as u32 + 4);
+        assert_eq!(log.newest(1).next().map(|r| r.met_ms), Some(172));
+        log.push(&record(1000, 0)).unwrap();
+        let times: Vec<u64> = log.newest(3).map(|r| r.met_ms).collect();
+        assert_eq!(times, [1000, 172, 171]);
+    }
+
+    #[test]
+    fn test_log_starts_on_blank_or_garbage_flash() {
+        let mut flash = FakeFlash::new();
+        flash.pages[0][..4].copy_from_slice(&[1, 2, 3, 4]);
+        let log = ConfigAuditLog::open(flash).unwrap();
+        assert_eq!(log.total(), 0);
+        assert_eq!(log.newest(10).count(), 0);
+        assert_eq!(log.flash().erases, 1);
+    }
+}
@@ -0,0 +1,186 @@
+//! Config blobs: a snapshot of the config variables, as sent by `config_export` and taken by
+//! `config_import`.
+//!
+//! A blob is a version byte, then 6 bytes per variable (its index in
+//! `ConfigVariableName::ALL`, a type tag and the value as a little-endian `u32`), then the
+//! CRC-32 of everything before it, little-endian. New variables must be added at the end of
+//! `ALL`, so that older blobs still import.
+
+use cts2_obc_telecommands::MAX_CONFIG_BLOB_LEN;
+use cts2_obc_telecommands::config::{ConfigStore, ConfigValue, ConfigVariableName};
+use thiserror::Error;
+
+use crate::crc32::crc32;
+
+const CONFIG_BLOB_VERSION: u8 = 1;
+const ENTRY_LEN: usize = 6;
+const CRC_LEN: usize = 4;
+
+/// Length of a blob with every variable.
+pub const CONFIG_BLOB_LEN: usize = 1 + ConfigVariableName::ALL.len() * ENTRY_LEN + CRC_LEN;
+
+const _: () = assert!(CONFIG_BLOB_LEN <= MAX_CONFIG_BLOB_LEN);
+
+/// A blob with every variable.
+pub type ConfigBlobBytes = heapless::Vec<u8, CONFIG_BLOB_LEN>;
+
+/// The variables in a blob, in blob order.
+pub type ConfigBlobValues =
+    heapless::Vec<(ConfigVariableName, ConfigValue), { ConfigVariableName::ALL.len() }>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ConfigBlobError {
+    #[error("blob has the wrong length")]
+    BadLength,
+
+    #[error("blob CRC mismatch")]
+    BadCrc,
+
+    #[error("unsupported blob version")]
+    UnsupportedVersion,
+
+    #[error("unknown config variable in blob")]
+    UnknownVariable,
+
+    #[error("config variable given twice in blob")]
+    DuplicateVariable,
+
+    #[error("unknown value type in blob")]
+    UnknownType,
+}
+
+pub(crate) fn encode_value(value: ConfigValue) -> (u8, u32) {
+    match value {
+        ConfigValue::U32(v) => (0, v),
+        ConfigValue::Bool(v) => (1, v as u32),
+        ConfigValue::F32(v) => (2, v.to_bits()),
+        ConfigValue::I32(v) => (3, v as u32),
+        ConfigValue::U8(v) => (4, v as u32),
+    }
+}
+
+pub(crate) fn decode_value(tag: u8, bits: u32) -> Result<ConfigValue, ConfigBlobError> {
+    match tag {
+        0 => Ok(ConfigValue::U32(bits)),
+        1 if bits <= 1 => Ok(ConfigValue::Bool(bits == 1)),
+        2 => Ok(ConfigValue::F32(f32::from_bits(bits))),
+        3 => Ok(ConfigValue::I32(bits as i32)),
+        4 => u8::try_from(bits)
+            .map(ConfigValue::U8)
+            .map_err(|_| ConfigBlobError::UnknownType),
+        _ => Err(ConfigBlobError::UnknownType),
+    }
+}
+
+/// Every variable in the store, as a blob.
+pub fn export_config(store: &ConfigStore) -> ConfigBlobBytes {
+    let mut blob = ConfigBlobBytes::new();
+    let _ = blob.push(CONFIG_BLOB_VERSION);
+    for (index, (_, value)) in store.values().enumerate() {
+        let (tag, bits) = encode_value(value);
+        let _ = blob.extend_from_slice(&[index as u8, tag]);
+        let _ = blob.extend_from_slice(&bits.to_le_bytes());
+    }
+    let crc = crc32(&blob);
+    let _ = blob.extend_from_slice(&crc.to_le_bytes());
+    blob
+}
+
+/// Check a blob and decode the variables in it. A blob may hold only some of the variables.
+///
+/// The types of the values aren't checked against the variables here, see
+/// [`ConfigStore::set_all`].
+pub fn decode_config(blob: &[u8]) -> Result<ConfigBlobValues, ConfigBlobError> {
+    let body_len = blob
+        .len()
+        .checked_sub(CRC_LEN)
+        .filter(|len| *len >= 1 && (len - 1) % ENTRY_LEN == 0)
+        .ok_or(ConfigBlobError::BadLength)?;
+    let (body, crc) = blob.split_at(body_len);
+    if crc32(body).to_le_bytes() != crc {
+        return Err(ConfigBlobError::BadCrc);
+    }
+    if body[0] != CONFIG_BLOB_VERSION {
+        return Err(ConfigBlobError::UnsupportedVersion);
+    }
+
+    let mut values = ConfigBlobValues::new();
+    for entry in body[1..].chunks_exact(ENTRY_LEN) {
+        let name = *ConfigVariableName::ALL
+            .get(entry[0] as usize)
+            .ok_or(ConfigBlobError::UnknownVariable)?;
+        if values.iter().any(|(n, _)| *n == name) {
+            return Err(ConfigBlobError::DuplicateVariable);
+        }
+        let bits = u32::from_le_bytes([entry[2], entry[3], entry[4], entry[5]]);
+        let value = decode_value(entry[1], bits)?;
+        // There can't be more distinct variables than `ALL` holds.
+        let _ = values.push((name, value));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_blob_round_trip() {
+        let store = ConfigStore::new();
+        store
+            .set(ConfigVariableName::HeartbeatMs, ConfigValue::U32(250))
+            .unwrap();
+        let blob = export_config(&store);
+        assert_eq!(blob.len(), CONFIG_BLOB_LEN);
+
+        let values = decode_config(&blob).unwrap();
+        assert!(values.iter().copied().eq(store.values()));
+
+        let other = ConfigStore::new();
+        other.set_all(&values).unwrap();
+        assert!(other.values().eq(store.values()));
+    }
+
+    #[test]
+    fn test_config_blob_errors() {
+        let blob = export_config(&ConfigStore::new());
+        assert_eq!(decode_config(&[]), Err(ConfigBlobError::BadLength));
+        assert_eq!(
+            decode_config(&blob[..blob.len() - 1]),
+            Err(ConfigBlobError::BadLength)
+        );
+
+        let mut corrupt = blob.clone();
+        corrupt[3] ^= 1;
+        assert_eq!(decode_config(&corrupt), Err(ConfigBlobError::BadCrc));
+
+        let with_crc = |body: &[u8]| {
+            let mut blob = heapless::Vec::<u8, 32>::from_slice(body).unwrap();
+            blob.extend_from_slice(&crc32(body).to_le_bytes()).unwrap();
+            blob
+        };
+        assert_eq!(
+            decode_config(&with_crc(&[2])),
+            Err(ConfigBlobError::UnsupportedVersion)
+        );
+        assert_eq!(
+            decode_config(&with_crc(&[1, 200, 0, 0, 0, 0, 0])),
+            Err(ConfigBlobError::UnknownVariable)
+        );
+        assert_eq!(
+            decode_config(&with_crc(&[1, 0, 9, 0, 0, 0, 0])),
+            Err(ConfigBlobError::UnknownType)
+        );
+        assert_eq!(
+            decode_config(&with_crc(&[1, 0, 0, 5, 0, 0, 0, 0, 0, 6, 0, 0, 0])),
+            Err(ConfigBlobError::DuplicateVariable)
+        );
+
+        // Only some of the variables.
+        let values = decode_config(&with_crc(&[1, 0, 0, 0xf4, 1, 0, 0])).unwrap();
+        assert_eq!(
+            values.as_slice(),
+            [(ConfigVariableName::HeartbeatMs, ConfigValue::U32(500))]
+        );
+    }
+}
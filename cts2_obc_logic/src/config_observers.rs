@@ -0,0 +1,149 @@
+//! Config observers: functions that are called when a config variable changes, so that a new
+//! value takes effect without a reboot.
+//!
+//! `ConfigStore` counts the changes to each variable. The registry remembers the count each
+//! observer last saw, and `poll` reports the observers whose variable changed since.
+
+use cts2_obc_telecommands::config::{ConfigStore, ConfigValue, ConfigVariableName};
+use thiserror::Error;
+
+/// A function to call with the new value when a variable changes.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigObserver {
+    pub name: ConfigVariableName,
+    pub on_change: fn(ConfigValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ConfigObserverError {
+    #[error("Config observer registry is full")]
+    RegistryFull,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Registered {
+    observer: ConfigObserver,
+    /// Change count when the observer was last called. `None` until the first poll.
+    seen: Option<u32>,
+}
+
+/// Fixed-capacity registry of config observers.
+#[derive(Debug)]
+pub struct ConfigObservers<const N: usize> {
+    observers: heapless::Vec<Registered, N>,
+}
+
+impl<const N: usize> ConfigObservers<N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            observers: heapless::Vec::new(),
+        }
+    }
+
+    pub fn register_all(
+        &mut self,
+        observers: &[ConfigObserver],
+    ) -> Result<(), ConfigObserverError> {
+        observers.iter().try_for_each(|&observer| {
+            self.observers
+                .push(Registered {
+                    observer,
+                    seen: None,
+                })
+                .map_err(|_| ConfigObserverError::RegistryFull)
+        })
+    }
+
+    /// The observers to call, with the value to call them with: those whose variable changed
+    /// since the last poll. The first poll reports every observer, with the current value.
+    ///
+    /// The caller calls them, e.g. outside a critical section.
+    pub fn poll(&mut self, store: &ConfigStore) -> heapless::Vec<(ConfigObserver, ConfigValue), N> {
+        let mut due = heapless::Vec::new();
+        for registered in &mut self.observers {
+            let count = store.change_count(registered.observer.name);
+            if registered.seen != Some(count) {
+                registered.seen = Some(count);
+                // Can't fail: there are no more due observers than registered ones.
+                let _ = due.push((registered.observer, store.get(registered.observer.name)));
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn observer(name: ConfigVariableName) -> ConfigObserver {
+        ConfigObserver {
+            name,
+            on_change: |_| {},
+        }
+    }
+
+    fn poll(
+        observers: &mut ConfigObservers<4>,
+        store: &ConfigStore,
+    ) -> Vec<(ConfigVariableName, ConfigValue)> {
+        observers
+            .poll(store)
+            .into_iter()
+            .map(|(observer, value)| (observer.name, value))
+            .collect()
+    }
+
+    #[test]
+    fn test_observers_see_changes() {
+        let store = ConfigStore::new();
+        let mut observers = ConfigObservers::<4>::new();
+        observers
+            .register_all(&[
+                observer(ConfigVariableName::HeartbeatMs),
+                observer(ConfigVariableName::ArmWindowS),
+            ])
+            .unwrap();
+
+        // Every observer gets the value it starts with.
+        assert_eq!(
+            poll(&mut observers, &store),
+            [
+                (ConfigVariableName::HeartbeatMs, ConfigValue::U32(1000)),
+                (ConfigVariableName::ArmWindowS, ConfigValue::U32(60)),
+            ]
+        );
+        assert_eq!(poll(&mut observers, &store), []);
+
+        store
+            .set(ConfigVariableName::HeartbeatMs, ConfigValue::U32(250))
+            .unwrap();
+        store
+            .set(ConfigVariableName::DeployBurnMs, ConfigValue::U32(1))
+            .unwrap();
+        assert_eq!(
+            poll(&mut observers, &store),
+            [(ConfigVariableName::HeartbeatMs, ConfigValue::U32(250))]
+        );
+
+        store.reset_to_defaults();
+        assert_eq!(
+            poll(&mut observers, &store),
+            [(ConfigVariableName::HeartbeatMs, ConfigValue::U32(1000))]
+        );
+    }
+
+    #[test]
+    fn test_observer_registry_full() {
+        let mut observers = ConfigObservers::<1>::new();
+        assert_eq!(
+            observers.register_all(&[
+                observer(ConfigVariableName::HeartbeatMs),
+                observer(ConfigVariableName::ArmWindowS),
+            ]),
+            Err(ConfigObserverError::RegistryFull)
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Tentative config changes: a new value that is put back to the previous one unless the
+//! ground confirms it in time.
+//!
+//! A wrong radio or power setting can cut the link, and then no telecommand can undo it. With
+//! `set_config_tentative`, the ground has to get through once more with `config_confirm` to
+//! keep the new value. Only one change can be tentative at a time.
+//!
+//! If the variable is changed again some other way, e.g. by `set_config`, the later value
+//! stands and the tentative change is dropped without a revert.
+
+use cts2_obc_telecommands::config::{ConfigStore, ConfigValue, ConfigVariableName};
+use cts2_obc_telecommands::error::ConfigError;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+pub enum TentativeError {
+    #[error("a tentative change is already pending")]
+    Pending,
+
+    #[error("no tentative change is pending")]
+    NothingPending,
+
+    #[error("{0}")]
+    Config(#[from] ConfigError),
+}
+
+/// A change that hasn't been confirmed yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TentativeChange {
+    pub name: ConfigVariableName,
+    /// The value before the change, which is put back on a timeout.
+    pub previous: ConfigValue,
+    pub value: ConfigValue,
+    /// Uptime at which the change is reverted.
+    pub deadline_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Pending {
+    change: TentativeChange,
+    /// Change count of the variable right after the tentative change.
+    change_count: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct TentativeConfig {
+    pending: Option<Pending>,
+}
+
+impl TentativeConfig {
+    pub const fn new() -> Self {
+        Self { pending: None }
+    }
+
+    /// Set `name` to `value` in the store now, and remember the old value to put back after
+    /// `timeout_ms`.
+    pub fn set(
+        &mut self,
+        store: &ConfigStore,
+        name: ConfigVariableName,
+        value: ConfigValue,
+        timeout_ms: u64,
+        now_ms: u64,
+    ) -> Result<TentativeChange, TentativeError> {
+        if self.pending.is_some() {
+            return Err(TentativeError::Pending);
+        }
+        let previous = store.get(name);
+        store.set(name, value)?;
+        let change = TentativeChange {
+            name,
+            previous,
+            value,
+            deadline_ms: now_ms.saturating_add(timeout_ms),
+        };
+        self.pending = Some(Pending {
+            change,
+            change_count: store.change_count(name),
+        });
+        Ok(change)
+    }
+
+    /// Keep the pending change.
+    pub fn confirm(&mut self) -> Result<TentativeChange, TentativeError> {
+        self.pending
+            .take()
+            .map(|pending| pending.change)
+            .ok_or(TentativeError::NothingPending)
+    }
+
+    /// Forget the pending change without reverting it, e.g. when the config is reset to
+    /// defaults anyway.
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// Revert the pending change if its time is up. Returns the reverted change.
+    ///
+    /// A variable that was changed again since is left alone, and `None` is returned.
+    pub fn poll(&mut self, store: &ConfigStore, now_ms: u64) -> Option<TentativeChange> {
+        let pending = self
+            .pending
+            .filter(|pending| now_ms >= pending.change.deadline_ms)?;
+        self.pending = None;
+        let change = pending.change;
+        if store.change_count(change.name) != pending.change_count {
+            return None;
+        }
+        // The previous value was read from the store, so it has the right type.
+        let _ = store.set(change.name, change.previous);
+        Some(change)
+    }
+
+    pub fn pending(&self) -> Option<&TentativeChange> {
+        self.pending.as_ref().map(|pending| &pending.change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: ConfigVariableName = ConfigVariableName::HeartbeatMs;
+
+    #[test]
+    fn test_tentative_change_reverts_on_timeout() {
+        let store = ConfigStore::new();
+        let mut tentative = TentativeConfig::new();
+        let change = tentative
+            .set(&store, NAME, ConfigValue::U32(250), 10_000, 1_000)
+            .unwrap();
+        assert_eq!(change.previous, ConfigValue::U32(1000));
+        assert_eq!(change.deadline_ms, 11_000);
+        assert_eq!(store.get(NAME), ConfigValue::U32(250));
+
+        assert_eq!(tentative.poll(&store, 10_999), None);
+        assert_eq!(store.get(NAME), ConfigValue::U32(250));
+
+        assert_eq!(tentative.poll(&store, 11_000), Some(change));
+        assert_eq!(store.get(NAME), ConfigValue::U32(1000));
+        assert_eq!(tentative.pending(), None);
+        assert_eq!(tentative.poll(&store, 20_000), None);
+    }
+
+    #[test]
+    fn test_confirmed_change_is_kept() {
+        let store = ConfigStore::new();
+        let mut tentative = TentativeConfig::new();
+        assert_eq!(tentative.confirm(), Err(TentativeError::NothingPending));
+
+        tentative
+            .set(&store, NAME, ConfigValue::U32(250), 10_000, 0)
+            .unwrap();
+        assert_eq!(tentative.confirm().unwrap().value, ConfigValue::U32(250));
+        assert_eq!(tentative.poll(&store, 50_000), None);
+        assert_eq!(store.get(NAME), ConfigValue::U32(250));
+    }
+
+    #[test]
+    fn test_later_change_is_not_reverted() {
+        let store = ConfigStore::new();
+        let mut tentative = TentativeConfig::new();
+        tentative
+            .set(&store, NAME, ConfigValue::U32(250), 60_000, 0)
+            .unwrap();
+        store.set(NAME, ConfigValue::U32(500)).unwrap();
+
+        assert_eq!(tentative.poll(&store, 59_999), None);
+        assert!(tentative.pending().is_some());
+        assert_eq!(tentative.poll(&store, 60_000), None);
+        assert_eq!(tentative.pending(), None);
+        assert_eq!(store.get(NAME), ConfigValue::U32(500));
+
+        // Changed and changed back to the tentative value still counts as a later change.
+        tentative
+            .set(&store, NAME, ConfigValue::U32(250), 60_000, 100_000)
+            .unwrap();
+        store.reset(NAME);
+        store.set(NAME, ConfigValue::U32(250)).unwrap();
+        assert_eq!(tentative.poll(&store, 160_000), None);
+        assert_eq!(store.get(NAME), ConfigValue::U32(250));
+
+        // Other variables don't matter.
+        tentative
+            .set(&store, NAME, ConfigValue::U32(100), 60_000, 200_000)
+            .unwrap();
+        store
+            .set(ConfigVariableName::ArmWindowS, ConfigValue::U32(5))
+            .unwrap();
+        assert!(tentative.poll(&store, 260_000).is_some());
+        assert_eq!(store.get(NAME), ConfigValue::U32(250));
+    }
+
+    #[test]
+    fn test_tentative_change_errors() {
+        let store = ConfigStore::new();
+        let mut tentative = TentativeConfig::new();
+        assert_eq!(
+            tentative.set(&store, NAME, ConfigValue::Bool(true), 10_000, 0),
+            Err(TentativeError::Config(
+                ConfigError::ConfigVariableNotThisType
+            ))
+        );
+        assert_eq!(tentative.pending(), None);
+
+        tentative
+            .set(&store, NAME, ConfigValue::U32(250), 10_000, 0)
+            .unwrap();
+        assert_eq!(
+            tentative.set(
+                &store,
+                ConfigVariableName::ArmWindowS,
+                ConfigValue::U32(5),
+                10_000,
+                0
+            ),
+            Err(TentativeError::Pending)
+        );
+        assert_eq!(
+            store.get(ConfigVariableName::ArmWindowS),
+            ConfigValue::U32(60)
+        );
+
+        tentative.cancel();
+        assert_eq!(tentative.poll(&store, 50_000), None);
+        assert_eq!(store.get(NAME), ConfigValue::U32(250));
+    }
+}
@@ -0,0 +1,81 @@
+//! Incremental CRC-32 (IEEE 802.3, as used by zlib and `crc32` tools).
+//!
+//! The CRC can be fed in pieces, so that large memory regions can be checked a few
+//! KiB at a time without blocking the main loop.
+
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+const TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Crc32 {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self { state: 0xFFFF_FFFF }
+    }
+
+    /// Feed more data into the CRC.
+    pub fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.state = TABLE[((self.state ^ b as u32) & 0xFF) as usize] ^ (self.state >> 8);
+        }
+    }
+
+    /// The CRC of all data fed so far. Does not reset the state.
+    pub const fn finalize(&self) -> u32 {
+        self.state ^ 0xFFFF_FFFF
+    }
+}
+
+/// Compute the CRC-32 of `data` in one go.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_check_value() {
+        // The standard check value for CRC-32/ISO-HDLC.
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn test_crc32_incremental_matches_one_shot() {
+        let data: std::vec::Vec<u8> = (0..=255u8).cycle().take(5000).collect();
+        let mut crc = Crc32::new();
+        for chunk in data.chunks(37) {
+            crc.update(chunk);
+        }
+        assert_eq!(crc.finalize(), crc32(&data));
+    }
+}
@@ -0,0 +1,418 @@
+//! Post-deployment silence and antenna deployment.
+//!
+//! After separation from the deployer, the satellite must not transmit until the silence period
+//! has passed. The antennas are then deployed one at a time by heating a burn wire until the
+//! antenna's feedback switch reports that it is out, with a limited number of attempts.
+//!
+//! Time is mission-elapsed time (MET): time since first power-on after separation, kept across
+//! resets, so that a reset during the silence period does not shorten or restart it.
+
+use crate::crc32::crc32;
+
+/// Most antennas that the sequencer can handle.
+pub const MAX_ANTENNAS: usize = 4;
+
+const MET_RECORD_MAGIC: u32 = 0x4D45_5401;
+
+/// Number of words in a stored mission-elapsed time record.
+pub const MET_RECORD_WORDS: usize = 4;
+
+/// Mission-elapsed time, kept in memory that survives a reset (but not a power cycle).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MetRecord {
+    pub mission_elapsed_ms: u64,
+}
+
+impl MetRecord {
+    fn check_word(lo: u32, hi: u32) -> u32 {
+        let mut bytes = [0u8; 12];
+        bytes[..4].copy_from_slice(&MET_RECORD_MAGIC.to_le_bytes());
+        bytes[4..8].copy_from_slice(&lo.to_le_bytes());
+        bytes[8..].copy_from_slice(&hi.to_le_bytes());
+        crc32(&bytes)
+    }
+
+    pub fn to_words(&self) -> [u32; MET_RECORD_WORDS] {
+        let lo = self.mission_elapsed_ms as u32;
+        let hi = (self.mission_elapsed_ms >> 32) as u32;
+        [MET_RECORD_MAGIC, lo, hi, Self::check_word(lo, hi)]
+    }
+
+    /// Decode a stored record. Returns `None` if the words don't hold a valid record, such as
+    /// the random RAM contents after a power cycle.
+    pub fn from_words(words: &[u32; MET_RECORD_WORDS]) -> Option<Self> {
+        let [magic, lo, hi, check] = *words;
+        if magic != MET_RECORD_MAGIC || check != Self::check_word(lo, hi) {
+            return None;
+        }
+        Some(Self {
+            mission_elapsed_ms: ((hi as u64) << 32) | lo as u64,
+        })
+    }
+}
+
+/// Deployment settings, normally read from the `ConfigStore`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeploymentConfig {
+    /// No transmission, and no deployment, before this mission-elapsed time.
+    pub silence_ms: u64,
+    /// How long each burn-wire attempt lasts.
+    pub burn_ms: u64,
+    /// Pause between attempts on the same antenna, to let the burn-wire circuit cool down.
+    pub retry_delay_ms: u64,
+    /// Attempts per antenna before giving up on it.
+    pub max_attempts: u32,
+}
+
+/// Whether the radio may transmit at this mission-elapsed time.
+pub const fn tx_allowed(mission_elapsed_ms: u64, config: &DeploymentConfig) -> bool {
+    mission_elapsed_ms >= config.silence_ms
+}
+
+/// Burn-wire outputs and feedback switch inputs.
+pub trait DeploymentHardware {
+    /// Number of antennas, at most `MAX_ANTENNAS`.
+    fn antenna_count(&self) -> usize;
+
+    fn set_burn_wire(&mut self, antenna: usize, on: bool);
+
+    /// Whether the antenna's feedback switch reports it as deployed.
+    fn is_deployed(&self, antenna: usize) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeploymentPhase {
+    /// Waiting for the silence period to end.
+    Silence,
+    /// Waiting to start the next attempt on `antenna`.
+    Waiting { antenna: usize, until_ms: u64 },
+    /// Heating `antenna`'s burn wire.
+    Burning { antenna: usize, since_ms: u64 },
+    /// Every antenna has been deployed or given up on.
+    Complete,
+}
+
+impl DeploymentPhase {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            DeploymentPhase::Silence => "silence",
+            DeploymentPhase::Waiting { .. } => "waiting",
+            DeploymentPhase::Burning { .. } => "burning",
+            DeploymentPhase::Complete => "complete",
+        }
+    }
+}
+
+/// Something notable that happened during a `step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeploymentEvent {
+    SilenceEnded,
+    /// The antenna's feedback switch reports it as deployed, after `attempts` burns.
+    AntennaDeployed {
+        antenna: usize,
+        attempts: u32,
+    },
+    /// The antenna was not deployed after `attempts` burns. It is not tried again.
+    AntennaFailed {
+        antenna: usize,
+        attempts: u32,
+    },
+}
+
+/// Antenna deployment state machine. Call `step` periodically with the current time.
+#[derive(Debug)]
+pub struct DeploymentSequencer {
+    phase: DeploymentPhase,
+    attempts: [u32; MAX_ANTENNAS],
+    deployed: [bool; MAX_ANTENNAS],
+}
+
+impl DeploymentSequencer {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            phase: DeploymentPhase::Silence,
+            attempts: [0; MAX_ANTENNAS],
+            deployed: [false; MAX_ANTENNAS],
+        }
+    }
+
+    pub const fn phase(&self) -> DeploymentPhase {
+        self.phase
+    }
+
+    /// Burn-wire attempts made on `antenna` so far.
+    pub fn attempts(&self, antenna: usize) -> u32 {
+        self.attempts.get(antenna).copied().unwrap_or(0)
+    }
+
+    /// Whether `antenna` has been seen deployed.
+    pub fn is_deployed(&self, antenna: usize) -> bool {
+        self.deployed.get(antenna).copied().unwrap_or(false)
+    }
+
+    /// Advance the sequence. Returns at most one event per call.
+    pub fn step<H: DeploymentHardware>(
+        &mut self,
+        mission_elapsed_ms: u64,
+        config: &DeploymentConfig,
+        hardware: &mut H,
+    ) -> Option<DeploymentEvent> {
+        let now = mission_elapsed_ms;
+        match self.phase {
+            DeploymentPhase::Silence => {
+                if !tx_allowed(now, config) {
+                    return None;
+                }
+                self.phase = self.start_antenna(0, now, hardware);
+                Some(DeploymentEvent::SilenceEnded)
+            }
+            DeploymentPhase::Waiting { antenna, until_ms } => {
+                if hardware.is_deployed(antenna) {
+                    return Some(self.finish_antenna(antenna, true, now, hardware));
+                }
+                if self.attempts[antenna] >= config.max_attempts {
+                    return Some(self.finish_antenna(antenna, false, now, hardware));
+                }
+                if now >= until_ms {
+                    hardware.set_burn_wire(antenna, true);
+                    self.attempts[antenna] += 1;
+                    self.phase = DeploymentPhase::Burning {
+                        antenna,
+                        since_ms: now,
+                    };
+                }
+                None
+            }
+            DeploymentPhase::Burning { antenna, since_ms } => {
+                if hardware.is_deployed(antenna) {
+                    hardware.set_burn_wire(antenna, false);
+                    return Some(self.finish_antenna(antenna, true, now, hardware));
+                }
+                if now.saturating_sub(since_ms) >= config.burn_ms {
+                    hardware.set_burn_wire(antenna, false);
+                    self.phase = DeploymentPhase::Waiting {
+                        antenna,
+                        until_ms: now.saturating_add(config.retry_delay_ms),
+                    };
+                }
+                None
+            }
+            DeploymentPhase::Complete => None,
+        }
+    }
+
+    /// The phase for starting on `antenna`, or `Complete` if there are no antennas left.
+    fn start_antenna<H: DeploymentHardware>(
+        &self,
+        antenna: usize,
+        now: u64,
+        hardware: &H,
+    ) -> DeploymentPhase {
+        if antenna < hardware.antenna_count().min(MAX_ANTENNAS) {
+            DeploymentPhase::Waiting {
+                antenna,
+                until_ms: now,
+            }
+        } else {
+            DeploymentPhase::Complete
+        }
+    }
+
+    fn finish_antenna<H: DeploymentHardware>(
+        &mut self,
+        antenna: usize,
+        deployed: bool,
+        now: u64,
+        hardware: &H,
+    ) -> DeploymentEvent {
+        self.deployed[antenna] = deployed;
+        self.phase = self.start_antenna(antenna + 1, now, hardware);
+        let attempts = self.attempts[antenna];
+        if deployed {
+            DeploymentEvent::AntennaDeployed { antenna, attempts }
+        } else {
+            DeploymentEvent::AntennaFailed { antenna, attempts }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    const CONFIG: DeploymentConfig = DeploymentConfig {
+        silence_ms: 1_000,
+        burn_ms: 100,
+        retry_delay_ms: 50,
+        max_attempts: 2,
+    };
+
+    /// Two antennas. Each one deploys once its wire has been burning for `burn_needed_ms`.
+    struct MockHardware {
+        burn_needed_ms: [Option<u64>; 2],
+        burning_since: [Option<u64>; 2],
+        deployed: [bool; 2],
+        now: u64,
+        /// (antenna, on) for every burn-wire change.
+        wire_log: Vec<(usize, bool)>,
+    }
+
+    impl MockHardware {
+        fn new(burn_needed_ms: [Option<u64>; 2]) -> Self {
+            Self {
+                burn_needed_ms,
+                burning_since: [None; 2],
+                deployed: [false; 2],
+                now: 0,
+                wire_log: Vec::new(),
+            }
+        }
+
+        fn advance_to(&mut self, now: u64) {
+            self.now = now;
+            for antenna in 0..2 {
+                if let (Some(since), Some(needed)) =
+                    (self.burning_since[antenna], self.burn_needed_ms[antenna])
+                    && now - since >= needed
+                {
+                    self.deployed[antenna] = true;
+                }
+            }
+        }
+    }
+
+    impl DeploymentHardware for MockHardware {
+        fn antenna_count(&self) -> usize {
+            2
+        }
+
+        fn set_burn_wire(&mut self, antenna: usize, on: bool) {
+            self.burning_since[antenna] = on.then_some(self.now);
+            self.wire_log.push((antenna, on));
+        }
+
+        fn is_deployed(&self, antenna: usize) -> bool {
+            self.deployed[antenna]
+        }
+    }
+
+    /// Step every 10 ms from `from` to `to` inclusive, collecting events.
+    fn run(
+        sequencer: &mut DeploymentSequencer,
+        hardware: &mut MockHardware,
+        from: u64,
+        to: u64,
+    ) -> Vec<(u64, DeploymentEvent)> {
+        let mut events = Vec::new();
+        for now in (from..=to).step_by(10) {
+            hardware.advance_to(now);
+            if let Some(event) = sequencer.step(now, &CONFIG, hardware) {
+                events.push((now, event));
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_silence_blocks_tx_and_burns() {
+        let mut hardware = MockHardware::new([Some(30), Some(30)]);
+        let mut sequencer = DeploymentSequencer::new();
+
+        assert!(run(&mut sequencer, &mut hardware, 0, 990).is_empty());
+        assert!(hardware.wire_log.is_empty());
+        assert!(!tx_allowed(999, &CONFIG));
+        assert!(tx_allowed(1_000, &CONFIG));
+        assert_eq!(sequencer.phase(), DeploymentPhase::Silence);
+    }
+
+    #[test]
+    fn test_deploys_antennas_in_turn() {
+        let mut hardware = MockHardware::new([Some(30), Some(30)]);
+        let mut sequencer = DeploymentSequencer::new();
+
+        let events = run(&mut sequencer, &mut hardware, 1_000, 1_200);
+        let events: Vec<_> = events.into_iter().map(|(_, event)| event).collect();
+        assert_eq!(
+            events,
+            [
+                DeploymentEvent::SilenceEnded,
+                DeploymentEvent::AntennaDeployed {
+                    antenna: 0,
+                    attempts: 1
+                },
+                DeploymentEvent::AntennaDeployed {
+                    antenna: 1,
+                    attempts: 1
+                },
+            ]
+        );
+        assert_eq!(
+            hardware.wire_log,
+            [(0, true), (0, false), (1, true), (1, false)]
+        );
+        assert_eq!(sequencer.phase(), DeploymentPhase::Complete);
+    }
+
+    #[test]
+    fn test_retries_then_gives_up() {
+        // Antenna 0 never deploys; antenna 1 deploys on its first burn.
+        let mut hardware = MockHardware::new([None, Some(30)]);
+        let mut sequencer = DeploymentSequencer::new();
+
+        let events = run(&mut sequencer, &mut hardware, 1_000, 2_000);
+        assert_eq!(
+            events,
+            [
+                (1_000, DeploymentEvent::SilenceEnded),
+                // Burns at 1010..1110 and, after the retry delay, 1160..1260.
+                (
+                    1_270,
+                    DeploymentEvent::AntennaFailed {
+                        antenna: 0,
+                        attempts: 2
+                    }
+                ),
+                (
+                    1_310,
+                    DeploymentEvent::AntennaDeployed {
+                        antenna: 1,
+                        attempts: 1
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            &hardware.wire_log[..4],
+            [(0, true), (0, false), (0, true), (0, false)]
+        );
+        assert!(!sequencer.is_deployed(0));
+        assert!(sequencer.is_deployed(1));
+    }
+
+    #[test]
+    fn test_already_deployed_antenna_is_not_burned() {
+        let mut hardware = MockHardware::new([Some(30), Some(30)]);
+        hardware.deployed = [true, true];
+        let mut sequencer = DeploymentSequencer::new();
+
+        run(&mut sequencer, &mut hardware, 1_000, 1_100);
+        assert!(hardware.wire_log.is_empty());
+        assert_eq!(sequencer.attempts(0), 0);
+        assert_eq!(sequencer.phase(), DeploymentPhase::Complete);
+    }
+
+    #[test]
+    fn test_met_record_round_trip_and_reject_corruption() {
+        let record = MetRecord {
+            mission_elapsed_ms: 0x1_2345_6789,
+        };
+        let mut words = record.to_words();
+        assert_eq!(MetRecord::from_words(&words), Some(record));
+
+        words[2] ^= 1;
+        assert_eq!(MetRecord::from_words(&words), None);
+        assert_eq!(MetRecord::from_words(&[0; 4]), None);
+    }
+}
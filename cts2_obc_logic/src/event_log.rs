@@ -0,0 +1,153 @@
+//! Bounded log of notable onboard events (faults, anomalies, autonomous actions).
+
+use heapless::Deque;
+
+/// Identifies the kind of an event. The numeric codes are part of the downlink format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u16)]
+pub enum EventId {
+    /// The running firmware image's CRC does not match the one embedded at build time.
+    /// Data: expected CRC, computed CRC.
+    ImageCrcMismatch = 1,
+
+    /// A single-bit flash error was corrected by ECC. Data: address, total count since boot.
+    FlashEccCorrected = 2,
+
+    /// A double-bit flash error was detected. Data: address, total count since boot.
+    FlashEccDetected = 3,
+
+    /// An SRAM2 parity error was detected. Data: approximate address, total count since boot.
+    Sram2ParityError = 4,
+
+    /// An FDIR rule tripped. Data: rule slot, telemetry value as `f32` bits.
+    FdirRuleTripped = 5,
+
+    /// The operating mode changed. Data: old mode, new mode.
+    ModeChanged = 6,
+
+    /// Too many fast resets in a row; started in safe mode. Data: fast reset count, boot count.
+    BootLoopDetected = 7,
+
+    /// The post-deployment silence period ended. Data: mission-elapsed time in seconds, 0.
+    DeploymentSilenceEnded = 8,
+
+    /// An antenna's feedback switch reports it as deployed. Data: antenna, burn attempts.
+    AntennaDeployed = 9,
+
+    /// An antenna did not deploy and was given up on. Data: antenna, burn attempts.
+    AntennaDeployFailed = 10,
+
+    /// The transmitter inhibit changed. Data: new state (see `TxInhibit::code`), duration in
+    /// seconds for a timed inhibit.
+    TxInhibitChanged = 11,
+
+    /// No telecommand for a long time; a recovery action is running. Data: action (see
+    /// `LinkLossAction`), seconds since the last telecommand.
+    LinkLossAction = 12,
+
+    /// A tentative config change wasn't confirmed in time, and was reverted. Data: variable
+    /// (index in `ConfigVariableName::ALL`), 0.
+    ConfigReverted = 13,
+}
+
+impl EventId {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            EventId::ImageCrcMismatch => "image_crc_mismatch",
+            EventId::FlashEccCorrected => "flash_ecc_corrected",
+            EventId::FlashEccDetected => "flash_ecc_detected",
+            EventId::Sram2ParityError => "sram2_parity_error",
+            EventId::FdirRuleTripped => "fdir_rule_tripped",
+            EventId::ModeChanged => "mode_changed",
+            EventId::BootLoopDetected => "boot_loop_detected",
+            EventId::DeploymentSilenceEnded => "deployment_silence_ended",
+            EventId::AntennaDeployed => "antenna_deployed",
+            EventId::AntennaDeployFailed => "antenna_deploy_failed",
+            EventId::TxInhibitChanged => "tx_inhibit_changed",
+            EventId::LinkLossAction => "link_loss_action",
+            EventId::ConfigReverted => "config_reverted",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub uptime_ms: u64,
+    pub id: EventId,
+    /// Event-specific details; see the `EventId` docs.
+    pub data: [u32; 2],
+}
+
+/// Ring of the most recent `N` events. The oldest event is dropped when the log is full.
+#[derive(Debug)]
+pub struct EventLog<const N: usize> {
+    events: Deque<Event, N>,
+    total_count: u32,
+}
+
+impl<const N: usize> EventLog<N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            events: Deque::new(),
+            total_count: 0,
+        }
+    }
+
+    pub fn push(&mut self, event: Event) {
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        // Cannot fail, as space was made above.
+        let _ = self.events.push_back(event);
+        self.total_count = self.total_count.wrapping_add(1);
+    }
+
+    /// Number of events raised since boot, including ones that have since been dropped.
+    pub const fn total_count(&self) -> u32 {
+        self.total_count
+    }
+
+    /// The `n` most recent events, newest first.
+    pub fn newest(&self, n: usize) -> impl Iterator<Item = &Event> {
+        self.events.iter().rev().take(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn event(uptime_ms: u64) -> Event {
+        Event {
+            uptime_ms,
+            id: EventId::ImageCrcMismatch,
+            data: [0, 0],
+        }
+    }
+
+    #[test]
+    fn test_event_log_newest_first() {
+        let mut log = EventLog::<4>::new();
+        log.push(event(1));
+        log.push(event(2));
+        log.push(event(3));
+
+        let times: Vec<u64> = log.newest(2).map(|e| e.uptime_ms).collect();
+        assert_eq!(times, [3, 2]);
+        assert_eq!(log.total_count(), 3);
+    }
+
+    #[test]
+    fn test_event_log_drops_oldest_when_full() {
+        let mut log = EventLog::<2>::new();
+        for t in 1..=5 {
+            log.push(event(t));
+        }
+
+        let times: Vec<u64> = log.newest(10).map(|e| e.uptime_ms).collect();
+        assert_eq!(times, [5, 4]);
+        assert_eq!(log.total_count(), 5);
+    }
+}
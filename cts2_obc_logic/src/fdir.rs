@@ -0,0 +1,292 @@
+//! Limit-checking rules engine for FDIR (fault detection, isolation and recovery).
+//!
+//! Each rule watches one telemetry point. When the point violates the rule's limit for
+//! `persistence` consecutive evaluations, the rule trips once and its action is returned to
+//! the caller to carry out. The rule re-arms when the value is back within limits.
+
+use cts2_obc_telecommands::fdir::{FdirAction, LimitCheck};
+use thiserror::Error;
+
+use crate::telemetry::TelemetryValue;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum FdirError {
+    #[error("FDIR rule slot is out of range")]
+    SlotOutOfRange,
+
+    #[error("FDIR rule slot is empty")]
+    EmptySlot,
+
+    #[error("Persistence must be at least 1")]
+    ZeroPersistence,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdirRule {
+    /// Name of the telemetry point to check.
+    pub channel: &'static str,
+    pub check: LimitCheck,
+    /// Number of consecutive violations before the rule trips.
+    pub persistence: u8,
+    pub action: FdirAction,
+    pub enabled: bool,
+}
+
+/// A rule together with its evaluation state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdirRuleState {
+    pub rule: FdirRule,
+    /// Consecutive evaluations that violated the limit.
+    pub violation_count: u8,
+    /// Whether the rule has tripped and not yet re-armed.
+    pub tripped: bool,
+    /// Number of times the rule has tripped.
+    pub trip_count: u32,
+}
+
+/// A rule that tripped during an evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdirTrip {
+    pub slot: usize,
+    pub channel: &'static str,
+    pub value: f64,
+    pub action: FdirAction,
+}
+
+/// Table of `N` rule slots.
+#[derive(Debug)]
+pub struct FdirEngine<const N: usize> {
+    slots: [Option<FdirRuleState>; N],
+}
+
+impl<const N: usize> FdirEngine<N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { None }; N],
+        }
+    }
+
+    /// Put `rule` in `slot`, replacing any rule already there. The rule starts un-tripped.
+    pub fn set_rule(&mut self, slot: usize, rule: FdirRule) -> Result<(), FdirError> {
+        if rule.persistence == 0 {
+            return Err(FdirError::ZeroPersistence);
+        }
+        let entry = self.slots.get_mut(slot).ok_or(FdirError::SlotOutOfRange)?;
+        *entry = Some(FdirRuleState {
+            rule,
+            violation_count: 0,
+            tripped: false,
+            trip_count: 0,
+        });
+        Ok(())
+    }
+
+    /// Load rules into slots `0..rules.len()`.
+    pub fn load(&mut self, rules: &[FdirRule]) -> Result<(), FdirError> {
+        rules
+            .iter()
+            .enumerate()
+            .try_for_each(|(slot, rule)| self.set_rule(slot, rule.clone()))
+    }
+
+    pub fn delete_rule(&mut self, slot: usize) -> Result<(), FdirError> {
+        let entry = self.slots.get_mut(slot).ok_or(FdirError::SlotOutOfRange)?;
+        entry.take().ok_or(FdirError::EmptySlot)?;
+        Ok(())
+    }
+
+    /// Enable or disable the rule in `slot`. Its evaluation state is reset either way.
+    pub fn set_enabled(&mut self, slot: usize, enabled: bool) -> Result<(), FdirError> {
+        let state = self
+            .slots
+            .get_mut(slot)
+            .ok_or(FdirError::SlotOutOfRange)?
+            .as_mut()
+            .ok_or(FdirError::EmptySlot)?;
+        state.rule.enabled = enabled;
+        state.violation_count = 0;
+        state.tripped = false;
+        Ok(())
+    }
+
+    /// Occupied slots, with their index.
+    pub fn rules(&self) -> impl Iterator<Item = (usize, &FdirRuleState)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, state)| state.as_ref().map(|state| (slot, state)))
+    }
+
+    /// Check every enabled rule against the current telemetry, and return the rules that
+    /// tripped.
+    ///
+    /// `read` returns the current value of a telemetry point, or `None` if it can't be read.
+    /// Rules whose point can't be read, or isn't numeric, are left as they were.
+    pub fn evaluate(
+        &mut self,
+        mut read: impl FnMut(&str) -> Option<TelemetryValue>,
+    ) -> heapless::Vec<FdirTrip, N> {
+        let mut trips = heapless::Vec::new();
+        for (slot, entry) in self.slots.iter_mut().enumerate() {
+            let Some(state) = entry else { continue };
+            if !state.rule.enabled {
+                continue;
+            }
+            let Some(value) = read(state.rule.channel).and_then(|v| v.as_f64()) else {
+                continue;
+            };
+
+            if !state.rule.check.is_violated(value) {
+                state.violation_count = 0;
+                state.tripped = false;
+                continue;
+            }
+
+            state.violation_count = state.violation_count.saturating_add(1);
+            if !state.tripped && state.violation_count >= state.rule.persistence {
+                state.tripped = true;
+                state.trip_count = state.trip_count.wrapping_add(1);
+                let _ = trips.push(FdirTrip {
+                    slot,
+                    channel: state.rule.channel,
+                    value,
+                    action: state.rule.action.clone(),
+                });
+            }
+        }
+        trips
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cts2_obc_telecommands::mode::OperatingMode;
+
+    fn rule(channel: &'static str, check: LimitCheck, persistence: u8) -> FdirRule {
+        FdirRule {
+            channel,
+            check,
+            persistence,
+            action: FdirAction::EnterMode(OperatingMode::Safe),
+            enabled: true,
+        }
+    }
+
+    /// Evaluate with `temp` and `volts` set to the given values, and return the tripped slots.
+    fn evaluate<const N: usize>(engine: &mut FdirEngine<N>, temp: f32, volts: u32) -> Vec<usize> {
+        engine
+            .evaluate(|name| match name {
+                "temp" => Some(TelemetryValue::F32(temp)),
+                "volts" => Some(TelemetryValue::U32(volts)),
+                "mode" => Some(TelemetryValue::Str("nominal")),
+                _ => None,
+            })
+            .iter()
+            .map(|trip| trip.slot)
+            .collect()
+    }
+
+    #[test]
+    fn test_rule_trips_after_persistence() {
+        let mut engine = FdirEngine::<4>::new();
+        engine
+            .set_rule(1, rule("temp", LimitCheck::Above(60.0), 3))
+            .unwrap();
+
+        assert!(evaluate(&mut engine, 61.0, 0).is_empty());
+        assert!(evaluate(&mut engine, 62.0, 0).is_empty());
+        assert_eq!(evaluate(&mut engine, 63.0, 0), [1]);
+        // Trips once, not on every evaluation while the fault persists.
+        assert!(evaluate(&mut engine, 64.0, 0).is_empty());
+        assert_eq!(engine.rules().next().unwrap().1.trip_count, 1);
+    }
+
+    #[test]
+    fn test_violation_count_resets_when_in_limits() {
+        let mut engine = FdirEngine::<4>::new();
+        engine
+            .set_rule(0, rule("temp", LimitCheck::Above(60.0), 2))
+            .unwrap();
+
+        assert!(evaluate(&mut engine, 61.0, 0).is_empty());
+        assert!(evaluate(&mut engine, 20.0, 0).is_empty());
+        assert!(evaluate(&mut engine, 61.0, 0).is_empty());
+        assert_eq!(evaluate(&mut engine, 61.0, 0), [0]);
+
+        // Re-arms once back in limits, and can trip again.
+        assert!(evaluate(&mut engine, 20.0, 0).is_empty());
+        assert!(evaluate(&mut engine, 61.0, 0).is_empty());
+        assert_eq!(evaluate(&mut engine, 61.0, 0), [0]);
+    }
+
+    #[test]
+    fn test_range_rule_and_trip_details() {
+        let mut engine = FdirEngine::<4>::new();
+        engine
+            .set_rule(
+                0,
+                rule(
+                    "volts",
+                    LimitCheck::Outside {
+                        low: 3000.0,
+                        high: 4200.0,
+                    },
+                    1,
+                ),
+            )
+            .unwrap();
+
+        assert!(evaluate(&mut engine, 0.0, 3700).is_empty());
+        let trips = engine.evaluate(|_| Some(TelemetryValue::U32(2900)));
+        assert_eq!(
+            trips[0],
+            FdirTrip {
+                slot: 0,
+                channel: "volts",
+                value: 2900.0,
+                action: FdirAction::EnterMode(OperatingMode::Safe),
+            }
+        );
+    }
+
+    #[test]
+    fn test_disabled_and_unreadable_rules_are_skipped() {
+        let mut engine = FdirEngine::<4>::new();
+        engine
+            .load(&[
+                rule("temp", LimitCheck::Above(60.0), 1),
+                rule("missing", LimitCheck::Above(0.0), 1),
+                rule("mode", LimitCheck::Above(0.0), 1),
+            ])
+            .unwrap();
+        engine.set_enabled(0, false).unwrap();
+
+        assert!(evaluate(&mut engine, 100.0, 0).is_empty());
+
+        engine.set_enabled(0, true).unwrap();
+        assert_eq!(evaluate(&mut engine, 100.0, 0), [0]);
+    }
+
+    #[test]
+    fn test_table_errors() {
+        let mut engine = FdirEngine::<2>::new();
+        assert_eq!(
+            engine.set_rule(2, rule("temp", LimitCheck::Above(0.0), 1)),
+            Err(FdirError::SlotOutOfRange)
+        );
+        assert_eq!(
+            engine.set_rule(0, rule("temp", LimitCheck::Above(0.0), 0)),
+            Err(FdirError::ZeroPersistence)
+        );
+        assert_eq!(engine.delete_rule(1), Err(FdirError::EmptySlot));
+        assert_eq!(engine.set_enabled(1, true), Err(FdirError::EmptySlot));
+
+        engine
+            .set_rule(1, rule("temp", LimitCheck::Above(0.0), 1))
+            .unwrap();
+        engine.delete_rule(1).unwrap();
+        assert_eq!(engine.rules().count(), 0);
+    }
+}
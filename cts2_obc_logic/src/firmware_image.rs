@@ -0,0 +1,300 @@
+//! Signed firmware image manifest.
+//!
+//! Every firmware image uploaded to the OBC is accompanied by a manifest that
+//! describes the image (version, size, SHA-256 hash) and carries an Ed25519
+//! signature. The signature covers the manifest header, which in turn covers the
+//! image through its hash, so the version and size cannot be altered either.
+//!
+//! Manifest layout (all integers little-endian):
+//!
+//! | Offset | Size | Field                     |
+//! |--------|------|---------------------------|
+//! | 0      | 4    | Magic (`b"CTS2"`)         |
+//! | 4      | 2    | Manifest format version   |
+//! | 6      | 2    | Reserved (must be zero)   |
+//! | 8      | 4    | Firmware version          |
+//! | 12     | 4    | Image size in bytes       |
+//! | 16     | 32   | SHA-256 of the image      |
+//! | 48     | 64   | Ed25519 signature of 0..48 |
+
+use ed25519_compact::{PublicKey, Signature};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Magic bytes at the start of every manifest.
+pub const MANIFEST_MAGIC: [u8; 4] = *b"CTS2";
+
+/// Manifest format version produced and accepted by this code.
+pub const MANIFEST_FORMAT_VERSION: u16 = 1;
+
+/// Number of bytes of the manifest that are covered by the signature.
+pub const MANIFEST_SIGNED_LEN: usize = 48;
+
+/// Total length of an encoded manifest, in bytes.
+pub const MANIFEST_LEN: usize = MANIFEST_SIGNED_LEN + SIGNATURE_LEN;
+
+/// Length of an Ed25519 public key, in bytes.
+pub const PUBLIC_KEY_LEN: usize = 32;
+
+/// Length of an Ed25519 signature, in bytes.
+pub const SIGNATURE_LEN: usize = 64;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum ImageVerifyError {
+    #[error("Manifest is shorter than expected")]
+    ManifestTooShort,
+
+    #[error("Manifest magic bytes are wrong")]
+    BadMagic,
+
+    #[error("Manifest format version is not supported")]
+    UnsupportedFormatVersion,
+
+    #[error("Image size does not match the manifest")]
+    SizeMismatch,
+
+    #[error("Image hash does not match the manifest")]
+    HashMismatch,
+
+    #[error("Public key is invalid")]
+    InvalidPublicKey,
+
+    #[error("Signature does not verify against the public key")]
+    BadSignature,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageManifest {
+    pub firmware_version: u32,
+    pub image_size: u32,
+    pub image_sha256: [u8; 32],
+    pub signature: [u8; SIGNATURE_LEN],
+}
+
+impl ImageManifest {
+    /// Create a manifest for `image` with an all-zero (invalid) signature.
+    ///
+    /// The signature must be filled in by the signing tool before upload.
+    pub fn new_unsigned(firmware_version: u32, image: &[u8]) -> Self {
+        Self {
+            firmware_version,
+            // Images larger than 4 GiB cannot exist on the OBC.
+            image_size: image.len() as u32,
+            image_sha256: sha256(image),
+            signature: [0; SIGNATURE_LEN],
+        }
+    }
+
+    /// Decode a manifest from its on-flash/on-wire representation.
+    ///
+    /// Only the header is validated here; use `verify_image` to check the signature.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageVerifyError> {
+        if bytes.len() < MANIFEST_LEN {
+            return Err(ImageVerifyError::ManifestTooShort);
+        }
+        if bytes[0..4] != MANIFEST_MAGIC {
+            return Err(ImageVerifyError::BadMagic);
+        }
+        let format_version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if format_version != MANIFEST_FORMAT_VERSION {
+            return Err(ImageVerifyError::UnsupportedFormatVersion);
+        }
+
+        let mut image_sha256 = [0u8; 32];
+        image_sha256.copy_from_slice(&bytes[16..48]);
+        let mut signature = [0u8; SIGNATURE_LEN];
+        signature.copy_from_slice(&bytes[MANIFEST_SIGNED_LEN..MANIFEST_LEN]);
+
+        Ok(Self {
+            firmware_version: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
+            image_size: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
+            image_sha256,
+            signature,
+        })
+    }
+
+    /// The portion of the manifest that the signature covers.
+    pub fn signed_bytes(&self) -> [u8; MANIFEST_SIGNED_LEN] {
+        let mut out = [0u8; MANIFEST_SIGNED_LEN];
+        out[0..4].copy_from_slice(&MANIFEST_MAGIC);
+        out[4..6].copy_from_slice(&MANIFEST_FORMAT_VERSION.to_le_bytes());
+        // out[6..8] is reserved and stays zero.
+        out[8..12].copy_from_slice(&self.firmware_version.to_le_bytes());
+        out[12..16].copy_from_slice(&self.image_size.to_le_bytes());
+        out[16..48].copy_from_slice(&self.image_sha256);
+        out
+    }
+
+    /// Encode the full manifest, including the signature.
+    pub fn to_bytes(&self) -> [u8; MANIFEST_LEN] {
+        let mut out = [0u8; MANIFEST_LEN];
+        out[..MANIFEST_SIGNED_LEN].copy_from_slice(&self.signed_bytes());
+        out[MANIFEST_SIGNED_LEN..].copy_from_slice(&self.signature);
+        out
+    }
+}
+
+/// Compute the SHA-256 hash of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+/// Check that `image` matches `manifest` and that the manifest was signed by `public_key`.
+///
+/// An image must pass this check before it is marked bootable.
+pub fn verify_image(
+    manifest: &ImageManifest,
+    image: &[u8],
+    public_key: &[u8; PUBLIC_KEY_LEN],
+) -> Result<(), ImageVerifyError> {
+    // Check the signature first, so that nothing in an unsigned manifest is trusted.
+    let public_key =
+        PublicKey::from_slice(public_key).map_err(|_| ImageVerifyError::InvalidPublicKey)?;
+    public_key
+        .verify(manifest.signed_bytes(), &Signature::new(manifest.signature))
+        .map_err(|_| ImageVerifyError::BadSignature)?;
+
+    if image.len() != manifest.image_size as usize {
+        return Err(ImageVerifyError::SizeMismatch);
+    }
+    if sha256(image) != manifest.image_sha256 {
+        return Err(ImageVerifyError::HashMismatch);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_compact::{KeyPair, Seed};
+
+    // Test vectors. Keys are derived from fixed seeds so that signatures are reproducible.
+    const SIGNING_SEED: [u8; 32] = [0x11; 32];
+    const OTHER_SEED: [u8; 32] = [0x22; 32];
+    const TEST_IMAGE: &[u8] = b"\x00\x00\x03\x20\xc1\x01\x00\x08CTS-SAT-2 test image payload";
+    const TEST_VERSION: u32 = 0x0001_0203;
+
+    fn signed_manifest(seed: [u8; 32], image: &[u8]) -> (ImageManifest, [u8; PUBLIC_KEY_LEN]) {
+        let key_pair = KeyPair::from_seed(Seed::new(seed));
+        let mut manifest = ImageManifest::new_unsigned(TEST_VERSION, image);
+        manifest.signature = *key_pair.sk.sign(manifest.signed_bytes(), None);
+        (manifest, *key_pair.pk)
+    }
+
+    #[test]
+    fn test_good_image_verifies() {
+        let (manifest, public_key) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+        assert_eq!(verify_image(&manifest, TEST_IMAGE, &public_key), Ok(()));
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let (manifest, _) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+        let bytes = manifest.to_bytes();
+        assert_eq!(&bytes[0..4], b"CTS2");
+        assert_eq!(ImageManifest::from_bytes(&bytes), Ok(manifest));
+    }
+
+    #[test]
+    fn test_manifest_matches_frozen_vector() {
+        // Ed25519 signatures are deterministic, so this pins the manifest encoding
+        // against what older signing tools produced.
+        const EXPECTED_HEX: &str = concat!(
+            "4354533201000000030201002400000",
+            "03ce1aef56b415017da7db0f587c5c2ff5eef968684b081bbe6fc7aa5d764412a",
+            "7e3279d6685d770d5a735c73f6cb61a6cf899fc709e8835b47e3129fafd06cf6",
+            "3cddf75ef27881e29e4c9fcbae0fcfb507e9832dcd41ba860b361e75e80ad30f",
+        );
+        let (manifest, _) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+        let actual_hex: std::string::String = manifest
+            .to_bytes()
+            .iter()
+            .map(|b| std::format!("{b:02x}"))
+            .collect();
+        assert_eq!(actual_hex, EXPECTED_HEX);
+    }
+
+    #[test]
+    fn test_tampered_image_is_rejected() {
+        let (manifest, public_key) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+
+        let mut tampered = [0u8; TEST_IMAGE.len()];
+        tampered.copy_from_slice(TEST_IMAGE);
+        tampered[10] ^= 0x01;
+        assert_eq!(
+            verify_image(&manifest, &tampered, &public_key),
+            Err(ImageVerifyError::HashMismatch)
+        );
+
+        assert_eq!(
+            verify_image(&manifest, &TEST_IMAGE[..TEST_IMAGE.len() - 1], &public_key),
+            Err(ImageVerifyError::SizeMismatch)
+        );
+    }
+
+    #[test]
+    fn test_tampered_manifest_is_rejected() {
+        let (manifest, public_key) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+
+        let mut bumped_version = manifest.clone();
+        bumped_version.firmware_version += 1;
+        assert_eq!(
+            verify_image(&bumped_version, TEST_IMAGE, &public_key),
+            Err(ImageVerifyError::BadSignature)
+        );
+
+        // A manifest re-hashed for a different image, but not re-signed.
+        let mut rehashed = manifest.clone();
+        rehashed.image_sha256 = sha256(b"malicious image");
+        assert_eq!(
+            verify_image(&rehashed, TEST_IMAGE, &public_key),
+            Err(ImageVerifyError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_is_rejected() {
+        let (manifest, _) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+        let (_, other_public_key) = signed_manifest(OTHER_SEED, TEST_IMAGE);
+        assert_eq!(
+            verify_image(&manifest, TEST_IMAGE, &other_public_key),
+            Err(ImageVerifyError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_unsigned_manifest_is_rejected() {
+        let (_, public_key) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+        let manifest = ImageManifest::new_unsigned(TEST_VERSION, TEST_IMAGE);
+        assert_eq!(
+            verify_image(&manifest, TEST_IMAGE, &public_key),
+            Err(ImageVerifyError::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_malformed_manifest_is_rejected() {
+        let (manifest, _) = signed_manifest(SIGNING_SEED, TEST_IMAGE);
+        let bytes = manifest.to_bytes();
+
+        assert_eq!(
+            ImageManifest::from_bytes(&bytes[..MANIFEST_LEN - 1]),
+            Err(ImageVerifyError::ManifestTooShort)
+        );
+
+        let mut bad_magic = bytes;
+        bad_magic[0] = b'X';
+        assert_eq!(
+            ImageManifest::from_bytes(&bad_magic),
+            Err(ImageVerifyError::BadMagic)
+        );
+
+        let mut bad_format = bytes;
+        bad_format[4] = 2;
+        assert_eq!(
+            ImageManifest::from_bytes(&bad_format),
+            Err(ImageVerifyError::UnsupportedFormatVersion)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Background CRC-32 self-check of the running firmware image.
+//!
+//! The image is checked a few KiB per call to `ImageCrcMonitor::step`, so that a full pass
+//! never blocks the main loop. When a pass completes, the result is compared with the CRC
+//! that was embedded into the image after it was built, and a new pass starts.
+
+use crate::crc32::Crc32;
+
+/// Value of the embedded CRC when no CRC was embedded after the build (erased flash).
+pub const IMAGE_CRC_NOT_PROVISIONED: u32 = 0xFFFF_FFFF;
+
+/// Offset of the image info block from the start of flash. Must match `IMAGE_INFO` in `memory.x`.
+///
+/// The block holds two little-endian u32s: the image length, then the expected CRC-32.
+pub const IMAGE_INFO_FLASH_OFFSET: usize = 0x000F_F000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageCrcStatus {
+    /// No pass has completed since boot.
+    Pending,
+    /// The image CRC matches the embedded CRC.
+    Match,
+    /// The image CRC differs from the embedded CRC. The image is corrupted.
+    Mismatch,
+    /// No CRC was embedded after the build (e.g. a debug build flashed with `cargo embed`),
+    /// so there is nothing to compare against.
+    NotProvisioned,
+}
+
+impl ImageCrcStatus {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ImageCrcStatus::Pending => "pending",
+            ImageCrcStatus::Match => "match",
+            ImageCrcStatus::Mismatch => "mismatch",
+            ImageCrcStatus::NotProvisioned => "not_provisioned",
+        }
+    }
+}
+
+/// Result of the most recently completed pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageCrcReport {
+    pub status: ImageCrcStatus,
+    pub expected_crc: u32,
+    pub computed_crc: u32,
+    /// Uptime at which the pass completed.
+    pub completed_at_ms: u64,
+    /// Number of passes completed since boot.
+    pub pass_count: u32,
+}
+
+#[derive(Debug)]
+pub struct ImageCrcMonitor {
+    offset: usize,
+    crc: Crc32,
+    last_report: ImageCrcReport,
+}
+
+impl ImageCrcMonitor {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            offset: 0,
+            crc: Crc32::new(),
+            last_report: ImageCrcReport {
+                status: ImageCrcStatus::Pending,
+                expected_crc: IMAGE_CRC_NOT_PROVISIONED,
+                computed_crc: 0,
+                completed_at_ms: 0,
+                pass_count: 0,
+            },
+        }
+    }
+
+    /// Feed up to `max_bytes` more of `image` into the CRC.
+    ///
+    /// Returns the new report when this call completes a pass; the next call starts a new pass.
+    pub fn step(
+        &mut self,
+        image: &[u8],
+        expected_crc: u32,
+        max_bytes: usize,
+        now_ms: u64,
+    ) -> Option<ImageCrcReport> {
+        let end = self.offset.saturating_add(max_bytes).min(image.len());
+        self.crc.update(&image[self.offset.min(end)..end]);
+        self.offset = end;
+
+        if self.offset < image.len() {
+            return None;
+        }
+
+        let computed_crc = self.crc.finalize();
+        let status = if expected_crc == IMAGE_CRC_NOT_PROVISIONED {
+            ImageCrcStatus::NotProvisioned
+        } else if computed_crc == expected_crc {
+            ImageCrcStatus::Match
+        } else {
+            ImageCrcStatus::Mismatch
+        };
+
+        self.last_report = ImageCrcReport {
+            status,
+            expected_crc,
+            computed_crc,
+            completed_at_ms: now_ms,
+            pass_count: self.last_report.pass_count.wrapping_add(1),
+        };
+        self.offset = 0;
+        self.crc = Crc32::new();
+
+        Some(self.last_report)
+    }
+
+    pub const fn last_report(&self) -> &ImageCrcReport {
+        &self.last_report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc32::crc32;
+    use std::vec::Vec;
+
+    fn test_image() -> Vec<u8> {
+        (0..10_000u32).map(|i| (i * 7 + 3) as u8).collect()
+    }
+
+    /// Run `step` until a pass completes, returning the report and the number of steps taken.
+    fn run_pass(
+        monitor: &mut ImageCrcMonitor,
+        image: &[u8],
+        expected: u32,
+    ) -> (ImageCrcReport, u32) {
+        let mut steps = 0;
+        loop {
+            steps += 1;
+            if let Some(report) = monitor.step(image, expected, 4096, u64::from(steps) * 500) {
+                return (report, steps);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pass_matches() {
+        let image = test_image();
+        let mut monitor = ImageCrcMonitor::new();
+        assert_eq!(monitor.last_report().status, ImageCrcStatus::Pending);
+
+        let (report, steps) = run_pass(&mut monitor, &image, crc32(&image));
+        assert_eq!(steps, 3); // 10_000 bytes in 4 KiB steps.
+        assert_eq!(report.status, ImageCrcStatus::Match);
+        assert_eq!(report.computed_crc, crc32(&image));
+        assert_eq!(report.completed_at_ms, 1500);
+        assert_eq!(report.pass_count, 1);
+        assert_eq!(monitor.last_report(), &report);
+    }
+
+    #[test]
+    fn test_corruption_is_detected() {
+        let mut image = test_image();
+        let expected = crc32(&image);
+        image[9_999] ^= 0x10; // Single bit flip.
+
+        let mut monitor = ImageCrcMonitor::new();
+        let (report, _) = run_pass(&mut monitor, &image, expected);
+        assert_eq!(report.status, ImageCrcStatus::Mismatch);
+        assert_eq!(report.expected_crc, expected);
+        assert_ne!(report.computed_crc, expected);
+    }
+
+    #[test]
+    fn test_not_provisioned() {
+        let image = test_image();
+        let mut monitor = ImageCrcMonitor::new();
+        let (report, _) = run_pass(&mut monitor, &image, IMAGE_CRC_NOT_PROVISIONED);
+        assert_eq!(report.status, ImageCrcStatus::NotProvisioned);
+    }
+
+    #[test]
+    fn test_passes_repeat() {
+        let image = test_image();
+        let mut monitor = ImageCrcMonitor::new();
+        run_pass(&mut monitor, &image, crc32(&image));
+        let (report, steps) = run_pass(&mut monitor, &image, crc32(&image));
+        assert_eq!(steps, 3);
+        assert_eq!(report.status, ImageCrcStatus::Match);
+        assert_eq!(report.pass_count, 2);
+    }
+}
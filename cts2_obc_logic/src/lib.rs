@@ -3,6 +3,30 @@
 #[cfg(test)]
 extern crate std;
 
+pub mod arming;
+pub mod boot_loop;
+pub mod command_log;
+pub mod config_audit;
+pub mod config_blob;
+pub mod config_observers;
+pub mod config_tentative;
+pub mod crc32;
+pub mod deployment;
+pub mod event_log;
+pub mod fdir;
+pub mod firmware_image;
+pub mod image_crc;
+pub mod link;
+pub mod link_loss;
+pub mod memory_access;
+pub mod memory_scrub;
+pub mod nv_store;
+pub mod page_flash;
+pub mod shell;
+pub mod subscriptions;
+pub mod telemetry;
+pub mod tx_inhibit;
+
 // TODO: Remove this placeholder function and add testable logic parts in here.
 pub fn multiply_by_2(i: u32) -> u32 {
     i * 2
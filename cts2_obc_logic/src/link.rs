@@ -0,0 +1,18 @@
+//! Communication links that telecommands arrive on and responses leave on.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Link {
+    /// Umbilical UART (USART2), used on the bench and before launch.
+    Umbilical,
+    /// The radio. Subject to the post-deployment silence period and the TX inhibit.
+    Radio,
+}
+
+impl Link {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Link::Umbilical => "umbilical",
+            Link::Radio => "radio",
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Link-loss watchdog.
+//!
+//! Every accepted telecommand resets the timer. If no telecommand arrives for a long time, the
+//! OBC tries to recover the link on its own, with increasingly drastic actions.
+//!
+//! Times are mission-elapsed (see `deployment`), and the watchdog's state is kept across
+//! resets, so that a reset, including the watchdog's own reboot, doesn't start the actions over.
+
+use crate::crc32::crc32;
+
+const LINK_LOSS_RECORD_MAGIC: u32 = 0x11CC_1055;
+
+/// Number of words in a stored watchdog record.
+pub const LINK_LOSS_RECORD_WORDS: usize = 5;
+
+/// Recovery actions, in the order they are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LinkLossAction {
+    ResetRadio = 0,
+    SwitchToBackupFrequency = 1,
+    RebootObc = 2,
+}
+
+impl LinkLossAction {
+    const ALL: [LinkLossAction; 3] = [
+        LinkLossAction::ResetRadio,
+        LinkLossAction::SwitchToBackupFrequency,
+        LinkLossAction::RebootObc,
+    ];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            LinkLossAction::ResetRadio => "reset_radio",
+            LinkLossAction::SwitchToBackupFrequency => "switch_to_backup_frequency",
+            LinkLossAction::RebootObc => "reboot_obc",
+        }
+    }
+}
+
+/// Time without a telecommand after which each action runs. Zero disables that action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkLossConfig {
+    pub reset_radio_ms: u64,
+    pub backup_frequency_ms: u64,
+    pub reboot_ms: u64,
+}
+
+impl LinkLossConfig {
+    const fn threshold_ms(&self, action: LinkLossAction) -> u64 {
+        match action {
+            LinkLossAction::ResetRadio => self.reset_radio_ms,
+            LinkLossAction::SwitchToBackupFrequency => self.backup_frequency_ms,
+            LinkLossAction::RebootObc => self.reboot_ms,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkLossWatchdog {
+    last_command_ms: u64,
+    /// Index into `LinkLossAction::ALL` of the next action to run.
+    next_action: usize,
+    /// The radio was switched to its backup frequency during this silence.
+    backup_frequency: bool,
+}
+
+impl LinkLossWatchdog {
+    /// A watchdog that counts from `now_ms`, as if a telecommand had just arrived.
+    pub const fn new(now_ms: u64) -> Self {
+        Self {
+            last_command_ms: now_ms,
+            next_action: 0,
+            backup_frequency: false,
+        }
+    }
+
+    /// Restart the timer, and the sequence of actions.
+    pub fn command_received(&mut self, now_ms: u64) {
+        *self = Self::new(now_ms);
+    }
+
+    /// Whether the radio should stay on its backup frequency, e.g. after a reset.
+    pub const fn uses_backup_frequency(&self) -> bool {
+        self.backup_frequency
+    }
+
+    /// Time since the last telecommand.
+    pub const fn silence_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.last_command_ms)
+    }
+
+    /// Return the next action, if its time has come. Each action runs once per silence, in
+    /// order. Disabled actions are skipped.
+    pub fn poll(&mut self, now_ms: u64, config: &LinkLossConfig) -> Option<LinkLossAction> {
+        let silence_ms = self.silence_ms(now_ms);
+        while let Some(&action) = LinkLossAction::ALL.get(self.next_action) {
+            let threshold_ms = config.threshold_ms(action);
+            if threshold_ms == 0 {
+                self.next_action += 1;
+                continue;
+            }
+            if silence_ms < threshold_ms {
+                return None;
+            }
+            self.next_action += 1;
+            if action == LinkLossAction::SwitchToBackupFrequency {
+                self.backup_frequency = true;
+            }
+            return Some(action);
+        }
+        None
+    }
+
+    fn check_word(words: &[u32]) -> u32 {
+        let mut bytes = [0u8; 4 * (LINK_LOSS_RECORD_WORDS - 1)];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        crc32(&bytes)
+    }
+
+    pub fn to_words(&self) -> [u32; LINK_LOSS_RECORD_WORDS] {
+        let mut words = [
+            LINK_LOSS_RECORD_MAGIC,
+            self.last_command_ms as u32,
+            (self.last_command_ms >> 32) as u32,
+            self.next_action as u32 | (self.backup_frequency as u32) << 8,
+            0,
+        ];
+        words[4] = Self::check_word(&words[..4]);
+        words
+    }
+
+    /// Decode a stored watchdog. Returns `None` if the words don't hold a valid record, such
+    /// as the random RAM contents after a power cycle.
+    pub fn from_words(words: &[u32; LINK_LOSS_RECORD_WORDS]) -> Option<Self> {
+        let [magic, lo, hi, packed, check] = *words;
+        if magic != LINK_LOSS_RECORD_MAGIC || check != Self::check_word(&words[..4]) {
+            return None;
+        }
+        let next_action = (packed & 0xFF) as usize;
+        if next_action > LinkLossAction::ALL.len() || packed >> 9 != 0 {
+            return None;
+        }
+        Some(Self {
+            last_command_ms: ((hi as u64) << 32) | lo as u64,
+            next_action,
+            backup_frequency: packed & 0x100 != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    const CONFIG: LinkLossConfig = LinkLossConfig {
+        reset_radio_ms: 24 * HOUR_MS,
+        backup_frequency_ms: 48 * HOUR_MS,
+        reboot_ms: 72 * HOUR_MS,
+    };
+
+    /// Poll every hour from `from_h` to `to_h` inclusive, collecting (hour, action).
+    fn run(
+        watchdog: &mut LinkLossWatchdog,
+        config: &LinkLossConfig,
+        from_h: u64,
+        to_h: u64,
+    ) -> Vec<(u64, LinkLossAction)> {
+        (from_h..=to_h)
+            .filter_map(|hour| {
+                watchdog
+                    .poll(hour * HOUR_MS, config)
+                    .map(|action| (hour, action))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_escalates_in_order() {
+        let mut watchdog = LinkLossWatchdog::new(0);
+        assert_eq!(
+            run(&mut watchdog, &CONFIG, 0, 100),
+            [
+                (24, LinkLossAction::ResetRadio),
+                (48, LinkLossAction::SwitchToBackupFrequency),
+                (72, LinkLossAction::RebootObc),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_command_resets_timer_and_sequence() {
+        let mut watchdog = LinkLossWatchdog::new(0);
+        assert_eq!(
+            run(&mut watchdog, &CONFIG, 0, 30),
+            [(24, LinkLossAction::ResetRadio)]
+        );
+
+        watchdog.command_received(30 * HOUR_MS);
+        assert_eq!(watchdog.silence_ms(31 * HOUR_MS), HOUR_MS);
+        assert_eq!(run(&mut watchdog, &CONFIG, 31, 53), []);
+        assert_eq!(
+            run(&mut watchdog, &CONFIG, 54, 54),
+            [(54, LinkLossAction::ResetRadio)]
+        );
+    }
+
+    #[test]
+    fn test_disabled_actions_are_skipped() {
+        let config = LinkLossConfig {
+            backup_frequency_ms: 0,
+            ..CONFIG
+        };
+        let mut watchdog = LinkLossWatchdog::new(0);
+        assert_eq!(
+            run(&mut watchdog, &config, 0, 100),
+            [
+                (24, LinkLossAction::ResetRadio),
+                (72, LinkLossAction::RebootObc)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_carries_on_across_resets() {
+        // Reset every 10 hours, picking up the saved watchdog each time.
+        let mut watchdog = LinkLossWatchdog::new(0);
+        let mut actions = Vec::new();
+        for hour in 0..=100 {
+            if hour % 10 == 0 {
+                watchdog = LinkLossWatchdog::from_words(&watchdog.to_words()).unwrap();
+            }
+            if let Some(action) = watchdog.poll(hour * HOUR_MS, &CONFIG) {
+                actions.push((hour, action));
+            }
+        }
+        assert_eq!(
+            actions,
+            [
+                (24, LinkLossAction::ResetRadio),
+                (48, LinkLossAction::SwitchToBackupFrequency),
+                (72, LinkLossAction::RebootObc),
+            ]
+        );
+
+        // After the reboot, the radio goes back to the backup frequency, and nothing more
+        // runs until a telecommand arrives.
+        let mut watchdog = LinkLossWatchdog::from_words(&watchdog.to_words()).unwrap();
+        assert!(watchdog.uses_backup_frequency());
+        assert_eq!(watchdog.poll(200 * HOUR_MS, &CONFIG), None);
+        watchdog.command_received(200 * HOUR_MS);
+        assert!(!watchdog.uses_backup_frequency());
+
+        let mut words = watchdog.to_words();
+        words[1] ^= 1;
+        assert_eq!(LinkLossWatchdog::from_words(&words), None);
+        assert_eq!(
+            LinkLossWatchdog::from_words(&[0; LINK_LOSS_RECORD_WORDS]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_late_poll_runs_one_action_at_a_time() {
+        let mut watchdog = LinkLossWatchdog::new(0);
+        let now = 100 * HOUR_MS;
+        assert_eq!(
+            watchdog.poll(now, &CONFIG),
+            Some(LinkLossAction::ResetRadio)
+        );
+        assert_eq!(
+            watchdog.poll(now, &CONFIG),
+            Some(LinkLossAction::SwitchToBackupFrequency)
+        );
+        assert_eq!(watchdog.poll(now, &CONFIG), Some(LinkLossAction::RebootObc));
+        assert_eq!(watchdog.poll(now, &CONFIG), None);
+    }
+}
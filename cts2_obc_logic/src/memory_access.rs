@@ -0,0 +1,316 @@
+//! Range checking and formatting for the memory peek/poke telecommands.
+
+use core::fmt::{self, Write};
+use cts2_obc_telecommands::MemoryAccess;
+use thiserror::Error;
+
+/// Largest number of bytes that a single `mem_read` may return.
+pub const MAX_MEM_READ_LEN: u32 = 1024;
+
+/// Largest number of bytes that a single `mem_crc` may cover, so that one telecommand can't
+/// keep the main loop busy for long.
+pub const MAX_MEM_CRC_LEN: u32 = 64 * 1024;
+
+/// Number of bytes shown on each line of a hex dump.
+pub const HEX_DUMP_BYTES_PER_LINE: usize = 16;
+
+/// An address range that the memory telecommands are allowed to touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub name: &'static str,
+    pub start: u32,
+    pub len: u32,
+    pub access: MemoryAccess,
+}
+
+impl MemoryRegion {
+    /// Whether `[addr, addr + len)` lies entirely within this region.
+    const fn contains(&self, addr: u32, len: u32) -> bool {
+        let region_end = self.start as u64 + self.len as u64;
+        addr >= self.start && (addr as u64 + len as u64) <= region_end
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum MemoryAccessError {
+    #[error("Length must not be zero")]
+    ZeroLength,
+
+    #[error("Length exceeds the maximum for this command")]
+    LengthTooLarge,
+
+    #[error("Address range is not in an allowed memory region")]
+    NotAllowed,
+
+    #[error("Memory region is read-only")]
+    ReadOnly,
+
+    #[error("Memory region doesn't allow that much access")]
+    AboveLimit,
+}
+
+/// Check that `[addr, addr + len)` may be accessed, and return the region that contains it.
+///
+/// The whole range must lie inside a single region; ranges that straddle regions are rejected.
+pub fn check_access(
+    regions: &[MemoryRegion],
+    addr: u32,
+    len: u32,
+    write: bool,
+) -> Result<&MemoryRegion, MemoryAccessError> {
+    if len == 0 {
+        return Err(MemoryAccessError::ZeroLength);
+    }
+
+    let region = regions
+        .iter()
+        .find(|r| r.access != MemoryAccess::NoAccess && r.contains(addr, len))
+        .ok_or(MemoryAccessError::NotAllowed)?;
+
+    if write && region.access != MemoryAccess::ReadWrite {
+        return Err(MemoryAccessError::ReadOnly);
+    }
+
+    Ok(region)
+}
+
+/// The allowed regions, with the access currently set for each.
+///
+/// The built-in regions give the most access each one may have, so the ground can take access
+/// away and give it back, but e.g. can't make flash writable.
+#[derive(Debug)]
+pub struct MemoryRegions<const N: usize> {
+    limits: &'static [MemoryRegion; N],
+    access: [MemoryAccess; N],
+}
+
+impl<const N: usize> MemoryRegions<N> {
+    /// Start with the built-in access of every region.
+    pub const fn new(limits: &'static [MemoryRegion; N]) -> Self {
+        let mut access = [MemoryAccess::NoAccess; N];
+        let mut i = 0;
+        while i < N {
+            access[i] = limits[i].access;
+            i += 1;
+        }
+        Self { limits, access }
+    }
+
+    /// The built-in regions.
+    pub const fn limits(&self) -> &'static [MemoryRegion; N] {
+        self.limits
+    }
+
+    /// The regions, with their current access.
+    pub fn regions(&self) -> [MemoryRegion; N] {
+        core::array::from_fn(|i| MemoryRegion {
+            access: self.access[i],
+            ..self.limits[i]
+        })
+    }
+
+    /// Like [`check_access`], with the current access of each region.
+    pub fn check(
+        &self,
+        addr: u32,
+        len: u32,
+        write: bool,
+    ) -> Result<MemoryRegion, MemoryAccessError> {
+        check_access(&self.regions(), addr, len, write).copied()
+    }
+
+    /// Set the access of the region that holds `addr`, and return the region.
+    pub fn set_access(
+        &mut self,
+        addr: u32,
+        access: MemoryAccess,
+    ) -> Result<MemoryRegion, MemoryAccessError> {
+        let index = self
+            .limits
+            .iter()
+            .position(|region| region.contains(addr, 1))
+            .ok_or(MemoryAccessError::NotAllowed)?;
+        if access > self.limits[index].access {
+            return Err(MemoryAccessError::AboveLimit);
+        }
+        self.access[index] = access;
+        Ok(MemoryRegion {
+            access,
+            ..self.limits[index]
+        })
+    }
+}
+
+/// Write a classic hex dump of `data`, labelling each line with its address.
+///
+/// Example line: `20000000: 48 45 4c 4c 4f 00 00 00 00 00 00 00 00 00 00 00 |HELLO...........|`
+pub fn write_hex_dump<W: Write>(out: &mut W, base_addr: u32, data: &[u8]) -> fmt::Result {
+    for (line_idx, line) in data.chunks(HEX_DUMP_BYTES_PER_LINE).enumerate() {
+        let line_addr = base_addr.wrapping_add((line_idx * HEX_DUMP_BYTES_PER_LINE) as u32);
+        write!(out, "{:08x}:", line_addr)?;
+
+        for b in line {
+            write!(out, " {:02x}", b)?;
+        }
+        // Pad short final lines so that the ASCII column lines up.
+        for _ in line.len()..HEX_DUMP_BYTES_PER_LINE {
+            out.write_str("   ")?;
+        }
+
+        out.write_str(" |")?;
+        for &b in line {
+            let c = if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            };
+            out.write_char(c)?;
+        }
+        out.write_str("|\r\n")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    const REGIONS: &[MemoryRegion] = &[
+        MemoryRegion {
+            name: "flash",
+            start: 0x0800_0000,
+            len: 0x0020_0000,
+            access: MemoryAccess::ReadOnly,
+        },
+        MemoryRegion {
+            name: "sram1",
+            start: 0x2000_0000,
+            len: 0x0003_0000,
+            access: MemoryAccess::ReadWrite,
+        },
+        MemoryRegion {
+            name: "top",
+            start: 0xFFFF_FF00,
+            len: 0x100,
+            access: MemoryAccess::ReadWrite,
+        },
+    ];
+
+    #[test]
+    fn test_check_access_inside_region() {
+        let region = check_access(REGIONS, 0x2000_0000, 16, true).unwrap();
+        assert_eq!(region.name, "sram1");
+
+        // Exactly up to the last byte of the region.
+        let region = check_access(REGIONS, 0x2002_FFF0, 16, false).unwrap();
+        assert_eq!(region.name, "sram1");
+    }
+
+    #[test]
+    fn test_check_access_rejects_outside_and_straddling() {
+        assert_eq!(
+            check_access(REGIONS, 0x1000_0000, 4, false),
+            Err(MemoryAccessError::NotAllowed)
+        );
+        // One byte past the end.
+        assert_eq!(
+            check_access(REGIONS, 0x2002_FFF0, 17, false),
+            Err(MemoryAccessError::NotAllowed)
+        );
+        // Starts just before the region.
+        assert_eq!(
+            check_access(REGIONS, 0x1FFF_FFFF, 2, false),
+            Err(MemoryAccessError::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_check_access_does_not_overflow() {
+        assert!(check_access(REGIONS, 0xFFFF_FF00, 0x100, false).is_ok());
+        assert_eq!(
+            check_access(REGIONS, 0xFFFF_FFFF, 2, false),
+            Err(MemoryAccessError::NotAllowed)
+        );
+        assert_eq!(
+            check_access(REGIONS, 0xFFFF_FF00, u32::MAX, false),
+            Err(MemoryAccessError::NotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_check_access_write_to_read_only() {
+        assert!(check_access(REGIONS, 0x0800_0000, 4, false).is_ok());
+        assert_eq!(
+            check_access(REGIONS, 0x0800_0000, 4, true),
+            Err(MemoryAccessError::ReadOnly)
+        );
+    }
+
+    #[test]
+    fn test_check_access_zero_length() {
+        assert_eq!(
+            check_access(REGIONS, 0x2000_0000, 0, false),
+            Err(MemoryAccessError::ZeroLength)
+        );
+    }
+
+    #[test]
+    fn test_memory_regions_set_access() {
+        static LIMITS: [MemoryRegion; 3] = [REGIONS[0], REGIONS[1], REGIONS[2]];
+        let mut regions = MemoryRegions::new(&LIMITS);
+        assert_eq!(regions.regions(), LIMITS);
+        assert!(regions.check(0x2000_0000, 4, true).is_ok());
+
+        let region = regions
+            .set_access(0x2000_0010, MemoryAccess::ReadOnly)
+            .unwrap();
+        assert_eq!(region.name, "sram1");
+        assert_eq!(
+            regions.check(0x2000_0000, 4, true),
+            Err(MemoryAccessError::ReadOnly)
+        );
+
+        regions
+            .set_access(0x2000_0000, MemoryAccess::NoAccess)
+            .unwrap();
+        assert_eq!(
+            regions.check(0x2000_0000, 4, false),
+            Err(MemoryAccessError::NotAllowed)
+        );
+
+        // Access can be given back, up to the built-in access.
+        regions
+            .set_access(0x2000_0000, MemoryAccess::ReadWrite)
+            .unwrap();
+        assert!(regions.check(0x2000_0000, 4, true).is_ok());
+        assert_eq!(
+            regions.set_access(0x0800_0000, MemoryAccess::ReadWrite),
+            Err(MemoryAccessError::AboveLimit)
+        );
+        assert_eq!(
+            regions.set_access(0x1000_0000, MemoryAccess::ReadOnly),
+            Err(MemoryAccessError::NotAllowed)
+        );
+        assert_eq!(regions.limits(), &LIMITS);
+    }
+
+    #[test]
+    fn test_hex_dump_format() {
+        let mut out = String::new();
+        let data = b"HELLO\x00\x01\x7f world!!!!!\xff";
+        write_hex_dump(&mut out, 0x2000_0000, data).unwrap();
+        assert_eq!(
+            out,
+            "20000000: 48 45 4c 4c 4f 00 01 7f 20 77 6f 72 6c 64 21 21 |HELLO... world!!|\r\n\
+             20000010: 21 21 21 ff                                     |!!!.|\r\n"
+        );
+    }
+
+    #[test]
+    fn test_hex_dump_empty() {
+        let mut out = String::new();
+        write_hex_dump(&mut out, 0, &[]).unwrap();
+        assert_eq!(out, "");
+    }
+}
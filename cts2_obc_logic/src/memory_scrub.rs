@@ -0,0 +1,244 @@
+//! Memory scrubbing and single-event-upset (SEU) statistics.
+//!
+//! The scrubber reads protected memory a chunk at a time, so that latent bit flips are found
+//! by the hardware ECC/parity checks before the data is needed.
+
+/// Base address of the main flash.
+const FLASH_BASE: u32 = 0x0800_0000;
+
+/// Size of one flash bank.
+const FLASH_BANK_SIZE: u32 = 0x0010_0000;
+
+/// Base address of the system flash (bootloader).
+const SYSTEM_FLASH_BASE: u32 = 0x1FFF_0000;
+
+// FLASH_ECCR bits (RM0432, STM32L4+).
+const ECCR_ADDR_MASK: u32 = 0x001F_FFFF;
+const ECCR_BK_ECC: u32 = 1 << 21;
+const ECCR_SYSF_ECC: u32 = 1 << 22;
+const ECCR_ECCC2: u32 = 1 << 28;
+const ECCR_ECCD2: u32 = 1 << 29;
+const ECCR_ECCC: u32 = 1 << 30;
+const ECCR_ECCD: u32 = 1 << 31;
+
+/// A memory range that the scrubber walks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrubRegion {
+    pub name: &'static str,
+    pub start: u32,
+    pub len: u32,
+    /// Write each word back after reading it. Refreshes the parity of RAM that is not
+    /// otherwise written, so that one upset is not reported again on every pass.
+    pub write_back: bool,
+}
+
+/// A piece of a region to scrub next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrubChunk {
+    pub region: ScrubRegion,
+    pub addr: u32,
+    pub len: u32,
+}
+
+/// Position of the scrubber within a list of regions.
+#[derive(Debug, Default)]
+pub struct ScrubCursor {
+    region_index: usize,
+    offset: u32,
+    pass_count: u32,
+}
+
+impl ScrubCursor {
+    pub const fn new() -> Self {
+        Self {
+            region_index: 0,
+            offset: 0,
+            pass_count: 0,
+        }
+    }
+
+    /// Return the next chunk of at most `max_len` bytes, and advance past it.
+    ///
+    /// Chunks never span two regions. After the last region, the cursor wraps around to the
+    /// first one and counts a completed pass. Returns `None` if there is nothing to scrub.
+    pub fn next_chunk(&mut self, regions: &[ScrubRegion], max_len: u32) -> Option<ScrubChunk> {
+        if max_len == 0 || regions.iter().all(|r| r.len == 0) {
+            return None;
+        }
+
+        // Skip past finished (or empty) regions.
+        loop {
+            match regions.get(self.region_index) {
+                Some(region) if self.offset < region.len => break,
+                Some(_) => {
+                    self.region_index += 1;
+                    self.offset = 0;
+                }
+                None => {
+                    self.region_index = 0;
+                    self.offset = 0;
+                    self.pass_count = self.pass_count.wrapping_add(1);
+                }
+            }
+        }
+
+        let region = regions[self.region_index];
+        let len = (region.len - self.offset).min(max_len);
+        let chunk = ScrubChunk {
+            region,
+            addr: region.start + self.offset,
+            len,
+        };
+        self.offset += len;
+        Some(chunk)
+    }
+
+    /// Number of completed passes over all regions.
+    pub fn pass_count(&self) -> u32 {
+        self.pass_count
+    }
+}
+
+/// A flash ECC event, decoded from the `FLASH_ECCR` register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlashEccStatus {
+    /// A single-bit error was found and corrected.
+    pub corrected: bool,
+    /// A double-bit (uncorrectable) error was found.
+    pub detected: bool,
+    /// Address of the failing double-word.
+    pub address: u32,
+}
+
+impl FlashEccStatus {
+    /// Decode a `FLASH_ECCR` value.
+    pub const fn from_eccr(eccr: u32) -> Self {
+        let offset = eccr & ECCR_ADDR_MASK;
+        let address = if eccr & ECCR_SYSF_ECC != 0 {
+            SYSTEM_FLASH_BASE + offset
+        } else if eccr & ECCR_BK_ECC != 0 {
+            FLASH_BASE + FLASH_BANK_SIZE + offset
+        } else {
+            FLASH_BASE + offset
+        };
+
+        Self {
+            corrected: eccr & (ECCR_ECCC | ECCR_ECCC2) != 0,
+            detected: eccr & (ECCR_ECCD | ECCR_ECCD2) != 0,
+            address,
+        }
+    }
+
+    /// Bits to write back to `FLASH_ECCR` to clear the flags in `eccr` (write 1 to clear).
+    pub const fn clear_flags_mask(eccr: u32) -> u32 {
+        eccr & (ECCR_ECCC | ECCR_ECCC2 | ECCR_ECCD | ECCR_ECCD2)
+    }
+}
+
+/// Count of memory errors of one kind, and where the last one was.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryErrorCount {
+    pub count: u32,
+    /// Address of the most recent error. Zero if there has been none.
+    pub last_address: u32,
+}
+
+/// Snapshot of the single-event-upset statistics since boot.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeuStats {
+    /// Single-bit flash errors, corrected by ECC.
+    pub flash_ecc_corrected: MemoryErrorCount,
+    /// Double-bit flash errors, which ECC cannot correct.
+    pub flash_ecc_detected: MemoryErrorCount,
+    /// SRAM2 parity errors. The hardware does not report the address, so `last_address` is
+    /// the start of the chunk that the scrubber was reading at the time.
+    pub sram2_parity: MemoryErrorCount,
+    /// Non-maskable interrupts with no known memory error cause.
+    pub unknown_nmi_count: u32,
+    /// Whether the SRAM2 parity check is enabled in the option bytes.
+    pub sram2_parity_check_enabled: bool,
+    /// Completed scrub passes over all regions.
+    pub scrub_pass_count: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REGIONS: &[ScrubRegion] = &[
+        ScrubRegion {
+            name: "a",
+            start: 0x1000,
+            len: 10,
+            write_back: false,
+        },
+        ScrubRegion {
+            name: "empty",
+            start: 0x2000,
+            len: 0,
+            write_back: false,
+        },
+        ScrubRegion {
+            name: "b",
+            start: 0x3000,
+            len: 4,
+            write_back: true,
+        },
+    ];
+
+    fn next(cursor: &mut ScrubCursor) -> (&'static str, u32, u32) {
+        let chunk = cursor.next_chunk(REGIONS, 4).unwrap();
+        (chunk.region.name, chunk.addr, chunk.len)
+    }
+
+    #[test]
+    fn test_scrub_cursor_walks_regions_and_wraps() {
+        let mut cursor = ScrubCursor::new();
+        assert_eq!(next(&mut cursor), ("a", 0x1000, 4));
+        assert_eq!(next(&mut cursor), ("a", 0x1004, 4));
+        assert_eq!(next(&mut cursor), ("a", 0x1008, 2));
+        assert_eq!(next(&mut cursor), ("b", 0x3000, 4));
+        assert_eq!(cursor.pass_count(), 0);
+
+        assert_eq!(next(&mut cursor), ("a", 0x1000, 4));
+        assert_eq!(cursor.pass_count(), 1);
+    }
+
+    #[test]
+    fn test_scrub_cursor_nothing_to_scrub() {
+        let mut cursor = ScrubCursor::new();
+        assert_eq!(cursor.next_chunk(&[], 4), None);
+        assert_eq!(cursor.next_chunk(&REGIONS[1..2], 4), None);
+        assert_eq!(cursor.next_chunk(REGIONS, 0), None);
+    }
+
+    #[test]
+    fn test_flash_ecc_decode() {
+        let status = FlashEccStatus::from_eccr(ECCR_ECCC | 0x0001_2340);
+        assert_eq!(
+            status,
+            FlashEccStatus {
+                corrected: true,
+                detected: false,
+                address: 0x0801_2340,
+            }
+        );
+
+        let status = FlashEccStatus::from_eccr(ECCR_ECCD | ECCR_BK_ECC | 0x18);
+        assert!(status.detected && !status.corrected);
+        assert_eq!(status.address, 0x0810_0018);
+
+        let status = FlashEccStatus::from_eccr(ECCR_ECCC2 | ECCR_SYSF_ECC | 0x8);
+        assert!(status.corrected);
+        assert_eq!(status.address, 0x1FFF_0008);
+    }
+
+    #[test]
+    fn test_flash_ecc_clear_mask_keeps_only_flags() {
+        let eccr = ECCR_ECCD | ECCR_ECCC | (1 << 24) | 0x1234;
+        assert_eq!(
+            FlashEccStatus::clear_flags_mask(eccr),
+            ECCR_ECCD | ECCR_ECCC
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Nonvolatile store: a few small records, such as the TX inhibit, kept in flash so that they
+//! survive a reset.
+//!
+//! A record is never rewritten in place. Each write appends an entry to the page being
+//! written, and the newest entry of a slot holds its record. When the page is full, the newest
+//! entry of each slot is copied to the other page, whose header is programmed last. Until
+//! then the full page is still the newest one, so a reset at any point leaves either the old
+//! or the new record.
+
+use crate::crc32::crc32;
+use crate::page_flash::{FLASH_PAGE_WORDS, PageFlash};
+
+const NV_ENTRY_MAGIC: u32 = 0x4E56_0E17;
+
+/// Words in each record slot.
+pub const NV_SLOT_WORDS: usize = 8;
+
+/// Number of record slots.
+pub const NV_SLOT_COUNT: usize = 4;
+
+/// Flash pages the store takes.
+pub const NV_STORE_PAGES: usize = 2;
+
+/// Words at the start of a page: its sequence number, then its complement.
+const HEADER_WORDS: usize = 2;
+
+/// Words in an entry: the slot, the record, then a CRC.
+const ENTRY_WORDS: usize = NV_SLOT_WORDS + 2;
+
+/// Entries that fit in a page.
+pub const NV_ENTRIES_PER_PAGE: usize = (FLASH_PAGE_WORDS - HEADER_WORDS) / ENTRY_WORDS;
+
+const ERASED: u32 = 0xFFFF_FFFF;
+
+/// A record. Slots that were never written read all ones.
+pub type NvRecord = [u32; NV_SLOT_WORDS];
+
+/// The nonvolatile store, in flash.
+#[derive(Debug)]
+pub struct NvStore<F> {
+    flash: F,
+    /// Page being written.
+    page: usize,
+    /// Sequence number of `page`.
+    seq: u32,
+    /// Entry in `page` that the next write goes in.
+    next: usize,
+}
+
+impl<F: PageFlash> NvStore<F> {
+    /// Pick up the records in `flash`, or start an empty store if there is none.
+    pub fn open(mut flash: F) -> Result<Self, F::Error> {
+        let newest = (0..NV_STORE_PAGES)
+            .filter_map(|page| page_seq(&flash, page).map(|seq| (page, seq)))
+            .max_by_key(|(_, seq)| *seq);
+        let Some((page, seq)) = newest else {
+            flash.erase(0)?;
+            flash.program(0, 0, &[0, !0])?;
+            return Ok(Self {
+                flash,
+                page: 0,
+                seq: 0,
+                next: 0,
+            });
+        };
+
+        // Entries are written in order, so the first erased one is next. An entry that was
+        // cut short isn't erased, so it is skipped.
+        let next = (0..NV_ENTRIES_PER_PAGE)
+            .rev()
+            .take_while(|&index| read_entry_words(&flash, page, index) == [ERASED; ENTRY_WORDS])
+            .last()
+            .unwrap_or(NV_ENTRIES_PER_PAGE);
+        Ok(Self {
+            flash,
+            page,
+            seq,
+            next,
+        })
+    }
+
+    /// The record in `slot`.
+    pub fn read(&self, slot: usize) -> NvRecord {
+        (0..self.next)
+            .rev()
+            .filter_map(|index| decode_entry(&read_entry_words(&self.flash, self.page, index)))
+            .find(|(entry_slot, _)| *entry_slot == slot)
+            .map_or([ERASED; NV_SLOT_WORDS], |(_, record)| record)
+    }
+
+    /// Replace the record in `slot`, which is below [`NV_SLOT_COUNT`].
+    pub fn write(&mut self, slot: usize, record: &NvRecord) -> Result<(), F::Error> {
+        if self.read(slot) == *record {
+            return Ok(());
+        }
+        if self.next == NV_ENTRIES_PER_PAGE {
+            return self.move_to_next_page(slot, record);
+        }
+        let offset = entry_offset(self.next);
+        // Move on even if programming fails, as the entry may be partly written.
+        self.next += 1;
+        self.flash
+            .program(self.page, offset, &encode_entry(slot, record))
+    }
+
+    /// Start the other page with the newest record of each slot, `record` being the newest in
+    /// `slot`.
+    fn move_to_next_page(&mut self, slot: usize, record: &NvRecord) -> Result<(), F::Error> {
+        let page = (self.page + 1) % NV_STORE_PAGES;
+        let seq = self.seq.wrapping_add(1);
+        self.flash.erase(page)?;
+        let mut next = 0;
+        for other in 0..NV_SLOT_COUNT {
+            let record = if other == slot {
+                *record
+            } else {
+                self.read(other)
+            };
+            if record != [ERASED; NV_SLOT_WORDS] {
+                self.flash
+                    .program(page, entry_offset(next), &encode_entry(other, &record))?;
+                next += 1;
+            }
+        }
+        self.flash.program(page, 0, &[seq, !seq])?;
+        self.page = page;
+        self.seq = seq;
+        self.next = next;
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn flash(&self) -> &F {
+        &self.flash
+    }
+}
+
+/// Sequence number of a page, if it has a valid header.
+fn page_seq<F: PageFlash>(flash: &F, page: usize) -> Option<u32> {
+    let mut header = [0; HEADER_WORDS];
+    flash.read(page, 0, &mut header);
+    (header[0] == !header[1]).then_some(header[0])
+}
+
+const fn entry_offset(index: usize) -> usize {
+    HEADER_WORDS + index * ENTRY_WORDS
+}
+
+fn read_entry_words<F: PageFlash>(flash: &F, page: usize, index: usize) -> [u32; ENTRY_WORDS] {
+    let mut words = [0; ENTRY_WORDS];
+    flash.read(page, entry_offset(index), &mut words);
+    words
+}
+
+fn check_word(words: &[u32]) -> u32 {
+    let mut bytes = [0u8; 4 * ENTRY_WORDS];
+    bytes[..4].copy_from_slice(&NV_ENTRY_MAGIC.to_le_bytes());
+    for (chunk, word) in bytes[4..].chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    crc32(&bytes)
+}
+
+fn encode_entry(slot: usize, record: &NvRecord) -> [u32; ENTRY_WORDS] {
+    let mut words = [0; ENTRY_WORDS];
+    words[0] = slot as u32;
+    words[1..=NV_SLOT_WORDS].copy_from_slice(record);
+    words[ENTRY_WORDS - 1] = check_word(&words[..ENTRY_WORDS - 1]);
+    words
+}
+
+/// The slot and record of an entry, if it checks out.
+fn decode_entry(words: &[u32; ENTRY_WORDS]) -> Option<(usize, NvRecord)> {
+    if words[ENTRY_WORDS - 1] != check_word(&words[..ENTRY_WORDS - 1]) {
+        return None;
+    }
+    let mut record = [0; NV_SLOT_WORDS];
+    record.copy_from_slice(&words[1..=NV_SLOT_WORDS]);
+    Some((words[0] as usize, record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type FakeFlash = crate::page_flash::FakeFlash<NV_STORE_PAGES>;
+
+    fn record(value: u32) -> NvRecord {
+        [value; NV_SLOT_WORDS]
+    }
+
+    #[test]
+    fn test_write_and_reopen() {
+        let mut store = NvStore::open(FakeFlash::new()).unwrap();
+        assert_eq!(store.read(0), [ERASED; NV_SLOT_WORDS]);
+        store.write(0, &record(1)).unwrap();
+        store.write(1, &record(2)).unwrap();
+        store.write(0, &record(3)).unwrap();
+        assert_eq!(store.read(0), record(3));
+        assert_eq!(store.read(1), record(2));
+
+        // Writing the same record again adds no entry.
+        store.write(1, &record(2)).unwrap();
+        assert_eq!(store.next, 3);
+
+        let store = NvStore::open(store.flash().clone()).unwrap();
+        assert_eq!(store.read(0), record(3));
+        assert_eq!(store.read(1), record(2));
+        assert_eq!(store.read(2), [ERASED; NV_SLOT_WORDS]);
+    }
+
+    #[test]
+    fn test_moves_to_other_page_when_full() {
+        let mut store = NvStore::open(FakeFlash::new()).unwrap();
+        store.write(1, &record(100)).unwrap();
+        let count = 2 * NV_ENTRIES_PER_PAGE as u32 + 5;
+        for i in 0..count {
+            store.write(0, &record(i)).unwrap();
+        }
+        assert_eq!(store.flash().erases, 3);
+
+        let store = NvStore::open(store.flash().clone()).unwrap();
+        assert_eq!(store.read(0), record(count - 1));
+        assert_eq!(store.read(1), record(100));
+    }
+
+    #[test]
+    fn test_interrupted_write_keeps_old_or_new_record() {
+        // Fill the page up to the last entry, so that the writes below append one entry and
+        // then move to the other page.
+        let mut store = NvStore::open(FakeFlash::new()).unwrap();
+        store.write(1, &record(100)).unwrap();
+        for i in 0..NV_ENTRIES_PER_PAGE as u32 - 2 {
+            store.write(0, &record(i % 2)).unwrap();
+        }
+        let before = store.flash().clone();
+        let old = store.read(0);
+
+        // Cut the flash off after every number of steps the two writes take.
+        for budget in 0..=2 * ENTRY_WORDS {
+            let mut flash = before.clone();
+            flash.budget = Some(budget);
+            let mut store = NvStore::open(flash).unwrap();
+            let done = store.write(0, &record(7)).is_ok() && store.write(0, &record(8)).is_ok();
+
+            let mut flash = store.flash().clone();
+            flash.budget = None;
+            let store = NvStore::open(flash).unwrap();
+            let read = store.read(0);
+            assert!(
+                [old, record(7), record(8)].contains(&read),
+                "budget {budget}"
+            );
+            assert_eq!(done, read == record(8), "budget {budget}");
+            assert_eq!(store.read(1), record(100), "budget {budget}");
+        }
+    }
+
+    #[test]
+    fn test_starts_on_blank_or_garbage_flash() {
+        let mut flash = FakeFlash::new();
+        flash.pages[1][..4].copy_from_slice(&[1, 2, 3, 4]);
+        let store = NvStore::open(flash).unwrap();
+        assert_eq!(store.read(0), [ERASED; NV_SLOT_WORDS]);
+        assert_eq!(store.flash().erases, 1);
+    }
+}
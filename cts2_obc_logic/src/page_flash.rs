@@ -0,0 +1,82 @@
+//! Flash pages, as used by the config audit trail and the nonvolatile store.
+
+/// Words in a flash page (4 KiB).
+pub const FLASH_PAGE_WORDS: usize = 1024;
+
+/// A few flash pages, numbered from 0. Flash can only be erased a page at a time, and
+/// programmed once after an erase, in double-words.
+pub trait PageFlash {
+    type Error;
+
+    /// Read `out.len()` words, starting `offset` words into `page`.
+    fn read(&self, page: usize, offset: usize, out: &mut [u32]);
+
+    fn erase(&mut self, page: usize) -> Result<(), Self::Error>;
+
+    /// Program erased words, starting `offset` words into `page`. `offset` and the number of
+    /// words are even.
+    fn program(&mut self, page: usize, offset: usize, words: &[u32]) -> Result<(), Self::Error>;
+}
+
+/// Flash pages in RAM, for tests.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub(crate) struct FakeFlash<const PAGES: usize> {
+    pub pages: [[u32; FLASH_PAGE_WORDS]; PAGES],
+    pub erases: usize,
+    /// Erases and double-words left before a simulated reset stops the flash. `None` for no
+    /// limit.
+    pub budget: Option<usize>,
+}
+
+#[cfg(test)]
+impl<const PAGES: usize> FakeFlash<PAGES> {
+    pub fn new() -> Self {
+        Self {
+            pages: [[0xFFFF_FFFF; FLASH_PAGE_WORDS]; PAGES],
+            erases: 0,
+            budget: None,
+        }
+    }
+
+    fn spend(&mut self) -> Result<(), ()> {
+        match &mut self.budget {
+            Some(0) => Err(()),
+            Some(left) => {
+                *left -= 1;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl<const PAGES: usize> PageFlash for FakeFlash<PAGES> {
+    type Error = ();
+
+    fn read(&self, page: usize, offset: usize, out: &mut [u32]) {
+        out.copy_from_slice(&self.pages[page][offset..offset + out.len()]);
+    }
+
+    fn erase(&mut self, page: usize) -> Result<(), ()> {
+        self.spend()?;
+        self.pages[page] = [0xFFFF_FFFF; FLASH_PAGE_WORDS];
+        self.erases += 1;
+        Ok(())
+    }
+
+    fn program(&mut self, page: usize, offset: usize, words: &[u32]) -> Result<(), ()> {
+        assert!(offset.is_multiple_of(2) && words.len().is_multiple_of(2));
+        for (i, pair) in words.chunks_exact(2).enumerate() {
+            self.spend()?;
+            for (j, &word) in pair.iter().enumerate() {
+                let stored = &mut self.pages[page][offset + 2 * i + j];
+                // Programmed flash must be erased before it is programmed again.
+                assert_eq!(*stored, 0xFFFF_FFFF);
+                *stored = word;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,443 @@
+//! Interactive shell for the umbilical UART, for use from a plain serial terminal.
+//!
+//! The line editor echoes what is typed, and handles backspace, the up and down arrows (to go
+//! through the history), Ctrl-C, Ctrl-U, Ctrl-L and tab completion of command and config
+//! variable names. The shell also has `help` and `exit`, on top of the telecommands.
+
+use core::fmt::{self, Write};
+
+use cts2_obc_telecommands::config::ConfigVariableName;
+use cts2_obc_telecommands::dictionary::{ArgType, COMMANDS, find_command};
+use cts2_obc_telecommands::format::MAX_TELECOMMAND_LEN;
+use cts2_obc_telecommands::response::ERROR_PREFIX;
+use heapless::Deque;
+
+/// A line of this alone, sent without the shell, turns the shell on.
+pub const SHELL_COMMAND: &str = "shell";
+
+pub const PROMPT: &str = "> ";
+
+/// Lines kept for the up arrow.
+pub const HISTORY_LEN: usize = 8;
+
+/// Commands of the shell itself, which aren't telecommands.
+const SHELL_COMMANDS: [&str; 2] = ["help", "exit"];
+
+pub type Line = heapless::String<MAX_TELECOMMAND_LEN>;
+
+/// Where the editor is in an ANSI escape sequence, e.g. `ESC [ A` for the up arrow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    /// After `ESC`.
+    Started,
+    /// After `ESC [`, until the final byte.
+    Csi,
+}
+
+#[derive(Debug)]
+pub struct LineEditor {
+    line: Line,
+    /// Oldest first.
+    history: Deque<Line, HISTORY_LEN>,
+    /// How far back in the history the line came from, while using the arrows. 0 is the newest.
+    browsing: Option<usize>,
+    escape: Escape,
+    /// The last line ended with `\r`, so a `\n` straight after it is part of the same line end.
+    after_cr: bool,
+}
+
+impl LineEditor {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            line: Line::new(),
+            history: Deque::new(),
+            browsing: None,
+            escape: Escape::None,
+            after_cr: false,
+        }
+    }
+
+    /// Handle one received byte, writing the echo to `out`. Returns the line when it is ended
+    /// with Enter (possibly empty), or dropped with Ctrl-C (empty).
+    pub fn feed<W: Write>(&mut self, byte: u8, out: &mut W) -> Result<Option<Line>, fmt::Error> {
+        let after_cr = core::mem::replace(&mut self.after_cr, false);
+
+        match self.escape {
+            Escape::Started => {
+                self.escape = if byte == b'[' {
+                    Escape::Csi
+                } else {
+                    Escape::None
+                };
+                return Ok(None);
+            }
+            Escape::Csi => {
+                // Parameter bytes (e.g. the `3` of `ESC [ 3 ~`) come before the final byte.
+                if (0x40..=0x7e).contains(&byte) {
+                    self.escape = Escape::None;
+                    match byte {
+                        b'A' => self.history_back(out)?,
+                        b'B' => self.history_forward(out)?,
+                        _ => {}
+                    }
+                }
+                return Ok(None);
+            }
+            Escape::None => {}
+        }
+
+        match byte {
+            b'\n' if after_cr => {}
+            b'\r' | b'\n' => {
+                self.after_cr = byte == b'\r';
+                out.write_str("\r\n")?;
+                let line = core::mem::take(&mut self.line);
+                self.remember(&line);
+                return Ok(Some(line));
+            }
+            // Ctrl-C
+            0x03 => {
+                out.write_str("^C\r\n")?;
+                self.line.clear();
+                self.browsing = None;
+                return Ok(Some(Line::new()));
+            }
+            // Backspace, or Delete, which most terminals send for the backspace key.
+            0x08 | 0x7f if self.line.pop().is_some() => out.write_str("\x08 \x08")?,
+            // Ctrl-U
+            0x15 => {
+                self.line.clear();
+                self.redraw(out)?;
+            }
+            // Ctrl-L
+            0x0c => self.redraw(out)?,
+            b'\t' => self.complete(out)?,
+            0x1b => self.escape = Escape::Started,
+            b' '..=b'~' => {
+                if self.line.push(byte as char).is_ok() {
+                    out.write_char(byte as char)?;
+                } else {
+                    // Bell: the line is full.
+                    out.write_char('\x07')?;
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// The line typed so far.
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+
+    /// Write the prompt and the line typed so far, over the current terminal line.
+    pub fn redraw<W: Write>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "\r\x1b[K{}{}", PROMPT, self.line)
+    }
+
+    fn remember(&mut self, line: &Line) {
+        self.browsing = None;
+        if line.trim().is_empty() || self.history.back() == Some(line) {
+            return;
+        }
+        if self.history.is_full() {
+            self.history.pop_front();
+        }
+        let _ = self.history.push_back(line.clone());
+    }
+
+    fn history_back<W: Write>(&mut self, out: &mut W) -> fmt::Result {
+        let back = self.browsing.map_or(0, |n| n + 1);
+        match self.history.iter().rev().nth(back) {
+            Some(line) => {
+                self.line = line.clone();
+                self.browsing = Some(back);
+                self.redraw(out)
+            }
+            None => out.write_char('\x07'),
+        }
+    }
+
+    fn history_forward<W: Write>(&mut self, out: &mut W) -> fmt::Result {
+        match self.browsing {
+            None => return out.write_char('\x07'),
+            Some(0) => {
+                self.browsing = None;
+                self.line.clear();
+            }
+            Some(back) => {
+                self.browsing = Some(back - 1);
+                if let Some(line) = self.history.iter().rev().nth(back - 1) {
+                    self.line = line.clone();
+                }
+            }
+        }
+        self.redraw(out)
+    }
+
+    /// Complete the word at the end of the line. With several matches, complete as far as they
+    /// agree, or list them if they don't agree any further.
+    fn complete<W: Write>(&mut self, out: &mut W) -> fmt::Result {
+        let word_start = self
+            .line
+            .rfind(|c| " \t(),;=[".contains(c))
+            .map_or(0, |i| i + 1);
+        let before = self.line[..word_start].trim_end();
+        let is_command = before.is_empty() || before.ends_with(';') || before == "help";
+        let word = &self.line[word_start..];
+
+        let mut matches = completions(word, is_command);
+        let Some(first) = matches.next() else {
+            return out.write_char('\x07');
+        };
+        // Longest prefix shared by all the matches.
+        let mut common = first;
+        let mut count = 1;
+        for name in matches {
+            let shared = common
+                .bytes()
+                .zip(name.bytes())
+                .take_while(|(a, b)| a == b)
+                .count();
+            common = &common[..shared];
+            count += 1;
+        }
+
+        if count > 1 && common.len() == word.len() {
+            out.write_str("\r\n")?;
+            for name in completions(word, is_command) {
+                write!(out, "{}  ", name)?;
+            }
+            out.write_str("\r\n")?;
+            return self.redraw(out);
+        }
+        let rest = &common[word.len()..];
+        if self.line.push_str(rest).is_err() {
+            return out.write_char('\x07');
+        }
+        out.write_str(rest)
+    }
+}
+
+/// Names starting with `word`: commands, or config variable names for arguments.
+fn completions(word: &str, is_command: bool) -> impl Iterator<Item = &'static str> + '_ {
+    let commands = COMMANDS
+        .iter()
+        .map(|command| command.name)
+        .chain(SHELL_COMMANDS)
+        .filter(move |_| is_command);
+    let config_names = ConfigVariableName::ALL
+        .into_iter()
+        .map(|name| name.as_str())
+        .filter(move |_| !is_command);
+    commands
+        .chain(config_names)
+        .filter(move |name| name.starts_with(word))
+}
+
+/// What to do with a line typed in the shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellLine<'a> {
+    Empty,
+    /// `help`, or `help <command>`.
+    Help(Option<&'a str>),
+    Exit,
+    /// Anything else: a line of telecommands.
+    Telecommands(&'a str),
+}
+
+impl<'a> ShellLine<'a> {
+    pub fn parse(line: &'a str) -> Self {
+        let line = line.trim();
+        match line.split_once(char::is_whitespace) {
+            _ if line.is_empty() => ShellLine::Empty,
+            _ if line == "help" => ShellLine::Help(None),
+            _ if line == "exit" => ShellLine::Exit,
+            Some(("help", topic)) => ShellLine::Help(Some(topic.trim())),
+            _ => ShellLine::Telecommands(line),
+        }
+    }
+}
+
+/// Write the help: every command with its arguments, or the details of one command.
+pub fn write_help<W: Write>(out: &mut W, topic: Option<&str>) -> fmt::Result {
+    let Some(name) = topic else {
+        for command in COMMANDS {
+            write!(out, "  {}\r\n", command.signature())?;
+        }
+        out.write_str("  help <command>\r\n  exit\r\n")?;
+        return out.write_str("Arguments can be given in order or as name=value.\r\n");
+    };
+
+    let Some(command) = find_command(name) else {
+        return write!(out, "{}unknown command\r\n", ERROR_PREFIX);
+    };
+    write!(
+        out,
+        "{}\r\n  {}\r\n",
+        command.signature(),
+        command.description
+    )?;
+    for param in command.params {
+        write!(out, "  {}: ", param.name)?;
+        match param.arg_type {
+            ArgType::Choice(choices) => {
+                out.write_str("one of")?;
+                for choice in choices {
+                    write!(out, " {}", choice)?;
+                }
+            }
+            arg_type => out.write_str(arg_type.as_str())?,
+        }
+        if let Some((min, max)) = param.range {
+            write!(out, ", {}..={}", min, max)?;
+        }
+        if param.optional {
+            out.write_str(", optional")?;
+        }
+        write!(out, ", e.g. {}\r\n", param.example)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    /// Type `input`, and return the lines entered and everything echoed.
+    fn type_in(editor: &mut LineEditor, input: &[u8]) -> (std::vec::Vec<String>, String) {
+        let mut echo = String::new();
+        let mut lines = std::vec::Vec::new();
+        for &byte in input {
+            if let Some(line) = editor.feed(byte, &mut echo).unwrap() {
+                lines.push(line.as_str().into());
+            }
+        }
+        (lines, echo)
+    }
+
+    #[test]
+    fn test_echo_and_backspace() {
+        let mut editor = LineEditor::new();
+        let (lines, echo) = type_in(&mut editor, b"hellx\x7f\x08lo_world\r\n");
+        assert_eq!(lines, ["hello_world"]);
+        assert_eq!(echo, "hellx\x08 \x08\x08 \x08lo_world\r\n");
+
+        // `\r\n` ends one line, but `\n\n` is two.
+        let (lines, _) = type_in(&mut editor, b"a\n\nb\r");
+        assert_eq!(lines, ["a", "", "b"]);
+
+        // Backspace on an empty line does nothing; Ctrl-C drops the line.
+        let (lines, echo) = type_in(&mut editor, b"\x7fabc\x03");
+        assert_eq!(lines, [""]);
+        assert_eq!(echo, "abc^C\r\n");
+
+        // Ctrl-L writes the line again, and Ctrl-U clears it.
+        let (_, echo) = type_in(&mut editor, b"ab\x0c\x15");
+        assert_eq!(echo, "ab\r\x1b[K> ab\r\x1b[K> ");
+    }
+
+    #[test]
+    fn test_history() {
+        let mut editor = LineEditor::new();
+        type_in(&mut editor, b"one\rtwo\rtwo\r\r");
+
+        // Up twice: repeated and empty lines are only kept once.
+        let (_, echo) = type_in(&mut editor, b"\x1b[A");
+        assert_eq!(echo, "\r\x1b[K> two");
+        type_in(&mut editor, b"\x1b[A");
+        assert_eq!(editor.line(), "one");
+        let (_, echo) = type_in(&mut editor, b"\x1b[A");
+        assert_eq!(echo, "\x07");
+
+        // Down goes back to an empty line; other escape sequences are ignored.
+        type_in(&mut editor, b"\x1b[B");
+        assert_eq!(editor.line(), "two");
+        type_in(&mut editor, b"\x1b[B\x1b[3~\x1b[C");
+        assert_eq!(editor.line(), "");
+
+        // A line from the history can be edited before it is sent.
+        let (lines, _) = type_in(&mut editor, b"\x1b[A\x1b[A\x7f\x7fld\r");
+        assert_eq!(lines, ["old"]);
+
+        for i in 0..HISTORY_LEN {
+            type_in(&mut editor, std::format!("cmd{i}\r").as_bytes());
+        }
+        assert_eq!(editor.history.len(), HISTORY_LEN);
+        assert_eq!(editor.history.front().unwrap(), "cmd0");
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut editor = LineEditor::new();
+        let (_, echo) = type_in(&mut editor, b"hel\t");
+        assert_eq!(editor.line(), "hel");
+        assert_eq!(echo, "hel\r\nhello_world  help  \r\n\r\x1b[K> hel");
+
+        type_in(&mut editor, b"lo\t");
+        assert_eq!(editor.line(), "hello_world");
+
+        // Config variable names complete in arguments, up to where they differ.
+        let mut editor = LineEditor::new();
+        type_in(&mut editor, b"get_config(deploy_\t");
+        assert_eq!(editor.line(), "get_config(deploy_");
+        type_in(&mut editor, b"b\t");
+        assert_eq!(editor.line(), "get_config(deploy_burn_ms");
+
+        let mut editor = LineEditor::new();
+        type_in(&mut editor, b"hello_world; set_conf\t");
+        assert_eq!(editor.line(), "hello_world; set_config");
+        let (_, echo) = type_in(&mut editor, b"(name=link_loss_reb\t");
+        assert_eq!(
+            editor.line(),
+            "hello_world; set_config(name=link_loss_reboot_s"
+        );
+        assert!(echo.ends_with("_reboot_s"));
+
+        let mut editor = LineEditor::new();
+        let (_, echo) = type_in(&mut editor, b"nothing\t");
+        assert_eq!(echo, "nothing\x07");
+    }
+
+    #[test]
+    fn test_shell_lines() {
+        assert_eq!(ShellLine::parse("  "), ShellLine::Empty);
+        assert_eq!(ShellLine::parse("help"), ShellLine::Help(None));
+        assert_eq!(
+            ShellLine::parse("help  mem_read "),
+            ShellLine::Help(Some("mem_read"))
+        );
+        assert_eq!(ShellLine::parse(" exit"), ShellLine::Exit);
+        assert_eq!(
+            ShellLine::parse("hello_world; get_sys_uptime"),
+            ShellLine::Telecommands("hello_world; get_sys_uptime")
+        );
+    }
+
+    #[test]
+    fn test_help() {
+        let mut out = String::new();
+        write_help(&mut out, None).unwrap();
+        assert_eq!(out.lines().count(), COMMANDS.len() + 3);
+        assert!(out.contains("  get_config(name: config_name)\r\n"));
+        // Listed the way `ShellLine::parse` takes it.
+        assert!(out.contains("  help <command>\r\n"));
+        assert_eq!(
+            ShellLine::parse("help get_config"),
+            ShellLine::Help(Some("get_config"))
+        );
+
+        out.clear();
+        write_help(&mut out, Some("tx_disable_for")).unwrap();
+        assert!(out.starts_with("tx_disable_for(duration_s: u32)\r\n"));
+        assert!(out.contains("  duration_s: u32, 1..="));
+
+        out.clear();
+        write_help(&mut out, Some("nope")).unwrap();
+        assert_eq!(out, "ERR: unknown command\r\n");
+    }
+}
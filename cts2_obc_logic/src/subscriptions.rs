@@ -0,0 +1,205 @@
+//! Periodic telemetry subscriptions.
+//!
+//! A subscription asks for one telemetry channel to be sent on a link every `period_ms`.
+
+use thiserror::Error;
+
+use crate::link::Link;
+
+/// Shortest allowed subscription period, to stop a subscription from flooding the link.
+pub const MIN_SUBSCRIPTION_PERIOD_MS: u32 = 100;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum SubscriptionError {
+    #[error("Subscription period is too short")]
+    PeriodTooShort,
+
+    #[error("Subscription table is full")]
+    TableFull,
+
+    #[error("Channel is not subscribed on this link")]
+    NotSubscribed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subscription {
+    /// Name of the telemetry point to send.
+    pub channel: &'static str,
+    pub period_ms: u32,
+    pub link: Link,
+    next_due_ms: u64,
+}
+
+/// Bounded table of subscriptions, driven by `take_due` from a periodic loop.
+#[derive(Debug)]
+pub struct SubscriptionTable<const N: usize> {
+    subscriptions: heapless::Vec<Subscription, N>,
+}
+
+impl<const N: usize> SubscriptionTable<N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            subscriptions: heapless::Vec::new(),
+        }
+    }
+
+    /// Subscribe to `channel` on `link`. The first value is due straight away.
+    ///
+    /// Subscribing again to the same channel on the same link changes its period.
+    pub fn subscribe(
+        &mut self,
+        channel: &'static str,
+        period_ms: u32,
+        link: Link,
+        now_ms: u64,
+    ) -> Result<(), SubscriptionError> {
+        if period_ms < MIN_SUBSCRIPTION_PERIOD_MS {
+            return Err(SubscriptionError::PeriodTooShort);
+        }
+
+        let subscription = Subscription {
+            channel,
+            period_ms,
+            link,
+            next_due_ms: now_ms,
+        };
+        match self
+            .subscriptions
+            .iter_mut()
+            .find(|s| s.channel == channel && s.link == link)
+        {
+            Some(existing) => *existing = subscription,
+            None => self
+                .subscriptions
+                .push(subscription)
+                .map_err(|_| SubscriptionError::TableFull)?,
+        }
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, channel: &str, link: Link) -> Result<(), SubscriptionError> {
+        let index = self
+            .subscriptions
+            .iter()
+            .position(|s| s.channel == channel && s.link == link)
+            .ok_or(SubscriptionError::NotSubscribed)?;
+        self.subscriptions.remove(index);
+        Ok(())
+    }
+
+    /// Return the subscriptions that are due at `now_ms`, and schedule their next send.
+    ///
+    /// If the caller fell behind by more than a period, the missed sends are skipped rather
+    /// than sent in a burst.
+    pub fn take_due(&mut self, now_ms: u64) -> heapless::Vec<Subscription, N> {
+        let mut due = heapless::Vec::new();
+        for s in self.subscriptions.iter_mut() {
+            if now_ms < s.next_due_ms {
+                continue;
+            }
+            let _ = due.push(*s);
+
+            s.next_due_ms += s.period_ms as u64;
+            if s.next_due_ms <= now_ms {
+                s.next_due_ms = now_ms + s.period_ms as u64;
+            }
+        }
+        due
+    }
+
+    pub fn subscriptions(&self) -> &[Subscription] {
+        &self.subscriptions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn due_channels<const N: usize>(
+        table: &mut SubscriptionTable<N>,
+        now_ms: u64,
+    ) -> std::vec::Vec<&'static str> {
+        table.take_due(now_ms).iter().map(|s| s.channel).collect()
+    }
+
+    #[test]
+    fn test_periodic_schedule() {
+        let mut table = SubscriptionTable::<4>::new();
+        table
+            .subscribe("uptime_ms", 1_000, Link::Umbilical, 0)
+            .unwrap();
+        table
+            .subscribe("uart_rx_bytes", 10_000, Link::Umbilical, 0)
+            .unwrap();
+
+        assert_eq!(due_channels(&mut table, 0), ["uptime_ms", "uart_rx_bytes"]);
+        assert!(due_channels(&mut table, 999).is_empty());
+        assert_eq!(due_channels(&mut table, 1_000), ["uptime_ms"]);
+        // Late polls don't drift the schedule.
+        assert_eq!(due_channels(&mut table, 2_400), ["uptime_ms"]);
+        assert_eq!(due_channels(&mut table, 3_000), ["uptime_ms"]);
+        assert_eq!(
+            due_channels(&mut table, 10_000),
+            ["uptime_ms", "uart_rx_bytes"]
+        );
+    }
+
+    #[test]
+    fn test_missed_periods_are_skipped() {
+        let mut table = SubscriptionTable::<4>::new();
+        table
+            .subscribe("uptime_ms", 1_000, Link::Umbilical, 0)
+            .unwrap();
+        assert_eq!(due_channels(&mut table, 0).len(), 1);
+
+        assert_eq!(due_channels(&mut table, 5_500).len(), 1);
+        assert!(due_channels(&mut table, 6_000).is_empty());
+        assert_eq!(due_channels(&mut table, 6_500).len(), 1);
+    }
+
+    #[test]
+    fn test_resubscribe_changes_period() {
+        let mut table = SubscriptionTable::<1>::new();
+        table
+            .subscribe("uptime_ms", 1_000, Link::Umbilical, 0)
+            .unwrap();
+        table
+            .subscribe("uptime_ms", 5_000, Link::Umbilical, 0)
+            .unwrap();
+
+        assert_eq!(table.subscriptions().len(), 1);
+        assert_eq!(table.subscriptions()[0].period_ms, 5_000);
+    }
+
+    #[test]
+    fn test_subscribe_errors() {
+        let mut table = SubscriptionTable::<1>::new();
+        assert_eq!(
+            table.subscribe("uptime_ms", 10, Link::Umbilical, 0),
+            Err(SubscriptionError::PeriodTooShort)
+        );
+        table
+            .subscribe("uptime_ms", 1_000, Link::Umbilical, 0)
+            .unwrap();
+        assert_eq!(
+            table.subscribe("event_count", 1_000, Link::Umbilical, 0),
+            Err(SubscriptionError::TableFull)
+        );
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let mut table = SubscriptionTable::<2>::new();
+        table
+            .subscribe("uptime_ms", 1_000, Link::Umbilical, 0)
+            .unwrap();
+        assert_eq!(
+            table.unsubscribe("event_count", Link::Umbilical),
+            Err(SubscriptionError::NotSubscribed)
+        );
+        table.unsubscribe("uptime_ms", Link::Umbilical).unwrap();
+        assert!(due_channels(&mut table, 10_000).is_empty());
+    }
+}
@@ -0,0 +1,349 @@
+//! Registry of named telemetry points, read on demand.
+//!
+//! Each module that owns some state lists its points (name, type, units, read callback), and
+//! the firmware registers them all at startup. Values are reported as one JSON object per line.
+
+use core::fmt::{self, Write};
+use cts2_obc_telecommands::dictionary;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryType {
+    U32,
+    U64,
+    I32,
+    F32,
+    Bool,
+    Str,
+}
+
+impl TelemetryType {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            TelemetryType::U32 => "u32",
+            TelemetryType::U64 => "u64",
+            TelemetryType::I32 => "i32",
+            TelemetryType::F32 => "f32",
+            TelemetryType::Bool => "bool",
+            TelemetryType::Str => "str",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TelemetryValue {
+    U32(u32),
+    U64(u64),
+    I32(i32),
+    F32(f32),
+    Bool(bool),
+    Str(&'static str),
+}
+
+impl TelemetryValue {
+    pub const fn value_type(&self) -> TelemetryType {
+        match self {
+            TelemetryValue::U32(_) => TelemetryType::U32,
+            TelemetryValue::U64(_) => TelemetryType::U64,
+            TelemetryValue::I32(_) => TelemetryType::I32,
+            TelemetryValue::F32(_) => TelemetryType::F32,
+            TelemetryValue::Bool(_) => TelemetryType::Bool,
+            TelemetryValue::Str(_) => TelemetryType::Str,
+        }
+    }
+
+    /// The value as a number, for limit checks. Booleans are 0 or 1; strings have no value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            TelemetryValue::U32(v) => Some(v as f64),
+            TelemetryValue::U64(v) => Some(v as f64),
+            TelemetryValue::I32(v) => Some(v as f64),
+            TelemetryValue::F32(v) => Some(v as f64),
+            TelemetryValue::Bool(v) => Some(if v { 1.0 } else { 0.0 }),
+            TelemetryValue::Str(_) => None,
+        }
+    }
+}
+
+/// Formats the value as a JSON literal. Non-finite floats become `null`.
+impl fmt::Display for TelemetryValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryValue::U32(v) => write!(f, "{}", v),
+            TelemetryValue::U64(v) => write!(f, "{}", v),
+            TelemetryValue::I32(v) => write!(f, "{}", v),
+            TelemetryValue::F32(v) if v.is_finite() => write!(f, "{}", v),
+            TelemetryValue::F32(_) => f.write_str("null"),
+            TelemetryValue::Bool(v) => write!(f, "{}", v),
+            TelemetryValue::Str(s) => write_json_str(f, s),
+        }
+    }
+}
+
+/// A named value that can be read on demand.
+#[derive(Debug, Clone, Copy)]
+pub struct TelemetryPoint {
+    /// Unique name, in `snake_case`.
+    pub name: &'static str,
+    pub value_type: TelemetryType,
+    /// Units of the value (e.g. `ms`), or empty if it has none.
+    pub units: &'static str,
+    /// Reads the current value. Must return a value of `value_type`.
+    pub read: fn() -> TelemetryValue,
+}
+
+/// Whether `lists`, one after the other, hold exactly the telemetry points of the command
+/// dictionary, in order, with the same types and units. Const, so that the firmware can check
+/// its points when it is built.
+pub const fn matches_dictionary(lists: &[&[TelemetryPoint]]) -> bool {
+    let expected = dictionary::TELEMETRY;
+    let mut count = 0;
+    let mut i = 0;
+    while i < lists.len() {
+        let mut j = 0;
+        while j < lists[i].len() {
+            let point = &lists[i][j];
+            if count == expected.len() {
+                return false;
+            }
+            let info = &expected[count];
+            if !str_eq(point.name, info.name)
+                || !str_eq(point.value_type.as_str(), info.value_type)
+                || !str_eq(point.units, info.units)
+            {
+                return false;
+            }
+            count += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    count == expected.len()
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum TelemetryError {
+    #[error("Unknown telemetry point")]
+    UnknownPoint,
+
+    #[error("Telemetry point name is already registered")]
+    DuplicateName,
+
+    #[error("Telemetry registry is full")]
+    RegistryFull,
+}
+
+/// Fixed-capacity registry of telemetry points.
+#[derive(Debug)]
+pub struct TelemetryRegistry<const N: usize> {
+    points: heapless::Vec<TelemetryPoint, N>,
+}
+
+impl<const N: usize> TelemetryRegistry<N> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            points: heapless::Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, point: TelemetryPoint) -> Result<(), TelemetryError> {
+        if self.get(point.name).is_some() {
+            return Err(TelemetryError::DuplicateName);
+        }
+        self.points
+            .push(point)
+            .map_err(|_| TelemetryError::RegistryFull)
+    }
+
+    /// Register every point in `points`. Stops at the first error.
+    pub fn register_all(&mut self, points: &[TelemetryPoint]) -> Result<(), TelemetryError> {
+        points.iter().try_for_each(|point| self.register(*point))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&TelemetryPoint> {
+        self.points.iter().find(|point| point.name == name)
+    }
+
+    /// Points in registration order.
+    pub fn points(&self) -> &[TelemetryPoint] {
+        &self.points
+    }
+}
+
+/// Write a JSON string literal, escaping quotes, backslashes and control characters.
+fn write_json_str<W: Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Write a point's current value, e.g. `{"name":"uptime_ms","value":1234,"units":"ms"}`.
+pub fn write_telemetry_value_json<W: Write>(
+    out: &mut W,
+    point: &TelemetryPoint,
+    value: &TelemetryValue,
+) -> fmt::Result {
+    out.write_str("{\"name\":")?;
+    write_json_str(out, point.name)?;
+    write!(out, ",\"value\":{},\"units\":", value)?;
+    write_json_str(out, point.units)?;
+    out.write_char('}')
+}
+
+/// Write a point's description, e.g. `{"name":"uptime_ms","type":"u64","units":"ms"}`.
+pub fn write_telemetry_info_json<W: Write>(out: &mut W, point: &TelemetryPoint) -> fmt::Result {
+    out.write_str("{\"name\":")?;
+    write_json_str(out, point.name)?;
+    write!(
+        out,
+        ",\"type\":\"{}\",\"units\":",
+        point.value_type.as_str()
+    )?;
+    write_json_str(out, point.units)?;
+    out.write_char('}')
+}
+
+/// Write an error for a point that could not be read, e.g.
+/// `{"name":"foo","error":"Unknown telemetry point"}`.
+pub fn write_telemetry_error_json<W: Write>(
+    out: &mut W,
+    name: &str,
+    error: TelemetryError,
+) -> fmt::Result {
+    out.write_str("{\"name\":")?;
+    write_json_str(out, name)?;
+    write!(out, ",\"error\":\"{}\"}}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    const UPTIME: TelemetryPoint = TelemetryPoint {
+        name: "uptime_ms",
+        value_type: TelemetryType::U64,
+        units: "ms",
+        read: || TelemetryValue::U64(1234),
+    };
+
+    const MODE: TelemetryPoint = TelemetryPoint {
+        name: "mode",
+        value_type: TelemetryType::Str,
+        units: "",
+        read: || TelemetryValue::Str("nominal"),
+    };
+
+    #[test]
+    fn test_matches_dictionary() {
+        let mut points: std::vec::Vec<TelemetryPoint> = dictionary::TELEMETRY
+            .iter()
+            .map(|info| TelemetryPoint {
+                name: info.name,
+                value_type: [
+                    TelemetryType::U32,
+                    TelemetryType::U64,
+                    TelemetryType::I32,
+                    TelemetryType::F32,
+                    TelemetryType::Bool,
+                    TelemetryType::Str,
+                ]
+                .into_iter()
+                .find(|value_type| value_type.as_str() == info.value_type)
+                .unwrap(),
+                units: info.units,
+                read: || TelemetryValue::U32(0),
+            })
+            .collect();
+        let (first, rest) = points.split_at(3);
+        assert!(matches_dictionary(&[first, rest]));
+        assert!(!matches_dictionary(&[first]));
+
+        points.swap(0, 1);
+        assert!(!matches_dictionary(&[&points]));
+        points.swap(0, 1);
+        points[4].units = "s";
+        assert!(!matches_dictionary(&[&points]));
+        points[4].units = dictionary::TELEMETRY[4].units;
+        points.push(UPTIME);
+        assert!(!matches_dictionary(&[&points]));
+    }
+
+    #[test]
+    fn test_registry_register_and_get() {
+        let mut registry = TelemetryRegistry::<2>::new();
+        registry.register_all(&[UPTIME, MODE]).unwrap();
+
+        let point = registry.get("mode").unwrap();
+        assert_eq!((point.read)(), TelemetryValue::Str("nominal"));
+        assert!(registry.get("nope").is_none());
+        assert_eq!(registry.points().len(), 2);
+    }
+
+    #[test]
+    fn test_registry_rejects_duplicates_and_overflow() {
+        let mut registry = TelemetryRegistry::<1>::new();
+        registry.register(UPTIME).unwrap();
+        assert_eq!(
+            registry.register(UPTIME),
+            Err(TelemetryError::DuplicateName)
+        );
+        assert_eq!(registry.register(MODE), Err(TelemetryError::RegistryFull));
+    }
+
+    #[test]
+    fn test_value_json() {
+        let mut out = String::new();
+        write_telemetry_value_json(&mut out, &UPTIME, &(UPTIME.read)()).unwrap();
+        assert_eq!(out, r#"{"name":"uptime_ms","value":1234,"units":"ms"}"#);
+
+        let mut out = String::new();
+        write_telemetry_value_json(&mut out, &MODE, &TelemetryValue::Str("a\"b")).unwrap();
+        assert_eq!(out, r#"{"name":"mode","value":"a\"b","units":""}"#);
+    }
+
+    #[test]
+    fn test_value_literals() {
+        assert_eq!(TelemetryValue::I32(-5).to_string(), "-5");
+        assert_eq!(TelemetryValue::F32(1.5).to_string(), "1.5");
+        assert_eq!(TelemetryValue::F32(f32::NAN).to_string(), "null");
+        assert_eq!(TelemetryValue::Bool(true).to_string(), "true");
+        assert_eq!(TelemetryValue::U32(7).value_type(), TelemetryType::U32);
+        assert_eq!(TelemetryValue::Bool(true).as_f64(), Some(1.0));
+        assert_eq!(TelemetryValue::Str("safe").as_f64(), None);
+    }
+
+    #[test]
+    fn test_info_and_error_json() {
+        let mut out = String::new();
+        write_telemetry_info_json(&mut out, &UPTIME).unwrap();
+        assert_eq!(out, r#"{"name":"uptime_ms","type":"u64","units":"ms"}"#);
+
+        let mut out = String::new();
+        write_telemetry_error_json(&mut out, "foo", TelemetryError::UnknownPoint).unwrap();
+        assert_eq!(out, r#"{"name":"foo","error":"Unknown telemetry point"}"#);
+    }
+}
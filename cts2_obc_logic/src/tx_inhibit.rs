@@ -0,0 +1,143 @@
+//! Ground-commanded transmitter inhibit.
+//!
+//! The state is kept in flash, so that a reset cannot turn the transmitter back on. A timed
+//! inhibit ends at a mission-elapsed time (see `deployment`).
+
+use crate::crc32::crc32;
+
+const TX_INHIBIT_RECORD_MAGIC: u32 = 0x7809_1B17;
+
+/// Number of words in a stored TX inhibit record.
+pub const TX_INHIBIT_RECORD_WORDS: usize = 5;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TxInhibit {
+    /// The transmitter may be used.
+    #[default]
+    Enabled,
+    /// The transmitter is off until `tx_enable`.
+    Disabled,
+    /// The transmitter is off until this mission-elapsed time.
+    DisabledUntil { met_ms: u64 },
+}
+
+impl TxInhibit {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            TxInhibit::Enabled => "enabled",
+            TxInhibit::Disabled => "disabled",
+            TxInhibit::DisabledUntil { .. } => "disabled_until",
+        }
+    }
+
+    /// Numeric code, as stored and in events: 0 enabled, 1 disabled, 2 disabled until a time.
+    pub const fn code(&self) -> u32 {
+        match self {
+            TxInhibit::Enabled => 0,
+            TxInhibit::Disabled => 1,
+            TxInhibit::DisabledUntil { .. } => 2,
+        }
+    }
+
+    /// Whether the transmitter may be used at this mission-elapsed time.
+    pub const fn allows_tx(&self, mission_elapsed_ms: u64) -> bool {
+        match *self {
+            TxInhibit::Enabled => true,
+            TxInhibit::Disabled => false,
+            TxInhibit::DisabledUntil { met_ms } => mission_elapsed_ms >= met_ms,
+        }
+    }
+
+    fn check_word(kind: u32, lo: u32, hi: u32) -> u32 {
+        let mut bytes = [0u8; 16];
+        bytes[..4].copy_from_slice(&TX_INHIBIT_RECORD_MAGIC.to_le_bytes());
+        bytes[4..8].copy_from_slice(&kind.to_le_bytes());
+        bytes[8..12].copy_from_slice(&lo.to_le_bytes());
+        bytes[12..].copy_from_slice(&hi.to_le_bytes());
+        crc32(&bytes)
+    }
+
+    pub fn to_words(&self) -> [u32; TX_INHIBIT_RECORD_WORDS] {
+        let kind = self.code();
+        let until = match *self {
+            TxInhibit::DisabledUntil { met_ms } => met_ms,
+            _ => 0,
+        };
+        let lo = until as u32;
+        let hi = (until >> 32) as u32;
+        [
+            TX_INHIBIT_RECORD_MAGIC,
+            kind,
+            lo,
+            hi,
+            Self::check_word(kind, lo, hi),
+        ]
+    }
+
+    /// Decode a stored record.
+    ///
+    /// Erased flash (all ones) means that nothing was ever stored, and the transmitter is
+    /// enabled. Any other invalid record is treated as `Disabled`: if the stored state can't
+    /// be trusted, stay off the air until the ground says otherwise.
+    pub fn from_words(words: &[u32; TX_INHIBIT_RECORD_WORDS]) -> Self {
+        if words.iter().all(|&word| word == 0xFFFF_FFFF) {
+            return TxInhibit::Enabled;
+        }
+
+        let [magic, kind, lo, hi, check] = *words;
+        if magic != TX_INHIBIT_RECORD_MAGIC || check != Self::check_word(kind, lo, hi) {
+            return TxInhibit::Disabled;
+        }
+        match kind {
+            0 => TxInhibit::Enabled,
+            2 => TxInhibit::DisabledUntil {
+                met_ms: ((hi as u64) << 32) | lo as u64,
+            },
+            _ => TxInhibit::Disabled,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_tx() {
+        assert!(TxInhibit::Enabled.allows_tx(0));
+        assert!(!TxInhibit::Disabled.allows_tx(u64::MAX));
+
+        let timed = TxInhibit::DisabledUntil { met_ms: 5_000 };
+        assert!(!timed.allows_tx(4_999));
+        assert!(timed.allows_tx(5_000));
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        for state in [
+            TxInhibit::Enabled,
+            TxInhibit::Disabled,
+            TxInhibit::DisabledUntil {
+                met_ms: 0x1_0000_0042,
+            },
+        ] {
+            assert_eq!(TxInhibit::from_words(&state.to_words()), state);
+        }
+    }
+
+    #[test]
+    fn test_erased_and_corrupt_records() {
+        assert_eq!(
+            TxInhibit::from_words(&[0xFFFF_FFFF; TX_INHIBIT_RECORD_WORDS]),
+            TxInhibit::Enabled
+        );
+
+        let mut words = TxInhibit::Enabled.to_words();
+        words[2] ^= 1;
+        assert_eq!(TxInhibit::from_words(&words), TxInhibit::Disabled);
+        assert_eq!(
+            TxInhibit::from_words(&[0; TX_INHIBIT_RECORD_WORDS]),
+            TxInhibit::Disabled
+        );
+    }
+}
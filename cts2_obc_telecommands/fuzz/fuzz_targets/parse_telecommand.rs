@@ -0,0 +1,33 @@
+//! Feed arbitrary bytes to `parse_telecommand`, as the umbilical UART and radio do.
+
+#![no_main]
+
+use cts2_obc_telecommands::error::ParsedTelecommandErr;
+use cts2_obc_telecommands::parse_telecommand;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The firmware drops commands that aren't UTF-8 before parsing them.
+    let Ok(input) = core::str::from_utf8(data) else {
+        return;
+    };
+
+    // The result depends only on the input. Compare the debug text, as `NaN != NaN`.
+    let result = parse_telecommand(input);
+    assert_eq!(
+        format!("{:?}", result),
+        format!("{:?}", parse_telecommand(input))
+    );
+
+    match result {
+        Err(ParsedTelecommandErr::Syntax(e)) => assert!(e.offset <= input.len()),
+        // The canonical text parses back to the same telecommand, so to the same text.
+        Ok(telecommand) => {
+            if let Ok(text) = telecommand.to_canonical() {
+                let reparsed = parse_telecommand(&text).expect("canonical text must parse");
+                assert_eq!(reparsed.to_canonical().as_deref(), Ok(text.as_str()));
+            }
+        }
+        Err(_) => {}
+    }
+});
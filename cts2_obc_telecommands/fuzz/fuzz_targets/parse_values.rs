@@ -0,0 +1,32 @@
+//! Feed arbitrary text to the argument value parsers, which also run on stored FDIR rules.
+
+#![no_main]
+
+use core::str::FromStr;
+use cts2_obc_telecommands::config::ConfigValue;
+use cts2_obc_telecommands::fdir::{FdirAction, LimitCheck};
+use cts2_obc_telecommands::grammar::split_call;
+use libfuzzer_sys::fuzz_target;
+
+/// Parse `input`, and check that a successful parse formats to text that parses the same way.
+fn check<T: FromStr + ToString>(input: &str) {
+    if let Ok(value) = input.parse::<T>() {
+        let text = value.to_string();
+        let reparsed = text.parse::<T>().ok().expect("formatted value must parse");
+        assert_eq!(reparsed.to_string(), text);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = core::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Err(e) = split_call(input) {
+        assert!(e.offset <= input.len());
+    }
+    let _ = ConfigValue::from_str(input);
+    check::<ConfigValue>(input);
+    check::<LimitCheck>(input);
+    check::<FdirAction>(input);
+});
@@ -0,0 +1,139 @@
+//! Several telecommands on one line, separated by `;`, with an optional `#` comment:
+//! `set_config(a, u32(1)); set_config(b, u32(2)); get_config(a) # check`.
+//!
+//! The whole line is parsed before any of it runs, so a typo in the last telecommand doesn't
+//! leave the first ones applied. The OBC then runs the telecommands in order, and stops at the
+//! first one that fails.
+//!
+//! A checked batch keeps each telecommand as text, and parses it again just before it runs. A
+//! `Telecommand` is over 600 bytes, so holding a whole batch of them would take kilobytes of
+//! stack.
+
+use crate::error::{BatchError, ParsedTelecommandErr, SyntaxError};
+use crate::grammar::{char_offset, split_batch, strip_comment};
+use crate::{Telecommand, parse_telecommand};
+
+/// Most telecommands on one line.
+pub const MAX_BATCH_LEN: usize = 8;
+
+/// The telecommands of one line, in order, each checked to parse.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TelecommandBatch<'a> {
+    texts: heapless::Vec<&'a str, MAX_BATCH_LEN>,
+}
+
+impl<'a> TelecommandBatch<'a> {
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+
+    /// The telecommands in order, parsed one at a time.
+    pub fn iter(&self) -> impl Iterator<Item = Telecommand> + '_ {
+        // `parse_batch` already parsed each text, so this never drops one.
+        self.texts
+            .iter()
+            .filter_map(|text| parse_telecommand(text).ok())
+    }
+}
+
+/// Parse a line of telecommands. A line that is blank or only a comment gives an empty batch.
+///
+/// Syntax error offsets count from the start of the line, not of the telecommand.
+pub fn parse_batch(line: &str) -> Result<TelecommandBatch<'_>, BatchError> {
+    let mut batch = TelecommandBatch::default();
+    for (index, (offset, text)) in split_batch(strip_comment(line)).enumerate() {
+        parse_telecommand(text).map_err(|error| {
+            let error = match error {
+                ParsedTelecommandErr::Syntax(e) => ParsedTelecommandErr::Syntax(SyntaxError {
+                    offset: char_offset(line, offset) + e.offset,
+                    ..e
+                }),
+                e => e,
+            };
+            BatchError::InvalidTelecommand { index, error }
+        })?;
+        batch
+            .texts
+            .push(text)
+            .map_err(|_| BatchError::TooManyTelecommands)?;
+    }
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SyntaxErrorKind;
+    use crate::mode::OperatingMode;
+    use std::vec::Vec;
+
+    fn parsed(batch: &TelecommandBatch) -> Vec<Telecommand> {
+        batch.iter().collect()
+    }
+
+    fn split(line: &str) -> Vec<(usize, &str)> {
+        split_batch(strip_comment(line)).collect()
+    }
+
+    #[test]
+    fn test_split_batch_and_comments() {
+        assert_eq!(
+            split("hello_world; get_config(a) # check; not a command"),
+            [(0, "hello_world"), (12, " get_config(a) ")]
+        );
+        assert_eq!(split(" ;; hello_world; "), [(3, " hello_world")]);
+        assert_eq!(split("# just a comment"), []);
+
+        // Separators inside quoted strings and brackets belong to the telecommand.
+        assert_eq!(
+            split(r#"f("a; b # c \" ;"); g(h(1; 2))"#),
+            [(0, r#"f("a; b # c \" ;")"#), (19, " g(h(1; 2))")]
+        );
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let batch = parse_batch("set_mode(safe); get_telemetry(mode) # check").unwrap();
+        assert_eq!(
+            parsed(&batch),
+            [
+                Telecommand::set_mode(OperatingMode::Safe),
+                Telecommand::get_telemetry("mode".try_into().unwrap()),
+            ]
+        );
+        assert_eq!(
+            parse_batch("  # nothing to do"),
+            Ok(TelecommandBatch::default())
+        );
+
+        // One bad telecommand rejects the whole line.
+        assert_eq!(
+            parse_batch("hello_world; get_events(x); get_sys_uptime"),
+            Err(BatchError::InvalidTelecommand {
+                index: 1,
+                error: ParsedTelecommandErr::InvalidArgument(0),
+            })
+        );
+        assert_eq!(
+            parse_batch("hello_world; hello_world(foo"),
+            Err(BatchError::InvalidTelecommand {
+                index: 1,
+                error: ParsedTelecommandErr::Syntax(SyntaxError {
+                    offset: 28,
+                    kind: SyntaxErrorKind::UnexpectedEnd,
+                }),
+            })
+        );
+
+        let full = ["hello_world"; MAX_BATCH_LEN].join(";");
+        assert_eq!(parse_batch(&full).map(|b| b.len()), Ok(MAX_BATCH_LEN));
+        assert_eq!(
+            parse_batch(&std::format!("{full}; hello_world")),
+            Err(BatchError::TooManyTelecommands)
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::error::ConfigError;
+use core::fmt;
 use core::str::FromStr;
-use core::sync::atomic::{AtomicU32, Ordering};
+use core::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
 
-use crate::shared;
+use crate::grammar;
 
 // Global configuration store
 // There is no float for atomic, consider
@@ -10,13 +11,82 @@ use crate::shared;
 pub struct ConfigStore {
     heartbeat_ms: AtomicU32,
     config_demo_variable1: AtomicU32,
+    deploy_silence_s: AtomicU32,
+    deploy_burn_ms: AtomicU32,
+    deploy_retry_delay_ms: AtomicU32,
+    deploy_max_attempts: AtomicU32,
+    link_loss_reset_radio_s: AtomicU32,
+    link_loss_backup_frequency_s: AtomicU32,
+    link_loss_reboot_s: AtomicU32,
+    arm_window_s: AtomicU32,
+    // how many times each variable changed value, by `ConfigVariableName::index()`
+    change_counts: [AtomicU32; ConfigVariableName::ALL.len()],
+    // called after every change, see `set_audit_hook()`
+    audit_hook: AtomicPtr<ConfigAuditHook>,
 }
 
+/// A function called after every change of a config variable, with the variable, the old
+/// value and the new value. Used to keep an audit trail.
+pub struct ConfigAuditHook(pub fn(ConfigVariableName, ConfigValue, ConfigValue));
+
 // All configuration variable names
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigVariableName {
     HeartbeatMs,
     ConfigDemoVariable1,
+    /// Radio silence after separation, before antenna deployment, in seconds.
+    DeploySilenceS,
+    /// How long each burn-wire attempt lasts.
+    DeployBurnMs,
+    /// Pause between burn-wire attempts on the same antenna.
+    DeployRetryDelayMs,
+    /// Burn-wire attempts per antenna before giving up on it.
+    DeployMaxAttempts,
+    /// Seconds without a telecommand before the radio is reset. 0 disables it.
+    LinkLossResetRadioS,
+    /// Seconds without a telecommand before switching to the backup frequency. 0 disables it.
+    LinkLossBackupFrequencyS,
+    /// Seconds without a telecommand before the OBC reboots. 0 disables it.
+    LinkLossRebootS,
+    /// Seconds after an `arm` in which the hazardous telecommand it names may run.
+    ArmWindowS,
+}
+
+impl ConfigVariableName {
+    /// Every variable, in declaration order.
+    pub const ALL: [ConfigVariableName; 10] = [
+        ConfigVariableName::HeartbeatMs,
+        ConfigVariableName::ConfigDemoVariable1,
+        ConfigVariableName::DeploySilenceS,
+        ConfigVariableName::DeployBurnMs,
+        ConfigVariableName::DeployRetryDelayMs,
+        ConfigVariableName::DeployMaxAttempts,
+        ConfigVariableName::LinkLossResetRadioS,
+        ConfigVariableName::LinkLossBackupFrequencyS,
+        ConfigVariableName::LinkLossRebootS,
+        ConfigVariableName::ArmWindowS,
+    ];
+
+    /// Position in [`ConfigVariableName::ALL`].
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// The name as written in telecommands, e.g. `heartbeat_ms`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ConfigVariableName::HeartbeatMs => "heartbeat_ms",
+            ConfigVariableName::ConfigDemoVariable1 => "config_demo_variable1",
+            ConfigVariableName::DeploySilenceS => "deploy_silence_s",
+            ConfigVariableName::DeployBurnMs => "deploy_burn_ms",
+            ConfigVariableName::DeployRetryDelayMs => "deploy_retry_delay_ms",
+            ConfigVariableName::DeployMaxAttempts => "deploy_max_attempts",
+            ConfigVariableName::LinkLossResetRadioS => "link_loss_reset_radio_s",
+            ConfigVariableName::LinkLossBackupFrequencyS => "link_loss_backup_frequency_s",
+            ConfigVariableName::LinkLossRebootS => "link_loss_reboot_s",
+            ConfigVariableName::ArmWindowS => "arm_window_s",
+        }
+    }
 }
 
 impl FromStr for ConfigVariableName {
@@ -26,6 +96,14 @@ impl FromStr for ConfigVariableName {
         match s {
             "heartbeat_ms" => Ok(ConfigVariableName::HeartbeatMs),
             "config_demo_variable1" => Ok(ConfigVariableName::ConfigDemoVariable1),
+            "deploy_silence_s" => Ok(ConfigVariableName::DeploySilenceS),
+            "deploy_burn_ms" => Ok(ConfigVariableName::DeployBurnMs),
+            "deploy_retry_delay_ms" => Ok(ConfigVariableName::DeployRetryDelayMs),
+            "deploy_max_attempts" => Ok(ConfigVariableName::DeployMaxAttempts),
+            "link_loss_reset_radio_s" => Ok(ConfigVariableName::LinkLossResetRadioS),
+            "link_loss_backup_frequency_s" => Ok(ConfigVariableName::LinkLossBackupFrequencyS),
+            "link_loss_reboot_s" => Ok(ConfigVariableName::LinkLossRebootS),
+            "arm_window_s" => Ok(ConfigVariableName::ArmWindowS),
             _ => Err(ConfigError::ConfigVariableNotFound),
         }
     }
@@ -40,11 +118,43 @@ pub enum ConfigValue {
     U8(u8),
 }
 
+impl ConfigValue {
+    /// The type as written in telecommands, e.g. `u32`.
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            ConfigValue::U32(_) => "u32",
+            ConfigValue::Bool(_) => "bool",
+            ConfigValue::F32(_) => "f32",
+            ConfigValue::I32(_) => "i32",
+            ConfigValue::U8(_) => "u8",
+        }
+    }
+
+    /// Whether both values have the same type, whatever their values.
+    pub fn same_type(&self, other: &ConfigValue) -> bool {
+        core::mem::discriminant(self) == core::mem::discriminant(other)
+    }
+}
+
+/// Formats the value the same way it is parsed, e.g. `u32(5)`.
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigValue::U32(value) => write!(f, "u32({})", value),
+            ConfigValue::Bool(value) => write!(f, "bool({})", value),
+            ConfigValue::F32(value) => write!(f, "f32({})", value),
+            ConfigValue::I32(value) => write!(f, "i32({})", value),
+            ConfigValue::U8(value) => write!(f, "u8({})", value),
+        }
+    }
+}
+
 impl FromStr for ConfigValue {
     type Err = ConfigError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (value_type, value_str) = shared::extract_function_and_args(s);
+        let (value_type, value_str) =
+            grammar::split_call(s).map_err(|_| ConfigError::ConfigParseValueTypeError)?;
 
         macro_rules! parse_value {
             ($type:ty, $variant:path) => {
@@ -73,6 +183,16 @@ impl ConfigStore {
         Self {
             heartbeat_ms: AtomicU32::new(1000),
             config_demo_variable1: AtomicU32::new(123),
+            deploy_silence_s: AtomicU32::new(30 * 60),
+            deploy_burn_ms: AtomicU32::new(5_000),
+            deploy_retry_delay_ms: AtomicU32::new(10_000),
+            deploy_max_attempts: AtomicU32::new(3),
+            link_loss_reset_radio_s: AtomicU32::new(24 * 60 * 60),
+            link_loss_backup_frequency_s: AtomicU32::new(48 * 60 * 60),
+            link_loss_reboot_s: AtomicU32::new(72 * 60 * 60),
+            arm_window_s: AtomicU32::new(60),
+            change_counts: [const { AtomicU32::new(0) }; ConfigVariableName::ALL.len()],
+            audit_hook: AtomicPtr::new(core::ptr::null_mut()),
         }
     }
 
@@ -85,11 +205,109 @@ impl ConfigStore {
             ConfigVariableName::ConfigDemoVariable1 => {
                 ConfigValue::U32(self.config_demo_variable1.load(Ordering::Relaxed))
             }
+            ConfigVariableName::DeploySilenceS => {
+                ConfigValue::U32(self.deploy_silence_s.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::DeployBurnMs => {
+                ConfigValue::U32(self.deploy_burn_ms.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::DeployRetryDelayMs => {
+                ConfigValue::U32(self.deploy_retry_delay_ms.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::DeployMaxAttempts => {
+                ConfigValue::U32(self.deploy_max_attempts.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::LinkLossResetRadioS => {
+                ConfigValue::U32(self.link_loss_reset_radio_s.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::LinkLossBackupFrequencyS => {
+                ConfigValue::U32(self.link_loss_backup_frequency_s.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::LinkLossRebootS => {
+                ConfigValue::U32(self.link_loss_reboot_s.load(Ordering::Relaxed))
+            }
+            ConfigVariableName::ArmWindowS => {
+                ConfigValue::U32(self.arm_window_s.load(Ordering::Relaxed))
+            }
         }
     }
 
-    // set a configuration value by name
+    // the default value of a variable (from `ConfigStore::new()`)
+    pub fn default_value(name: ConfigVariableName) -> ConfigValue {
+        Self::new().get(name)
+    }
+
+    // every variable with its current value, in `ConfigVariableName::ALL` order
+    pub fn values(&self) -> impl Iterator<Item = (ConfigVariableName, ConfigValue)> + '_ {
+        ConfigVariableName::ALL
+            .into_iter()
+            .map(|name| (name, self.get(name)))
+    }
+
+    // the variables that differ from their defaults, with the current and default values
+    pub fn diff(
+        &self,
+    ) -> impl Iterator<Item = (ConfigVariableName, ConfigValue, ConfigValue)> + '_ {
+        self.values()
+            .map(|(name, value)| (name, value, Self::default_value(name)))
+            .filter(|(_, value, default)| value != default)
+    }
+
+    // reset one variable to its default value
+    pub fn reset(&self, name: ConfigVariableName) {
+        // The default always has the variable's type.
+        let _ = self.set(name, Self::default_value(name));
+    }
+
+    // set several variables at once: either all of them, or none if any has the wrong type
+    pub fn set_all(&self, values: &[(ConfigVariableName, ConfigValue)]) -> Result<(), ConfigError> {
+        if !values
+            .iter()
+            .all(|(name, value)| self.get(*name).same_type(value))
+        {
+            return Err(ConfigError::ConfigVariableNotThisType);
+        }
+        for (name, value) in values {
+            self.set(*name, *value)?;
+        }
+        Ok(())
+    }
+
+    // reset every variable to its default value (from `ConfigStore::new()`)
+    pub fn reset_to_defaults(&self) {
+        for name in ConfigVariableName::ALL {
+            self.reset(name);
+        }
+    }
+
+    // how many times a variable changed value. Observers compare it with the count they last
+    // saw, to find out that the variable changed.
+    pub fn change_count(&self, name: ConfigVariableName) -> u32 {
+        self.change_counts[name.index()].load(Ordering::Relaxed)
+    }
+
+    // call `hook` after every change from now on, replacing any earlier hook
+    pub fn set_audit_hook(&self, hook: &'static ConfigAuditHook) {
+        self.audit_hook
+            .store(hook as *const ConfigAuditHook as *mut _, Ordering::Release);
+    }
+
+    // set a configuration value by name. Counts a change if the value is different.
     pub fn set(&self, name: ConfigVariableName, value: ConfigValue) -> Result<(), ConfigError> {
+        let old = self.get(name);
+        self.store(name, value)?;
+        if value != old {
+            self.change_counts[name.index()].fetch_add(1, Ordering::Relaxed);
+            // SAFETY: The pointer is null, or was made from a `&'static` in `set_audit_hook()`.
+            if let Some(hook) = unsafe { self.audit_hook.load(Ordering::Acquire).as_ref() } {
+                (hook.0)(name, old, value);
+            }
+        }
+        Ok(())
+    }
+
+    // store a configuration value by name
+    fn store(&self, name: ConfigVariableName, value: ConfigValue) -> Result<(), ConfigError> {
         match (name, value) {
             (ConfigVariableName::HeartbeatMs, ConfigValue::U32(v)) => {
                 self.heartbeat_ms.store(v, Ordering::Relaxed);
@@ -99,6 +317,39 @@ impl ConfigStore {
                 self.config_demo_variable1.store(v, Ordering::Relaxed);
                 Ok(())
             }
+            (ConfigVariableName::DeploySilenceS, ConfigValue::U32(v)) => {
+                self.deploy_silence_s.store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::DeployBurnMs, ConfigValue::U32(v)) => {
+                self.deploy_burn_ms.store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::DeployRetryDelayMs, ConfigValue::U32(v)) => {
+                self.deploy_retry_delay_ms.store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::DeployMaxAttempts, ConfigValue::U32(v)) => {
+                self.deploy_max_attempts.store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::LinkLossResetRadioS, ConfigValue::U32(v)) => {
+                self.link_loss_reset_radio_s.store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::LinkLossBackupFrequencyS, ConfigValue::U32(v)) => {
+                self.link_loss_backup_frequency_s
+                    .store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::LinkLossRebootS, ConfigValue::U32(v)) => {
+                self.link_loss_reboot_s.store(v, Ordering::Relaxed);
+                Ok(())
+            }
+            (ConfigVariableName::ArmWindowS, ConfigValue::U32(v)) => {
+                self.arm_window_s.store(v, Ordering::Relaxed);
+                Ok(())
+            }
             _ => Err(ConfigError::ConfigVariableNotThisType),
         }
     }
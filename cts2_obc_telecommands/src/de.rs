@@ -0,0 +1,335 @@
+//! Deserialize telecommand arguments into an arguments struct with serde.
+//!
+//! Each field of the struct is a parameter, in declaration order. So a struct such as
+//! [`DemoCommandWithArgumentsArgs`](crate::DemoCommandWithArgumentsArgs) can be given
+//! positionally, `demo_command_with_arguments(1, 2, true, 3.0, 4.0)`, or by name,
+//! `demo_command_with_arguments(arg_u32=1, arg_bool=true, ...)`. Missing `Option` fields are
+//! `None`, and missing `#[serde(default)]` fields take their default.
+
+use core::fmt;
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::error::ParsedTelecommandErr;
+use crate::grammar::{Arg, Args, Params};
+use crate::shared::parse_u64;
+
+/// Longest quoted string argument that can be deserialized.
+const MAX_STRING_LEN: usize = 64;
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("{0}")]
+    Parse(ParsedTelecommandErr),
+
+    #[error("missing field `{0}`")]
+    MissingField(&'static str),
+
+    #[error("invalid value")]
+    Invalid,
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        Error::Invalid
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::MissingField(field)
+    }
+}
+
+/// Deserialize the remaining parameters into `T`, which must be a struct. Call
+/// [`Params::finish`] afterwards to reject unknown keyword arguments.
+pub fn from_params<'a, T: de::Deserialize<'a>>(
+    params: &mut Params<'a>,
+) -> Result<T, ParsedTelecommandErr> {
+    let first_index = params.index();
+    let mut fields: &'static [&'static str] = &[];
+
+    T::deserialize(ParamsDeserializer {
+        params,
+        fields: &mut fields,
+    })
+    .map_err(|e| match e {
+        Error::Parse(e) => e,
+        Error::MissingField(field) => {
+            let position = fields.iter().position(|&f| f == field).unwrap_or(0);
+            ParsedTelecommandErr::MissingArgument(first_index.saturating_add(position as u8))
+        }
+        Error::Invalid => ParsedTelecommandErr::InvalidArgument(first_index),
+    })
+}
+
+struct ParamsDeserializer<'p, 'a> {
+    params: &'p mut Params<'a>,
+    /// The struct's fields, to turn a missing field back into a parameter index.
+    fields: &'p mut &'static [&'static str],
+}
+
+impl<'de> Deserializer<'de> for ParamsDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Invalid)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        *self.fields = fields;
+        visitor.visit_map(Fields {
+            params: self.params,
+            fields,
+            current: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+/// The fields of the struct that were given an argument, in order.
+struct Fields<'p, 'a> {
+    params: &'p mut Params<'a>,
+    fields: &'static [&'static str],
+    current: Option<Arg<'a>>,
+}
+
+impl<'de> MapAccess<'de> for Fields<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        while let Some((&field, rest)) = self.fields.split_first() {
+            self.fields = rest;
+            if let Some(arg) = self.params.optional(field) {
+                self.current = Some(arg);
+                return seed
+                    .deserialize(BorrowedStrDeserializer::new(field))
+                    .map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let arg = self.current.take().ok_or(Error::Invalid)?;
+        seed.deserialize(ArgDeserializer(arg)).map_err(|e| match e {
+            Error::Parse(e) => Error::Parse(e),
+            _ => Error::Parse(arg.invalid()),
+        })
+    }
+}
+
+/// Deserializes one argument, using the same syntax as [`Arg`]'s methods.
+struct ArgDeserializer<'a>(Arg<'a>);
+
+impl<'de> ArgDeserializer<'de> {
+    fn parse<T: core::str::FromStr>(&self) -> Result<T, Error> {
+        self.0.text().parse().map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'de> Deserializer<'de> for ArgDeserializer<'de> {
+    type Error = Error;
+
+    /// Guess the type from the syntax, for types that accept several.
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let text = self.0.text();
+        if text.starts_with('"') {
+            self.deserialize_str(visitor)
+        } else if text.starts_with('[') {
+            self.deserialize_seq(visitor)
+        } else if text == "true" || text == "false" {
+            self.deserialize_bool(visitor)
+        } else if text == "null" {
+            visitor.visit_unit()
+        } else if let Some(value) = parse_u64(text) {
+            visitor.visit_u64(value)
+        } else if let Ok(value) = text.parse::<i64>() {
+            visitor.visit_i64(value)
+        } else if let Ok(value) = text.parse::<f64>() {
+            visitor.visit_f64(value)
+        } else {
+            visitor.visit_borrowed_str(text)
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.parse()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(parse_u64(self.0.text()).ok_or(Error::Invalid)?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.parse()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.parse()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.parse()?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.0.text().starts_with('"') {
+            let s = self.0.string::<MAX_STRING_LEN>().map_err(Error::Parse)?;
+            visitor.visit_str(&s)
+        } else {
+            visitor.visit_borrowed_str(self.0.text())
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.0.text() == "null" {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(Elements(self.0.list().map_err(Error::Parse)?))
+    }
+
+    /// Unit variants, written as their name (e.g. `bin`).
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(BorrowedStrDeserializer::new(self.0.text()))
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 char bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct map
+        struct ignored_any
+    }
+}
+
+/// The elements of a list argument.
+struct Elements<'a>(Args<'a>);
+
+impl<'de> SeqAccess<'de> for Elements<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.0
+            .next()
+            .map(|arg| seed.deserialize(ArgDeserializer(arg)))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grammar::parse_command;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestArgs {
+        count: u8,
+        label: heapless::String<8>,
+        values: heapless::Vec<u32, 4>,
+        scale: Option<f32>,
+        #[serde(default)]
+        verbose: bool,
+    }
+
+    fn parse(input: &str) -> Result<TestArgs, ParsedTelecommandErr> {
+        let (_, args) = parse_command(input).unwrap();
+        let mut params = Params::new(args);
+        let parsed = from_params(&mut params)?;
+        params.finish()?;
+        Ok(parsed)
+    }
+
+    #[test]
+    fn test_positional_and_keyword_forms_agree() {
+        let expected = TestArgs {
+            count: 3,
+            label: "a, b".try_into().unwrap(),
+            values: heapless::Vec::from_slice(&[1, 0x10]).unwrap(),
+            scale: Some(1.5),
+            verbose: false,
+        };
+        assert_eq!(parse(r#"f(3, "a, b", [1, 0x10], 1.5)"#), Ok(expected));
+
+        let keyword = parse(r#"f(label="a, b", scale=1.5, values=[1, 0x10], count=3)"#);
+        assert_eq!(keyword, parse(r#"f(3, "a, b", [1, 0x10], 1.5)"#));
+    }
+
+    #[test]
+    fn test_optional_fields_and_errors() {
+        let parsed = parse("f(count=1, label=x, values=[], verbose=true)").unwrap();
+        assert_eq!(parsed.scale, None);
+        assert!(parsed.verbose);
+
+        assert_eq!(
+            parse("f(1, x)"),
+            Err(ParsedTelecommandErr::MissingArgument(2))
+        );
+        assert_eq!(
+            parse("f(label=x, values=[], count=300)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse("f(1, x, [], scale=fast)"),
+            Err(ParsedTelecommandErr::InvalidArgument(3))
+        );
+        assert_eq!(
+            parse("f(1, x, [], colour=red)"),
+            Err(ParsedTelecommandErr::UnexpectedKeyword(3))
+        );
+    }
+}
@@ -0,0 +1,812 @@
+//! Machine-readable dictionary of the telecommands, config variables, telemetry points and
+//! error codes, for ground software.
+//!
+//! The OBC sends it in reply to `get_command_dictionary`, and `cts2_obc_image_tool
+//! command-dictionary` prints the same text on the host. Each entry is one JSON object per line,
+//! tagged with its `kind`:
+//!
+//! ```text
+//! {"kind":"command","name":"get_events","description":"...","hazardous":false,"params":[{"name":"count","type":"u32","optional":false,"example":"10"}]}
+//! {"kind":"config","name":"heartbeat_ms","type":"u32","default":"u32(1000)"}
+//! {"kind":"telemetry","name":"uptime_ms","type":"u64","units":"ms"}
+//! {"kind":"error","code":5,"name":"invalid_argument","message":"Invalid argument value"}
+//! ```
+
+use core::fmt::{self, Write};
+
+use crate::config::{ConfigStore, ConfigVariableName};
+use crate::{
+    MAX_CONFIG_BLOB_LEN, MAX_MEM_WRITE_LEN, MAX_TELEMETRY_NAME_LEN,
+    MAX_TELEMETRY_POINTS_PER_REQUEST,
+};
+
+/// Longest entry, in bytes, without the line ending.
+pub const MAX_ENTRY_LEN: usize = 768;
+
+/// Type of a telecommand argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgType {
+    U8,
+    U32,
+    U64,
+    Bool,
+    F32,
+    F64,
+    /// Hex bytes, bare (`deadbeef`) or as a list (`[0xde, 0xad]`).
+    Bytes,
+    /// Name of a config variable, e.g. `heartbeat_ms`.
+    ConfigName,
+    /// Name of a hazardous telecommand, e.g. `set_config`.
+    CommandName,
+    /// A typed config value, e.g. `u32(500)`.
+    ConfigValue,
+    /// Name of a telemetry point, e.g. `uptime_ms`.
+    TelemetryName,
+    /// List of telemetry point names, e.g. `[uptime_ms, mode]`.
+    TelemetryNames,
+    /// `above(x)`, `below(x)` or `outside(low..high)`.
+    LimitCheck,
+    /// `event`, `mode(<mode>)` or `command(<telecommand>)`.
+    FdirAction,
+    /// One of a fixed set of words.
+    Choice(&'static [&'static str]),
+}
+
+impl ArgType {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ArgType::U8 => "u8",
+            ArgType::U32 => "u32",
+            ArgType::U64 => "u64",
+            ArgType::Bool => "bool",
+            ArgType::F32 => "f32",
+            ArgType::F64 => "f64",
+            ArgType::Bytes => "bytes",
+            ArgType::ConfigName => "config_name",
+            ArgType::CommandName => "command_name",
+            ArgType::ConfigValue => "config_value",
+            ArgType::TelemetryName => "telemetry_name",
+            ArgType::TelemetryNames => "telemetry_names",
+            ArgType::LimitCheck => "limit_check",
+            ArgType::FdirAction => "fdir_action",
+            ArgType::Choice(_) => "choice",
+        }
+    }
+}
+
+/// A parameter of a telecommand, in the order it is given positionally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParamInfo {
+    /// Name, for keyword arguments.
+    pub name: &'static str,
+    pub arg_type: ArgType,
+    /// Smallest and largest accepted value of a number, or length of bytes, a name or a list.
+    pub range: Option<(u64, u64)>,
+    /// Whether the argument can be left out.
+    pub optional: bool,
+    /// A valid argument, as written in a telecommand.
+    pub example: &'static str,
+}
+
+/// A telecommand and its parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [ParamInfo],
+    /// Whether the ground must send an `arm` naming this telecommand before it runs.
+    pub hazardous: bool,
+}
+
+impl CommandInfo {
+    /// The command as it is called, e.g. `get_events(count: u32)`.
+    pub const fn signature(&self) -> Signature<'_> {
+        Signature(self)
+    }
+}
+
+/// Writes a command's parameters and their types. Optional parameters end with `?`, and a
+/// choice lists its words: `set_mode(mode: nominal|safe)`.
+pub struct Signature<'a>(&'a CommandInfo);
+
+impl fmt::Display for Signature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0.name)?;
+        if self.0.params.is_empty() {
+            return Ok(());
+        }
+        f.write_char('(')?;
+        for (i, param) in self.0.params.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(param.name)?;
+            if param.optional {
+                f.write_char('?')?;
+            }
+            f.write_str(": ")?;
+            match param.arg_type {
+                ArgType::Choice(choices) => {
+                    for (j, choice) in choices.iter().enumerate() {
+                        if j > 0 {
+                            f.write_char('|')?;
+                        }
+                        f.write_str(choice)?;
+                    }
+                }
+                arg_type => f.write_str(arg_type.as_str())?,
+            }
+        }
+        f.write_char(')')
+    }
+}
+
+/// A telemetry point that the firmware registers. The firmware build fails if its points
+/// don't match this list (see `cts2_obc_logic::telemetry::matches_dictionary`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TelemetryInfo {
+    pub name: &'static str,
+    /// `u32`, `u64`, `i32`, `f32`, `bool` or `str`.
+    pub value_type: &'static str,
+    /// Units of the value, or empty if it has none.
+    pub units: &'static str,
+}
+
+/// An error code, from [`ParsedTelecommandErr::code`](crate::error::ParsedTelecommandErr::code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorInfo {
+    pub code: u8,
+    pub name: &'static str,
+    pub message: &'static str,
+}
+
+const fn param(name: &'static str, arg_type: ArgType, example: &'static str) -> ParamInfo {
+    ParamInfo {
+        name,
+        arg_type,
+        range: None,
+        optional: false,
+        example,
+    }
+}
+
+const fn ranged(
+    name: &'static str,
+    arg_type: ArgType,
+    range: (u64, u64),
+    example: &'static str,
+) -> ParamInfo {
+    ParamInfo {
+        range: Some(range),
+        ..param(name, arg_type, example)
+    }
+}
+
+const fn optional(name: &'static str, arg_type: ArgType, example: &'static str) -> ParamInfo {
+    ParamInfo {
+        optional: true,
+        ..param(name, arg_type, example)
+    }
+}
+
+const fn command(
+    name: &'static str,
+    description: &'static str,
+    params: &'static [ParamInfo],
+) -> CommandInfo {
+    CommandInfo {
+        name,
+        description,
+        params,
+        hazardous: false,
+    }
+}
+
+const fn hazardous(
+    name: &'static str,
+    description: &'static str,
+    params: &'static [ParamInfo],
+) -> CommandInfo {
+    CommandInfo {
+        hazardous: true,
+        ..command(name, description, params)
+    }
+}
+
+const TELEMETRY_NAME_RANGE: (u64, u64) = (1, MAX_TELEMETRY_NAME_LEN as u64);
+const FDIR_SLOT: ParamInfo = param("slot", ArgType::U8, "0");
+
+/// Every telecommand the OBC accepts.
+pub const COMMANDS: &[CommandInfo] = &[
+    command("hello_world", "Reply HELLO_WORLD, to check the link.", &[]),
+    command("get_sys_uptime", "Time since boot, in ms.", &[]),
+    command(
+        "demo_command_with_arguments",
+        "Echo one argument of each type. Also takes them as one JSON object.",
+        &[
+            param("arg_u32", ArgType::U32, "1"),
+            param("arg_u64", ArgType::U64, "2"),
+            param("arg_bool", ArgType::Bool, "true"),
+            param("arg_f32", ArgType::F32, "3.5"),
+            param("arg_f64", ArgType::F64, "4.5"),
+            optional("arg_nullable_u32", ArgType::U32, "null"),
+        ],
+    ),
+    command(
+        "get_config",
+        "Read a config variable.",
+        &[param("name", ArgType::ConfigName, "heartbeat_ms")],
+    ),
+    hazardous(
+        "set_config",
+        "Set a config variable.",
+        &[
+            param("name", ArgType::ConfigName, "heartbeat_ms"),
+            param("value", ArgType::ConfigValue, "u32(500)"),
+        ],
+    ),
+    hazardous(
+        "verify_staged_firmware",
+        "Check the staged firmware image, and mark it bootable if it passes.",
+        &[],
+    ),
+    command(
+        "mem_read",
+        "Dump memory, as a hex dump or raw bytes.",
+        &[
+            param("address", ArgType::U32, "0x20000000"),
+            param("length", ArgType::U32, "16"),
+            optional("format", ArgType::Choice(&["hex", "bin"]), "hex"),
+        ],
+    ),
+    hazardous(
+        "mem_write",
+        "Write bytes to memory.",
+        &[
+            param("address", ArgType::U32, "0x20000000"),
+            ranged(
+                "bytes",
+                ArgType::Bytes,
+                (1, MAX_MEM_WRITE_LEN as u64),
+                "deadbeef",
+            ),
+        ],
+    ),
+    command(
+        "mem_crc",
+        "CRC-32 of a memory range, up to 64 KiB.",
+        &[
+            param("address", ArgType::U32, "0x08000000"),
+            param("length", ArgType::U32, "1024"),
+        ],
+    ),
+    command(
+        "mem_list_regions",
+        "List the memory regions, with what the memory telecommands may do in each.",
+        &[],
+    ),
+    hazardous(
+        "mem_set_region",
+        "Set what the memory telecommands may do in the region that holds an address, up to its built-in access.",
+        &[
+            param("address", ArgType::U32, "0x20000000"),
+            param("access", ArgType::Choice(&["none", "ro", "rw"]), "ro"),
+        ],
+    ),
+    command(
+        "get_image_crc_status",
+        "Result of the last CRC check of the running image.",
+        &[],
+    ),
+    command(
+        "get_events",
+        "The most recent events in the event log.",
+        &[param("count", ArgType::U32, "10")],
+    ),
+    command("get_seu_stats", "Memory scrubbing error counts.", &[]),
+    command(
+        "get_telemetry",
+        "Read a telemetry point.",
+        &[ranged(
+            "name",
+            ArgType::TelemetryName,
+            TELEMETRY_NAME_RANGE,
+            "uptime_ms",
+        )],
+    ),
+    command(
+        "list_telemetry",
+        "List the telemetry points, with their types and units.",
+        &[],
+    ),
+    command(
+        "get_telemetry_many",
+        "Read several telemetry points.",
+        &[ranged(
+            "names",
+            ArgType::TelemetryNames,
+            (0, MAX_TELEMETRY_POINTS_PER_REQUEST as u64),
+            "[uptime_ms, mode]",
+        )],
+    ),
+    command(
+        "subscribe",
+        "Send a telemetry point periodically on this link.",
+        &[
+            ranged(
+                "name",
+                ArgType::TelemetryName,
+                TELEMETRY_NAME_RANGE,
+                "uptime_ms",
+            ),
+            param("period_ms", ArgType::U32, "1000"),
+        ],
+    ),
+    command(
+        "unsubscribe",
+        "Stop sending a telemetry point on this link.",
+        &[ranged(
+            "name",
+            ArgType::TelemetryName,
+            TELEMETRY_NAME_RANGE,
+            "uptime_ms",
+        )],
+    ),
+    command("list_subscriptions", "List the subscriptions.", &[]),
+    hazardous(
+        "set_mode",
+        "Change the operating mode.",
+        &[param("mode", ArgType::Choice(&["nominal", "safe"]), "safe")],
+    ),
+    command("fdir_list_rules", "List the FDIR rules.", &[]),
+    hazardous(
+        "fdir_set_rule",
+        "Add an FDIR rule, or replace the one in the slot.",
+        &[
+            FDIR_SLOT,
+            ranged(
+                "channel",
+                ArgType::TelemetryName,
+                TELEMETRY_NAME_RANGE,
+                "uart_rx_overflow_bytes",
+            ),
+            param("check", ArgType::LimitCheck, "above(100)"),
+            ranged("persistence", ArgType::U8, (1, u8::MAX as u64), "3"),
+            param("action", ArgType::FdirAction, "mode(safe)"),
+        ],
+    ),
+    command("fdir_enable_rule", "Enable an FDIR rule.", &[FDIR_SLOT]),
+    command("fdir_disable_rule", "Disable an FDIR rule.", &[FDIR_SLOT]),
+    command("fdir_delete_rule", "Delete an FDIR rule.", &[FDIR_SLOT]),
+    hazardous(
+        "tx_disable",
+        "Inhibit the radio transmitter until tx_enable, across reboots.",
+        &[],
+    ),
+    command("tx_enable", "Allow the radio transmitter again.", &[]),
+    hazardous(
+        "tx_disable_for",
+        "Inhibit the radio transmitter for a time, across reboots.",
+        &[ranged(
+            "duration_s",
+            ArgType::U32,
+            (1, u32::MAX as u64),
+            "3600",
+        )],
+    ),
+    command(
+        "get_command_dictionary",
+        "This dictionary, one JSON object per line.",
+        &[],
+    ),
+    command(
+        "get_cmd_history",
+        "The most recent telecommands, with their results and run times.",
+        &[param("count", ArgType::U32, "10")],
+    ),
+    command(
+        "get_cmd_stats",
+        "How many times each telecommand was received, and succeeded or failed.",
+        &[],
+    ),
+    command(
+        "arm",
+        "Allow the named hazardous telecommand to run once, within arm_window_s. The nonce must differ from the last arm's.",
+        &[
+            param("command", ArgType::CommandName, "set_config"),
+            param("nonce", ArgType::U32, "1"),
+        ],
+    ),
+    command("get_config_all", "Read every config variable.", &[]),
+    command(
+        "config_export",
+        "Every config variable as a blob with a CRC, for config_import.",
+        &[],
+    ),
+    hazardous(
+        "config_import",
+        "Set the config variables in a blob from config_export. Sets all of them, or none if the blob is invalid.",
+        &[ranged(
+            "blob",
+            ArgType::Bytes,
+            (1, MAX_CONFIG_BLOB_LEN as u64),
+            "01",
+        )],
+    ),
+    hazardous(
+        "config_reset",
+        "Reset a config variable to its default, or every one with `all`.",
+        &[param("name", ArgType::ConfigName, "heartbeat_ms")],
+    ),
+    command(
+        "config_diff",
+        "The config variables that differ from their defaults.",
+        &[],
+    ),
+    hazardous(
+        "set_config_tentative",
+        "Set a config variable, and put the old value back unless config_confirm comes within the timeout.",
+        &[
+            param("name", ArgType::ConfigName, "heartbeat_ms"),
+            param("value", ArgType::ConfigValue, "u32(500)"),
+            ranged("timeout_s", ArgType::U32, (1, u32::MAX as u64), "60"),
+        ],
+    ),
+    command(
+        "config_confirm",
+        "Keep the value from set_config_tentative.",
+        &[],
+    ),
+    command(
+        "get_config_audit",
+        "The most recent config changes (up to 32), with their old and new values and what made them.",
+        &[param("count", ArgType::U32, "10")],
+    ),
+];
+
+const fn telemetry(
+    name: &'static str,
+    value_type: &'static str,
+    units: &'static str,
+) -> TelemetryInfo {
+    TelemetryInfo {
+        name,
+        value_type,
+        units,
+    }
+}
+
+/// Every telemetry point the firmware registers, in registration order.
+pub const TELEMETRY: &[TelemetryInfo] = &[
+    telemetry("uptime_ms", "u64", "ms"),
+    telemetry("mode", "str", ""),
+    telemetry("boot_count", "u32", ""),
+    telemetry("fast_reset_count", "u32", ""),
+    telemetry("met_ms", "u64", "ms"),
+    telemetry("deployment_phase", "str", ""),
+    telemetry("antennas_deployed", "u32", ""),
+    telemetry("radio_tx_allowed", "bool", ""),
+    telemetry("radio_tx_bytes", "u32", "bytes"),
+    telemetry("radio_tx_blocked", "u32", ""),
+    telemetry("radio_resets", "u32", ""),
+    telemetry("radio_backup_frequency", "bool", ""),
+    telemetry("command_silence_ms", "u64", "ms"),
+    telemetry("tx_inhibit", "str", ""),
+    telemetry("uart_rx_bytes", "u32", "bytes"),
+    telemetry("uart_rx_overflow_bytes", "u32", "bytes"),
+    telemetry("uart_tx_bytes", "u32", "bytes"),
+    telemetry("commands_received", "u32", ""),
+    telemetry("commands_ok", "u32", ""),
+    telemetry("commands_failed", "u32", ""),
+    telemetry("event_count", "u32", ""),
+    telemetry("image_crc_status", "str", ""),
+    telemetry("image_crc_passes", "u32", ""),
+    telemetry("seu_flash_ecc_corrected", "u32", ""),
+    telemetry("seu_flash_ecc_detected", "u32", ""),
+    telemetry("seu_sram2_parity", "u32", ""),
+    telemetry("scrub_passes", "u32", ""),
+];
+
+const fn error(code: u8, name: &'static str, message: &'static str) -> ErrorInfo {
+    ErrorInfo {
+        code,
+        name,
+        message,
+    }
+}
+
+/// Every error code a telecommand can be rejected with.
+pub const ERRORS: &[ErrorInfo] = &[
+    error(1, "unknown_command", "Unknown telecommand"),
+    error(
+        2,
+        "deserialization_error",
+        "Failed to deserialize telecommand arguments",
+    ),
+    error(3, "missing_argument", "Missing required argument"),
+    error(4, "exceeded_argument_count", "Too many arguments provided"),
+    error(5, "invalid_argument", "Invalid argument value"),
+    error(6, "unexpected_keyword", "Unexpected keyword argument"),
+    error(7, "syntax", "Syntax error"),
+    error(8, "too_many_telecommands", "Too many telecommands in batch"),
+    error(
+        16,
+        "config_variable_not_found",
+        "Configuration variable not found",
+    ),
+    error(
+        17,
+        "config_parse_value_type_error",
+        "Cannot parse the type with the value string",
+    ),
+    error(
+        18,
+        "config_variable_not_this_type",
+        "Type mismatch for configuration variable (config variable is another type)",
+    ),
+    error(
+        19,
+        "config_variable_unknown_type",
+        "Unknown type for configuration variable",
+    ),
+];
+
+/// Look up a telecommand by name.
+pub fn find_command(name: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Look up a telemetry point by name.
+pub fn find_telemetry(name: &str) -> Option<&'static TelemetryInfo> {
+    TELEMETRY.iter().find(|point| point.name == name)
+}
+
+/// One line of the dictionary.
+#[derive(Debug, Clone, Copy)]
+pub enum Entry {
+    Command(&'static CommandInfo),
+    Config(ConfigVariableName),
+    Telemetry(&'static TelemetryInfo),
+    Error(&'static ErrorInfo),
+}
+
+/// Every entry of the dictionary: commands, then config variables, telemetry points and errors.
+pub fn entries() -> impl Iterator<Item = Entry> {
+    COMMANDS
+        .iter()
+        .map(Entry::Command)
+        .chain(ConfigVariableName::ALL.into_iter().map(Entry::Config))
+        .chain(TELEMETRY.iter().map(Entry::Telemetry))
+        .chain(ERRORS.iter().map(Entry::Error))
+}
+
+fn write_json_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+fn write_param(f: &mut fmt::Formatter<'_>, param: &ParamInfo) -> fmt::Result {
+    f.write_str("{\"name\":")?;
+    write_json_str(f, param.name)?;
+    write!(f, ",\"type\":\"{}\"", param.arg_type.as_str())?;
+    if let ArgType::Choice(choices) = param.arg_type {
+        f.write_str(",\"choices\":[")?;
+        for (i, choice) in choices.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            write_json_str(f, choice)?;
+        }
+        f.write_char(']')?;
+    }
+    if let Some((min, max)) = param.range {
+        write!(f, ",\"min\":{},\"max\":{}", min, max)?;
+    }
+    write!(f, ",\"optional\":{},\"example\":", param.optional)?;
+    write_json_str(f, param.example)?;
+    f.write_char('}')
+}
+
+/// Writes the entry as one JSON object, without a line ending.
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Command(command) => {
+                f.write_str("{\"kind\":\"command\",\"name\":")?;
+                write_json_str(f, command.name)?;
+                f.write_str(",\"description\":")?;
+                write_json_str(f, command.description)?;
+                write!(f, ",\"hazardous\":{},\"params\":[", command.hazardous)?;
+                for (i, param) in command.params.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_param(f, param)?;
+                }
+                f.write_str("]}")
+            }
+            Entry::Config(name) => {
+                let default = ConfigStore::new().get(*name);
+                f.write_str("{\"kind\":\"config\",\"name\":")?;
+                write_json_str(f, name.as_str())?;
+                write!(
+                    f,
+                    ",\"type\":\"{}\",\"default\":\"{}\"}}",
+                    default.type_name(),
+                    default
+                )
+            }
+            Entry::Telemetry(point) => {
+                f.write_str("{\"kind\":\"telemetry\",\"name\":")?;
+                write_json_str(f, point.name)?;
+                write!(f, ",\"type\":\"{}\",\"units\":", point.value_type)?;
+                write_json_str(f, point.units)?;
+                f.write_char('}')
+            }
+            Entry::Error(error) => {
+                write!(f, "{{\"kind\":\"error\",\"code\":{},\"name\":", error.code)?;
+                write_json_str(f, error.name)?;
+                f.write_str(",\"message\":")?;
+                write_json_str(f, error.message)?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigValue;
+    use crate::error::{
+        BatchError, ConfigError, ParsedTelecommandErr, SyntaxError, SyntaxErrorKind,
+    };
+    use crate::parse_telecommand;
+    use std::format;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    #[test]
+    fn test_examples_parse_positionally_and_by_keyword() {
+        for command in COMMANDS {
+            let positional: Vec<&str> = command.params.iter().map(|p| p.example).collect();
+            let text = format!("{}({})", command.name, positional.join(", "));
+            let parsed = parse_telecommand(&text);
+            assert_eq!(
+                parsed.as_ref().map(|t| t.name()),
+                Ok(command.name),
+                "{text}"
+            );
+
+            // Keyword arguments, in reverse order.
+            let keywords: Vec<String> = command
+                .params
+                .iter()
+                .rev()
+                .map(|p| format!("{}={}", p.name, p.example))
+                .collect();
+            let text = format!("{}({})", command.name, keywords.join(", "));
+            assert_eq!(parse_telecommand(&text), parsed, "{text}");
+
+            // Required parameters can't be left out, and optional ones can.
+            let required = command.params.iter().filter(|p| !p.optional).count();
+            let text = format!("{}({})", command.name, positional[..required].join(", "));
+            assert!(parse_telecommand(&text).is_ok(), "{text}");
+            if required > 0 {
+                let text = format!(
+                    "{}({})",
+                    command.name,
+                    positional[..required - 1].join(", ")
+                );
+                assert_eq!(
+                    parse_telecommand(&text),
+                    Err(ParsedTelecommandErr::MissingArgument((required - 1) as u8)),
+                    "{text}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ranges_are_enforced() {
+        assert!(parse_telecommand("tx_disable_for(0)").is_err());
+        assert!(parse_telecommand("fdir_set_rule(0, mode, above(1), 0, event)").is_err());
+        let too_long = "ab".repeat(MAX_MEM_WRITE_LEN + 1);
+        assert!(parse_telecommand(&format!("mem_write(0, {too_long})")).is_err());
+    }
+
+    #[test]
+    fn test_error_codes_match_errors() {
+        let errors = [
+            ParsedTelecommandErr::UnknownCommand,
+            ParsedTelecommandErr::MissingArgument(0),
+            ParsedTelecommandErr::ExceededArgumentCount,
+            ParsedTelecommandErr::InvalidArgument(0),
+            ParsedTelecommandErr::UnexpectedKeyword(0),
+            ParsedTelecommandErr::Syntax(SyntaxError {
+                offset: 0,
+                kind: SyntaxErrorKind::UnexpectedEnd,
+            }),
+            ParsedTelecommandErr::ConfigError(ConfigError::ConfigVariableNotFound),
+            ParsedTelecommandErr::ConfigError(ConfigError::ConfigParseValueTypeError),
+            ParsedTelecommandErr::ConfigError(ConfigError::ConfigVariableNotThisType),
+            ParsedTelecommandErr::ConfigError(ConfigError::ConfigVariableUnknownType),
+        ];
+        for e in errors {
+            let info = ERRORS.iter().find(|info| info.code == e.code()).unwrap();
+            let message = match &e {
+                ParsedTelecommandErr::ConfigError(e) => e.to_string(),
+                e => e.to_string(),
+            };
+            assert_eq!(info.message, message);
+        }
+        let e = BatchError::TooManyTelecommands;
+        let info = ERRORS.iter().find(|info| info.code == e.code()).unwrap();
+        assert_eq!(info.message, e.to_string());
+
+        let mut codes: Vec<u8> = ERRORS.iter().map(|info| info.code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), ERRORS.len());
+    }
+
+    #[test]
+    fn test_entries_are_json_lines() {
+        let lines: Vec<String> = entries().map(|entry| entry.to_string()).collect();
+        assert_eq!(
+            lines.len(),
+            COMMANDS.len() + ConfigVariableName::ALL.len() + TELEMETRY.len() + ERRORS.len()
+        );
+        for line in &lines {
+            assert!(line.len() <= MAX_ENTRY_LEN, "{line}");
+            assert!(!line.contains('\n'));
+        }
+
+        assert_eq!(
+            Entry::Command(find_command("mem_read").unwrap()).to_string(),
+            concat!(
+                r#"{"kind":"command","name":"mem_read","description":"Dump memory, as a hex dump or raw bytes.","hazardous":false,"params":["#,
+                r#"{"name":"address","type":"u32","optional":false,"example":"0x20000000"},"#,
+                r#"{"name":"length","type":"u32","optional":false,"example":"16"},"#,
+                r#"{"name":"format","type":"choice","choices":["hex","bin"],"optional":true,"example":"hex"}]}"#
+            )
+        );
+        assert_eq!(
+            Entry::Config(ConfigVariableName::HeartbeatMs).to_string(),
+            r#"{"kind":"config","name":"heartbeat_ms","type":"u32","default":"u32(1000)"}"#
+        );
+        assert_eq!(
+            Entry::Telemetry(find_telemetry("uptime_ms").unwrap()).to_string(),
+            r#"{"kind":"telemetry","name":"uptime_ms","type":"u64","units":"ms"}"#
+        );
+        assert_eq!(
+            Entry::Error(&ERRORS[0]).to_string(),
+            r#"{"kind":"error","code":1,"name":"unknown_command","message":"Unknown telecommand"}"#
+        );
+
+        // Config defaults are given as they are written in `set_config`.
+        for name in ConfigVariableName::ALL {
+            let default = ConfigStore::new().get(name);
+            assert_eq!(default.to_string().parse::<ConfigValue>(), Ok(default));
+        }
+    }
+
+    #[test]
+    fn test_signatures() {
+        let signature = |name| find_command(name).unwrap().signature().to_string();
+        assert_eq!(signature("hello_world"), "hello_world");
+        assert_eq!(signature("get_events"), "get_events(count: u32)");
+        assert_eq!(
+            signature("mem_read"),
+            "mem_read(address: u32, length: u32, format?: hex|bin)"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 pub type IndexMissing = u8;
+pub type IndexInvalid = u8;
 
 #[derive(Debug, Error, PartialEq)]
 pub enum ParsedTelecommandErr {
@@ -16,8 +17,69 @@ pub enum ParsedTelecommandErr {
     #[error("Too many arguments provided")]
     ExceededArgumentCount,
 
+    #[error("Invalid argument value")]
+    InvalidArgument(IndexInvalid),
+
+    #[error("Unexpected keyword argument")]
+    UnexpectedKeyword(IndexInvalid),
+
     #[error("Configuration error")]
     ConfigError(#[from] ConfigError),
+
+    #[error("Syntax error")]
+    Syntax(#[from] SyntaxError),
+}
+
+impl ParsedTelecommandErr {
+    /// Numeric code of the error, listed in [`dictionary::ERRORS`](crate::dictionary::ERRORS).
+    pub const fn code(&self) -> u8 {
+        match self {
+            ParsedTelecommandErr::UnknownCommand => 1,
+            ParsedTelecommandErr::DeserializationError(_) => 2,
+            ParsedTelecommandErr::MissingArgument(_) => 3,
+            ParsedTelecommandErr::ExceededArgumentCount => 4,
+            ParsedTelecommandErr::InvalidArgument(_) => 5,
+            ParsedTelecommandErr::UnexpectedKeyword(_) => 6,
+            ParsedTelecommandErr::Syntax(_) => 7,
+            ParsedTelecommandErr::ConfigError(e) => e.code(),
+        }
+    }
+}
+
+/// A telecommand that doesn't follow the grammar, and where it went wrong.
+#[derive(Debug, PartialEq, Copy, Clone, Error)]
+#[error("{kind} at offset {offset}")]
+pub struct SyntaxError {
+    /// Character offset in the telecommand.
+    pub offset: usize,
+    pub kind: SyntaxErrorKind,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone, Error)]
+pub enum SyntaxErrorKind {
+    #[error("unexpected character")]
+    UnexpectedCharacter,
+
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+
+    #[error("unterminated string")]
+    UnterminatedString,
+
+    #[error("invalid escape sequence")]
+    InvalidEscape,
+
+    #[error("mismatched bracket")]
+    MismatchedBracket,
+
+    #[error("brackets nested too deeply")]
+    NestingTooDeep,
+
+    #[error("empty argument")]
+    EmptyArgument,
+
+    #[error("positional argument after keyword argument")]
+    PositionalAfterKeyword,
 }
 
 // config operation errors
@@ -35,3 +97,39 @@ pub enum ConfigError {
     #[error("Unknown type for configuration variable")]
     ConfigVariableUnknownType,
 }
+
+impl ConfigError {
+    /// Numeric code of the error, listed in [`dictionary::ERRORS`](crate::dictionary::ERRORS).
+    pub const fn code(&self) -> u8 {
+        match self {
+            ConfigError::ConfigVariableNotFound => 16,
+            ConfigError::ConfigParseValueTypeError => 17,
+            ConfigError::ConfigVariableNotThisType => 18,
+            ConfigError::ConfigVariableUnknownType => 19,
+        }
+    }
+}
+
+/// A line of telecommands that was rejected. None of them ran.
+#[derive(Debug, PartialEq, Error)]
+pub enum BatchError {
+    #[error("Invalid telecommand in batch")]
+    InvalidTelecommand {
+        /// Position of the telecommand in the line, counting from 0.
+        index: usize,
+        error: ParsedTelecommandErr,
+    },
+
+    #[error("Too many telecommands in batch")]
+    TooManyTelecommands,
+}
+
+impl BatchError {
+    /// Numeric code of the error, listed in [`dictionary::ERRORS`](crate::dictionary::ERRORS).
+    pub const fn code(&self) -> u8 {
+        match self {
+            BatchError::InvalidTelecommand { error, .. } => error.code(),
+            BatchError::TooManyTelecommands => 8,
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Argument types for the FDIR (fault detection, isolation and recovery) rule telecommands.
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::TelemetryName;
+use crate::grammar::split_call;
+use crate::mode::OperatingMode;
+
+/// Longest telecommand that an FDIR rule can store and run.
+pub const MAX_STORED_COMMAND_LEN: usize = 64;
+
+/// A telecommand string, run when an FDIR rule trips.
+pub type StoredCommand = heapless::String<MAX_STORED_COMMAND_LEN>;
+
+/// The condition that counts as a limit violation.
+///
+/// Written as `above(100)`, `below(5)` or `outside(5..100)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitCheck {
+    Above(f64),
+    Below(f64),
+    /// Violated when the value is below `low` or above `high`.
+    Outside {
+        low: f64,
+        high: f64,
+    },
+}
+
+impl LimitCheck {
+    pub fn is_violated(&self, value: f64) -> bool {
+        match *self {
+            LimitCheck::Above(limit) => value > limit,
+            LimitCheck::Below(limit) => value < limit,
+            LimitCheck::Outside { low, high } => value < low || value > high,
+        }
+    }
+}
+
+/// Formats the check the same way it is parsed, e.g. `outside(5..100)`.
+impl fmt::Display for LimitCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitCheck::Above(limit) => write!(f, "above({})", limit),
+            LimitCheck::Below(limit) => write!(f, "below({})", limit),
+            LimitCheck::Outside { low, high } => write!(f, "outside({}..{})", low, high),
+        }
+    }
+}
+
+impl FromStr for LimitCheck {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, args) = split_call(s).map_err(|_| ())?;
+        let parse = |s: &str| s.trim_ascii().parse::<f64>().map_err(|_| ());
+
+        match kind {
+            "above" => Ok(LimitCheck::Above(parse(args)?)),
+            "below" => Ok(LimitCheck::Below(parse(args)?)),
+            "outside" => {
+                let (low, high) = args.split_once("..").ok_or(())?;
+                let (low, high) = (parse(low)?, parse(high)?);
+                if low > high {
+                    return Err(());
+                }
+                Ok(LimitCheck::Outside { low, high })
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+/// What to do when an FDIR rule trips. A trip is always logged as an event.
+///
+/// Written as `event`, `mode(safe)` or `command(<telecommand>)`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FdirAction {
+    /// Only log the event.
+    EventOnly,
+    EnterMode(OperatingMode),
+    RunCommand(StoredCommand),
+}
+
+/// Formats the action the same way it is parsed, e.g. `mode(safe)`.
+impl fmt::Display for FdirAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FdirAction::EventOnly => f.write_str("event"),
+            FdirAction::EnterMode(mode) => write!(f, "mode({})", mode.as_str()),
+            FdirAction::RunCommand(command) => write!(f, "command({})", command),
+        }
+    }
+}
+
+impl FromStr for FdirAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim_ascii() == "event" {
+            return Ok(FdirAction::EventOnly);
+        }
+
+        let (kind, args) = split_call(s).map_err(|_| ())?;
+        match kind {
+            "mode" => Ok(FdirAction::EnterMode(args.parse()?)),
+            "command" if !args.is_empty() => Ok(FdirAction::RunCommand(
+                StoredCommand::try_from(args).map_err(|_| ())?,
+            )),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Arguments of `fdir_set_rule(slot, channel, check, persistence, action)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FdirRuleArgs {
+    /// Index in the rule table. An existing rule in the slot is replaced.
+    pub slot: u8,
+    /// Name of the telemetry point to check.
+    pub channel: TelemetryName,
+    pub check: LimitCheck,
+    /// Number of consecutive violations before the rule trips. At least 1.
+    pub persistence: u8,
+    pub action: FdirAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_limit_check() {
+        assert_eq!("above(100)".parse(), Ok(LimitCheck::Above(100.0)));
+        assert_eq!("below(-2.5)".parse(), Ok(LimitCheck::Below(-2.5)));
+        assert_eq!(
+            "outside(5..100)".parse(),
+            Ok(LimitCheck::Outside {
+                low: 5.0,
+                high: 100.0
+            })
+        );
+        assert_eq!("outside(100..5)".parse::<LimitCheck>(), Err(()));
+        assert_eq!("above()".parse::<LimitCheck>(), Err(()));
+        assert_eq!("equals(3)".parse::<LimitCheck>(), Err(()));
+    }
+
+    #[test]
+    fn test_limit_check_violation() {
+        let check = LimitCheck::Outside {
+            low: 5.0,
+            high: 100.0,
+        };
+        assert!(check.is_violated(4.9));
+        assert!(!check.is_violated(5.0));
+        assert!(!check.is_violated(100.0));
+        assert!(check.is_violated(100.1));
+        assert!(LimitCheck::Above(0.0).is_violated(1.0));
+        assert!(!LimitCheck::Below(0.0).is_violated(0.0));
+    }
+
+    #[test]
+    fn test_parse_fdir_action() {
+        assert_eq!("event".parse(), Ok(FdirAction::EventOnly));
+        assert_eq!(
+            "mode(safe)".parse(),
+            Ok(FdirAction::EnterMode(OperatingMode::Safe))
+        );
+        assert_eq!(
+            "command(get_events(5))".parse(),
+            Ok(FdirAction::RunCommand("get_events(5)".try_into().unwrap()))
+        );
+        assert_eq!("command()".parse::<FdirAction>(), Err(()));
+        assert_eq!("mode(sleep)".parse::<FdirAction>(), Err(()));
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for s in ["above(100)", "below(-2.5)", "outside(5..100)"] {
+            assert_eq!(s.parse::<LimitCheck>().unwrap().to_string(), s);
+        }
+        for s in ["event", "mode(safe)", "command(get_events(5))"] {
+            assert_eq!(s.parse::<FdirAction>().unwrap().to_string(), s);
+        }
+    }
+}
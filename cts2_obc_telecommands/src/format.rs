@@ -0,0 +1,389 @@
+//! Canonical text of a telecommand: the text that `parse_telecommand` turns back into the same
+//! telecommand. Used to echo, store or forward a telecommand after it has been parsed.
+//!
+//! Arguments are positional. Addresses are written in hex, and other numbers in decimal.
+
+use core::fmt::{self, Write};
+
+use crate::Telecommand;
+use crate::config::ConfigVariableName;
+
+/// Longest canonical telecommand, the same as the longest one the OBC can receive.
+pub const MAX_TELECOMMAND_LEN: usize = 256;
+
+/// Canonical text of a telecommand (e.g. `mem_read(0x20000000, 16, hex)`).
+pub type TelecommandString = heapless::String<MAX_TELECOMMAND_LEN>;
+
+impl Telecommand {
+    /// Name of the telecommand, e.g. `mem_read`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Telecommand::hello_world => "hello_world",
+            Telecommand::get_sys_uptime => "get_sys_uptime",
+            Telecommand::demo_command_with_arguments(_) => "demo_command_with_arguments",
+            Telecommand::get_config(_) => "get_config",
+            Telecommand::set_config(..) => "set_config",
+            Telecommand::verify_staged_firmware => "verify_staged_firmware",
+            Telecommand::mem_read(..) => "mem_read",
+            Telecommand::mem_write(..) => "mem_write",
+            Telecommand::mem_crc(..) => "mem_crc",
+            Telecommand::mem_list_regions => "mem_list_regions",
+            Telecommand::mem_set_region(..) => "mem_set_region",
+            Telecommand::get_image_crc_status => "get_image_crc_status",
+            Telecommand::get_events(_) => "get_events",
+            Telecommand::get_seu_stats => "get_seu_stats",
+            Telecommand::get_telemetry(_) => "get_telemetry",
+            Telecommand::list_telemetry => "list_telemetry",
+            Telecommand::get_telemetry_many(_) => "get_telemetry_many",
+            Telecommand::subscribe(..) => "subscribe",
+            Telecommand::unsubscribe(_) => "unsubscribe",
+            Telecommand::list_subscriptions => "list_subscriptions",
+            Telecommand::set_mode(_) => "set_mode",
+            Telecommand::fdir_list_rules => "fdir_list_rules",
+            Telecommand::fdir_set_rule(_) => "fdir_set_rule",
+            Telecommand::fdir_enable_rule(_) => "fdir_enable_rule",
+            Telecommand::fdir_disable_rule(_) => "fdir_disable_rule",
+            Telecommand::fdir_delete_rule(_) => "fdir_delete_rule",
+            Telecommand::tx_disable => "tx_disable",
+            Telecommand::tx_enable => "tx_enable",
+            Telecommand::tx_disable_for(_) => "tx_disable_for",
+            Telecommand::get_command_dictionary => "get_command_dictionary",
+            Telecommand::get_cmd_history(_) => "get_cmd_history",
+            Telecommand::get_cmd_stats => "get_cmd_stats",
+            Telecommand::arm(..) => "arm",
+            Telecommand::get_config_all => "get_config_all",
+            Telecommand::config_export => "config_export",
+            Telecommand::config_import(_) => "config_import",
+            Telecommand::config_reset(_) => "config_reset",
+            Telecommand::config_diff => "config_diff",
+            Telecommand::set_config_tentative(..) => "set_config_tentative",
+            Telecommand::config_confirm => "config_confirm",
+            Telecommand::get_config_audit(_) => "get_config_audit",
+        }
+    }
+
+    /// The canonical text. Fails if it is longer than [`MAX_TELECOMMAND_LEN`].
+    pub fn to_canonical(&self) -> Result<TelecommandString, fmt::Error> {
+        let mut s = TelecommandString::new();
+        write!(s, "{}", self)?;
+        Ok(s)
+    }
+}
+
+/// Writes the canonical text, e.g. `set_config(heartbeat_ms, u32(500))`.
+impl fmt::Display for Telecommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.name())?;
+        match self {
+            Telecommand::hello_world
+            | Telecommand::get_sys_uptime
+            | Telecommand::verify_staged_firmware
+            | Telecommand::mem_list_regions
+            | Telecommand::get_image_crc_status
+            | Telecommand::get_seu_stats
+            | Telecommand::list_telemetry
+            | Telecommand::list_subscriptions
+            | Telecommand::fdir_list_rules
+            | Telecommand::tx_disable
+            | Telecommand::tx_enable
+            | Telecommand::get_command_dictionary
+            | Telecommand::get_cmd_stats
+            | Telecommand::get_config_all
+            | Telecommand::config_export
+            | Telecommand::config_diff
+            | Telecommand::config_confirm => {}
+            Telecommand::demo_command_with_arguments(args) => {
+                write!(
+                    f,
+                    "{}, {}, {}, {}, {}",
+                    args.arg_u32, args.arg_u64, args.arg_bool, args.arg_f32, args.arg_f64
+                )?;
+                if let Some(value) = args.arg_nullable_u32 {
+                    write!(f, ", {}", value)?;
+                }
+            }
+            Telecommand::get_config(name) => f.write_str(name.as_str())?,
+            Telecommand::set_config(name, value) => write!(f, "{}, {}", name.as_str(), value)?,
+            Telecommand::set_config_tentative(name, value, timeout_s) => {
+                write!(f, "{}, {}, {}", name.as_str(), value, timeout_s)?
+            }
+            Telecommand::mem_read(address, length, format) => {
+                write!(f, "{:#010x}, {}, {}", address, length, format.as_str())?
+            }
+            Telecommand::mem_write(address, bytes) => {
+                write!(f, "{:#010x}, ", address)?;
+                for byte in bytes {
+                    write!(f, "{:02x}", byte)?;
+                }
+            }
+            Telecommand::config_import(blob) => {
+                for byte in blob {
+                    write!(f, "{:02x}", byte)?;
+                }
+            }
+            Telecommand::config_reset(name) => {
+                f.write_str(name.as_ref().map_or("all", ConfigVariableName::as_str))?
+            }
+            Telecommand::mem_crc(address, length) => write!(f, "{:#010x}, {}", address, length)?,
+            Telecommand::mem_set_region(address, access) => {
+                write!(f, "{:#010x}, {}", address, access.as_str())?
+            }
+            Telecommand::arm(command, nonce) => write!(f, "{}, {}", command.name, nonce)?,
+            Telecommand::get_events(count)
+            | Telecommand::get_cmd_history(count)
+            | Telecommand::get_config_audit(count) => write!(f, "{}", count)?,
+            Telecommand::get_telemetry(name) | Telecommand::unsubscribe(name) => {
+                f.write_str(name)?
+            }
+            Telecommand::get_telemetry_many(names) => {
+                f.write_str("[")?;
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(name)?;
+                }
+                f.write_str("]")?;
+            }
+            Telecommand::subscribe(name, period_ms) => write!(f, "{}, {}", name, period_ms)?,
+            Telecommand::set_mode(mode) => f.write_str(mode.as_str())?,
+            Telecommand::fdir_set_rule(rule) => write!(
+                f,
+                "{}, {}, {}, {}, {}",
+                rule.slot, rule.channel, rule.check, rule.persistence, rule.action
+            )?,
+            Telecommand::fdir_enable_rule(slot)
+            | Telecommand::fdir_disable_rule(slot)
+            | Telecommand::fdir_delete_rule(slot) => write!(f, "{}", slot)?,
+            Telecommand::tx_disable_for(duration_s) => write!(f, "{}", duration_s)?,
+        }
+        f.write_str(")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigValue;
+    use crate::dictionary::{COMMANDS, CommandInfo};
+    use crate::fdir::{FdirAction, FdirRuleArgs, LimitCheck};
+    use crate::mode::OperatingMode;
+    use crate::{
+        ConfigBlob, DemoCommandWithArgumentsArgs, MAX_CONFIG_BLOB_LEN, MAX_MEM_WRITE_LEN,
+        MAX_TELEMETRY_POINTS_PER_REQUEST, MemDumpFormat, MemWriteBytes, MemoryAccess,
+        TelemetryName, TelemetryNames, parse_telecommand,
+    };
+    use proptest::prelude::*;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_canonical_text() {
+        let cases = [
+            (Telecommand::hello_world, "hello_world()"),
+            (
+                Telecommand::set_config(ConfigVariableName::HeartbeatMs, ConfigValue::U32(500)),
+                "set_config(heartbeat_ms, u32(500))",
+            ),
+            (
+                Telecommand::mem_read(0x2000_0000, 16, MemDumpFormat::Binary),
+                "mem_read(0x20000000, 16, bin)",
+            ),
+            (
+                Telecommand::mem_write(0x100, MemWriteBytes::from_slice(&[0xde, 0x0d]).unwrap()),
+                "mem_write(0x00000100, de0d)",
+            ),
+        ];
+        for (telecommand, text) in cases {
+            assert_eq!(telecommand.to_canonical().unwrap(), text);
+        }
+    }
+
+    fn telemetry_name() -> impl Strategy<Value = TelemetryName> {
+        "[a-z0-9_]{1,32}".prop_map(|s| s.as_str().try_into().unwrap())
+    }
+
+    /// Names short enough that a full `get_telemetry_many` fits in a telecommand.
+    fn short_telemetry_name() -> impl Strategy<Value = TelemetryName> {
+        "[a-z0-9_]{1,12}".prop_map(|s| s.as_str().try_into().unwrap())
+    }
+
+    /// Finite floats, small enough that their text fits in a telecommand.
+    fn finite_f64() -> impl Strategy<Value = f64> {
+        -1e9..1e9
+    }
+
+    fn finite_f32() -> impl Strategy<Value = f32> {
+        prop::num::f32::NORMAL | prop::num::f32::ZERO
+    }
+
+    fn config_value() -> impl Strategy<Value = ConfigValue> {
+        prop_oneof![
+            any::<u32>().prop_map(ConfigValue::U32),
+            any::<bool>().prop_map(ConfigValue::Bool),
+            finite_f32().prop_map(ConfigValue::F32),
+            any::<i32>().prop_map(ConfigValue::I32),
+            any::<u8>().prop_map(ConfigValue::U8),
+        ]
+    }
+
+    fn config_name() -> impl Strategy<Value = ConfigVariableName> {
+        prop::sample::select(&ConfigVariableName::ALL[..])
+    }
+
+    fn hazardous_command() -> impl Strategy<Value = &'static CommandInfo> {
+        let hazardous: Vec<&'static CommandInfo> = COMMANDS
+            .iter()
+            .filter(|command| command.hazardous)
+            .collect();
+        prop::sample::select(hazardous)
+    }
+
+    fn mode() -> impl Strategy<Value = OperatingMode> {
+        prop_oneof![Just(OperatingMode::Nominal), Just(OperatingMode::Safe)]
+    }
+
+    fn limit_check() -> impl Strategy<Value = LimitCheck> {
+        prop_oneof![
+            finite_f64().prop_map(LimitCheck::Above),
+            finite_f64().prop_map(LimitCheck::Below),
+            (finite_f64(), finite_f64()).prop_map(|(a, b)| LimitCheck::Outside {
+                low: a.min(b),
+                high: a.max(b),
+            }),
+        ]
+    }
+
+    fn fdir_action() -> impl Strategy<Value = FdirAction> {
+        prop_oneof![
+            Just(FdirAction::EventOnly),
+            mode().prop_map(FdirAction::EnterMode),
+            (telemetry_name(), any::<u32>()).prop_map(|(name, period_ms)| {
+                let command = Telecommand::subscribe(name, period_ms).to_string();
+                FdirAction::RunCommand(command.as_str().try_into().unwrap())
+            }),
+        ]
+    }
+
+    fn telecommand() -> impl Strategy<Value = Telecommand> {
+        let demo = (
+            any::<u32>(),
+            any::<u64>(),
+            any::<bool>(),
+            finite_f32(),
+            finite_f64(),
+            any::<Option<u32>>(),
+        )
+            .prop_map(|(a, b, c, d, e, g)| {
+                Telecommand::demo_command_with_arguments(DemoCommandWithArgumentsArgs {
+                    arg_u32: a,
+                    arg_u64: b,
+                    arg_bool: c,
+                    arg_f32: d,
+                    arg_f64: e,
+                    arg_nullable_u32: g,
+                })
+            });
+        let mem_write = (
+            any::<u32>(),
+            prop::collection::vec(any::<u8>(), 1..=MAX_MEM_WRITE_LEN),
+        )
+            .prop_map(|(address, bytes)| {
+                Telecommand::mem_write(address, MemWriteBytes::from_slice(&bytes).unwrap())
+            });
+        let telemetry_many =
+            prop::collection::vec(short_telemetry_name(), 0..=MAX_TELEMETRY_POINTS_PER_REQUEST)
+                .prop_map(|names| {
+                    Telecommand::get_telemetry_many(TelemetryNames::from_iter(names))
+                });
+        let fdir_set_rule = (
+            any::<u8>(),
+            telemetry_name(),
+            limit_check(),
+            1..=u8::MAX,
+            fdir_action(),
+        )
+            .prop_map(|(slot, channel, check, persistence, action)| {
+                Telecommand::fdir_set_rule(FdirRuleArgs {
+                    slot,
+                    channel,
+                    check,
+                    persistence,
+                    action,
+                })
+            });
+
+        prop_oneof![
+            Just(Telecommand::hello_world),
+            Just(Telecommand::get_sys_uptime),
+            demo,
+            config_name().prop_map(Telecommand::get_config),
+            (config_name(), config_value()).prop_map(|(n, v)| Telecommand::set_config(n, v)),
+            Just(Telecommand::verify_staged_firmware),
+            (
+                any::<u32>(),
+                any::<u32>(),
+                prop_oneof![Just(MemDumpFormat::Hex), Just(MemDumpFormat::Binary)]
+            )
+                .prop_map(|(a, l, f)| Telecommand::mem_read(a, l, f)),
+            mem_write,
+            (any::<u32>(), any::<u32>()).prop_map(|(a, l)| Telecommand::mem_crc(a, l)),
+            Just(Telecommand::mem_list_regions),
+            (
+                any::<u32>(),
+                prop_oneof![
+                    Just(MemoryAccess::NoAccess),
+                    Just(MemoryAccess::ReadOnly),
+                    Just(MemoryAccess::ReadWrite)
+                ]
+            )
+                .prop_map(|(a, access)| Telecommand::mem_set_region(a, access)),
+            Just(Telecommand::get_image_crc_status),
+            any::<u32>().prop_map(Telecommand::get_events),
+            Just(Telecommand::get_seu_stats),
+            telemetry_name().prop_map(Telecommand::get_telemetry),
+            Just(Telecommand::list_telemetry),
+            telemetry_many,
+            (telemetry_name(), any::<u32>()).prop_map(|(n, p)| Telecommand::subscribe(n, p)),
+            telemetry_name().prop_map(Telecommand::unsubscribe),
+            Just(Telecommand::list_subscriptions),
+            mode().prop_map(Telecommand::set_mode),
+            Just(Telecommand::fdir_list_rules),
+            fdir_set_rule,
+            any::<u8>().prop_map(Telecommand::fdir_enable_rule),
+            any::<u8>().prop_map(Telecommand::fdir_disable_rule),
+            any::<u8>().prop_map(Telecommand::fdir_delete_rule),
+            Just(Telecommand::tx_disable),
+            Just(Telecommand::tx_enable),
+            (1..=u32::MAX).prop_map(Telecommand::tx_disable_for),
+            Just(Telecommand::get_command_dictionary),
+            any::<u32>().prop_map(Telecommand::get_cmd_history),
+            Just(Telecommand::get_cmd_stats),
+            (hazardous_command(), any::<u32>()).prop_map(|(c, n)| Telecommand::arm(c, n)),
+            Just(Telecommand::get_config_all),
+            Just(Telecommand::config_export),
+            prop::collection::vec(any::<u8>(), 1..=MAX_CONFIG_BLOB_LEN).prop_map(|blob| {
+                Telecommand::config_import(ConfigBlob::from_slice(&blob).unwrap())
+            }),
+            prop::option::of(config_name()).prop_map(Telecommand::config_reset),
+            Just(Telecommand::config_diff),
+            (config_name(), config_value(), 1..=u32::MAX)
+                .prop_map(|(n, v, t)| Telecommand::set_config_tentative(n, v, t)),
+            Just(Telecommand::config_confirm),
+            any::<u32>().prop_map(Telecommand::get_config_audit),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_canonical_text_round_trips(telecommand in telecommand()) {
+            let text = telecommand.to_canonical().unwrap();
+            prop_assert_eq!(parse_telecommand(&text), Ok(telecommand));
+        }
+
+        #[test]
+        fn test_every_telecommand_is_in_dictionary(telecommand in telecommand()) {
+            prop_assert!(crate::dictionary::find_command(telecommand.name()).is_some());
+        }
+    }
+}
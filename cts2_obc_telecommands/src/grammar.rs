@@ -0,0 +1,663 @@
+//! Telecommand grammar.
+//!
+//! A telecommand is `name`, `name()` or `name(arg, arg, ...)`. An argument is anything up to
+//! the next top-level comma: brackets (`()`, `[]`, `{}`) nest, and commas inside them or inside
+//! quoted strings don't split arguments. So all of these are single arguments:
+//!
+//! - Numbers: `42`, `0x2A`, `0b101010`.
+//! - Quoted strings, with escapes: `"a \"b\", c"`.
+//! - Lists and byte arrays: `[uptime_ms, mode]`, `[0xde, 0xad]`, or bare hex `dead`.
+//! - JSON objects: `{"a": [1, 2]}`.
+//! - Calls: `above(5)`, `command(subscribe(uptime_ms, 1000))`.
+//!
+//! Arguments can also be given by name, as `key=value`, after any positional ones:
+//! `mem_read(0x20000000, length=16)`. [`Params`] matches them to the telecommand's parameters.
+//!
+//! The whole command is checked before any argument is interpreted. Syntax errors report the
+//! character offset where parsing failed. Parsing works on bytes, and converts the offset at
+//! the end.
+
+use crate::error::{
+    IndexInvalid, IndexMissing, ParsedTelecommandErr, SyntaxError, SyntaxErrorKind,
+};
+use crate::shared::{parse_hex_bytes, parse_u32};
+
+/// Deepest nesting of brackets accepted.
+const MAX_NESTING: usize = 16;
+
+fn syntax_error(offset: usize, kind: SyntaxErrorKind) -> SyntaxError {
+    SyntaxError { offset, kind }
+}
+
+/// Number of characters in `text` before the byte offset `offset`.
+pub(crate) fn char_offset(text: &str, offset: usize) -> usize {
+    text.char_indices().take_while(|(i, _)| *i < offset).count()
+}
+
+fn skip_whitespace(s: &[u8], mut pos: usize) -> usize {
+    while s.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Scan a quoted string starting at its opening quote. Returns the position after the closing
+/// quote.
+fn scan_string(s: &[u8], start: usize) -> Result<usize, SyntaxError> {
+    let mut pos = start + 1;
+    loop {
+        match s.get(pos) {
+            None => return Err(syntax_error(start, SyntaxErrorKind::UnterminatedString)),
+            Some(b'"') => return Ok(pos + 1),
+            Some(b'\\') => match s.get(pos + 1) {
+                Some(
+                    b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' | b'0' | b'x' | b'u',
+                ) => pos += 2,
+                Some(_) => return Err(syntax_error(pos, SyntaxErrorKind::InvalidEscape)),
+                None => return Err(syntax_error(start, SyntaxErrorKind::UnterminatedString)),
+            },
+            Some(_) => pos += 1,
+        }
+    }
+}
+
+/// Scan one value starting at `pos`. Returns the position of the top-level `,` or closing
+/// bracket that ends it, or the end of the input.
+fn scan_value(s: &[u8], mut pos: usize) -> Result<usize, SyntaxError> {
+    let mut closers = heapless::Vec::<u8, MAX_NESTING>::new();
+    loop {
+        match s.get(pos) {
+            None if closers.is_empty() => return Ok(pos),
+            None => return Err(syntax_error(pos, SyntaxErrorKind::UnexpectedEnd)),
+            Some(b'"') => pos = scan_string(s, pos)?,
+            Some(&open @ (b'(' | b'[' | b'{')) => {
+                let closer = match open {
+                    b'(' => b')',
+                    b'[' => b']',
+                    _ => b'}',
+                };
+                closers
+                    .push(closer)
+                    .map_err(|_| syntax_error(pos, SyntaxErrorKind::NestingTooDeep))?;
+                pos += 1;
+            }
+            Some(&close @ (b')' | b']' | b'}')) => {
+                if closers.is_empty() {
+                    return Ok(pos);
+                }
+                if closers.pop() != Some(close) {
+                    return Err(syntax_error(pos, SyntaxErrorKind::MismatchedBracket));
+                }
+                pos += 1;
+            }
+            Some(b',') if closers.is_empty() => return Ok(pos),
+            Some(_) => pos += 1,
+        }
+    }
+}
+
+/// If the argument in `s[start..end]` is `key=value`, return the end of the key and the start
+/// of the value.
+fn split_keyword(s: &[u8], start: usize, end: usize) -> Option<(usize, usize)> {
+    if !s
+        .get(start)
+        .is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_')
+    {
+        return None;
+    }
+    let mut pos = start;
+    while pos < end && (s[pos].is_ascii_alphanumeric() || s[pos] == b'_') {
+        pos += 1;
+    }
+    let key_end = pos;
+
+    pos = skip_whitespace(s, pos);
+    if pos < end && s[pos] == b'=' && s.get(pos + 1) != Some(&b'=') {
+        Some((key_end, skip_whitespace(s, pos + 1).min(end)))
+    } else {
+        None
+    }
+}
+
+/// Check the arguments of a telecommand, starting just after the opening bracket. Returns the
+/// position after `closer`.
+fn scan_list(s: &[u8], pos: usize, closer: u8) -> Result<usize, SyntaxError> {
+    let mut pos = skip_whitespace(s, pos);
+    if s.get(pos) == Some(&closer) {
+        return Ok(pos + 1);
+    }
+
+    let mut seen_keyword = false;
+    loop {
+        let start = skip_whitespace(s, pos);
+        pos = scan_value(s, start)?;
+        if start == pos {
+            return Err(syntax_error(start, SyntaxErrorKind::EmptyArgument));
+        }
+        match split_keyword(s, start, pos) {
+            Some((_, value_start)) if value_start == pos => {
+                return Err(syntax_error(value_start, SyntaxErrorKind::EmptyArgument));
+            }
+            Some(_) => seen_keyword = true,
+            None if seen_keyword => {
+                return Err(syntax_error(start, SyntaxErrorKind::PositionalAfterKeyword));
+            }
+            None => {}
+        }
+        match s.get(pos) {
+            Some(b',') => pos += 1,
+            Some(&b) if b == closer => return Ok(pos + 1),
+            Some(_) => return Err(syntax_error(pos, SyntaxErrorKind::MismatchedBracket)),
+            None => return Err(syntax_error(pos, SyntaxErrorKind::UnexpectedEnd)),
+        }
+    }
+}
+
+/// Split a telecommand into its name and its arguments, checking the syntax of the whole
+/// input. The name is not checked against the known telecommands.
+pub fn parse_command(input: &str) -> Result<(&str, Args<'_>), SyntaxError> {
+    scan_command(input).map_err(|e| SyntaxError {
+        offset: char_offset(input, e.offset),
+        ..e
+    })
+}
+
+/// Like `parse_command`, with byte offsets.
+fn scan_command(input: &str) -> Result<(&str, Args<'_>), SyntaxError> {
+    let s = input.as_bytes();
+    let name_start = skip_whitespace(s, 0);
+    let mut pos = name_start;
+    while s
+        .get(pos)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+    {
+        pos += 1;
+    }
+    let name = &input[name_start..pos];
+
+    pos = skip_whitespace(s, pos);
+    let args = match s.get(pos) {
+        None => Args::new("", pos, true),
+        Some(b'(') => {
+            let end = scan_list(s, pos + 1, b')')?;
+            let args = Args::new(&input[pos + 1..end - 1], pos + 1, true);
+            pos = end;
+            args
+        }
+        Some(_) => return Err(syntax_error(pos, SyntaxErrorKind::UnexpectedCharacter)),
+    };
+
+    pos = skip_whitespace(s, pos);
+    if pos < s.len() {
+        return Err(syntax_error(pos, SyntaxErrorKind::UnexpectedCharacter));
+    }
+    Ok((name, args))
+}
+
+/// Split a call such as `above(5)` into its name and the raw text of its arguments (`5`).
+pub fn split_call(input: &str) -> Result<(&str, &str), SyntaxError> {
+    let (name, args) = parse_command(input)?;
+    Ok((name, args.text.trim_ascii()))
+}
+
+/// Position of the first `target` byte from `pos` that is outside quoted strings, and outside
+/// brackets unless `in_brackets`. Brackets and strings aren't checked here: an unterminated
+/// string runs to the end of the input, and the telecommand's own parse reports it.
+fn find_unquoted(s: &[u8], mut pos: usize, target: u8, in_brackets: bool) -> Option<usize> {
+    let mut in_string = false;
+    let mut depth = 0usize;
+    while let Some(&b) = s.get(pos) {
+        if in_string {
+            match b {
+                b'\\' => pos += 1,
+                b'"' => in_string = false,
+                _ => {}
+            }
+        } else if b == target && (depth == 0 || in_brackets) {
+            return Some(pos);
+        } else {
+            match b {
+                b'"' => in_string = true,
+                b'(' | b'[' | b'{' => depth += 1,
+                b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        pos += 1;
+    }
+    None
+}
+
+/// Remove a `#` comment, which runs to the end of the line. A `#` in a quoted string doesn't
+/// start a comment.
+pub fn strip_comment(line: &str) -> &str {
+    match find_unquoted(line.as_bytes(), 0, b'#', true) {
+        Some(pos) => &line[..pos],
+        None => line,
+    }
+}
+
+/// Split a line into telecommands at each `;` outside brackets and quoted strings.
+pub fn split_batch(line: &str) -> Batch<'_> {
+    Batch { line, pos: 0 }
+}
+
+/// Telecommands of a line, each with its offset in the line. Blank ones, such as after a
+/// trailing `;`, are skipped.
+#[derive(Debug, Clone)]
+pub struct Batch<'a> {
+    line: &'a str,
+    pos: usize,
+}
+
+impl<'a> Iterator for Batch<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        while self.pos <= self.line.len() {
+            let start = self.pos;
+            let end =
+                find_unquoted(self.line.as_bytes(), start, b';', false).unwrap_or(self.line.len());
+            self.pos = end + 1;
+
+            let command = &self.line[start..end];
+            if !command.trim_ascii().is_empty() {
+                return Some((start, command));
+            }
+        }
+        None
+    }
+}
+
+/// Arguments of a telecommand, or elements of a list, taken one at a time.
+#[derive(Debug, Clone)]
+pub struct Args<'a> {
+    /// Text between the brackets, already checked by `scan_list`.
+    text: &'a str,
+    /// Offset of `text` in the whole telecommand.
+    offset: usize,
+    /// Whether `key=value` arguments are recognised. Only telecommand arguments have names,
+    /// not list elements.
+    keywords: bool,
+    pos: usize,
+    index: u8,
+    /// Whether the previous argument ended with a comma, so another one follows even if empty.
+    after_comma: bool,
+}
+
+impl<'a> Args<'a> {
+    fn new(text: &'a str, offset: usize, keywords: bool) -> Self {
+        Self {
+            text,
+            offset,
+            keywords,
+            pos: 0,
+            index: 0,
+            after_comma: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Args<'a> {
+    type Item = Arg<'a>;
+
+    fn next(&mut self) -> Option<Arg<'a>> {
+        let s = self.text.as_bytes();
+        let start = skip_whitespace(s, self.pos);
+        if start >= s.len() && !self.after_comma {
+            return None;
+        }
+        // The brackets were checked when the `Args` were made, so this cannot fail. Empty
+        // arguments are only rejected at the top level, so an empty list element is returned
+        // for the caller to reject.
+        let end = scan_value(s, start).ok()?;
+        self.pos = end + 1;
+        self.after_comma = s.get(end) == Some(&b',');
+
+        let (key, value_start) = match split_keyword(s, start, end).filter(|_| self.keywords) {
+            Some((key_end, value_start)) => (Some(&self.text[start..key_end]), value_start),
+            None => (None, start),
+        };
+        let arg = Arg {
+            key,
+            text: self.text[value_start..end].trim_ascii_end(),
+            offset: self.offset + value_start,
+            index: self.index,
+        };
+        self.index = self.index.saturating_add(1);
+        Some(arg)
+    }
+}
+
+/// Binds the arguments of a telecommand to its parameters, in order. Each parameter takes the
+/// next positional argument if there is one, and otherwise the keyword argument with its name.
+#[derive(Debug, Clone)]
+pub struct Params<'a> {
+    args: Args<'a>,
+    /// Index of the next parameter.
+    param: u8,
+    /// Keyword arguments already bound, by position after the last positional argument.
+    used_keywords: u32,
+}
+
+impl<'a> Params<'a> {
+    pub fn new(args: Args<'a>) -> Self {
+        Self {
+            args,
+            param: 0,
+            used_keywords: 0,
+        }
+    }
+
+    /// Index of the next parameter.
+    pub fn index(&self) -> IndexMissing {
+        self.param
+    }
+
+    /// Take the argument for the next parameter, if one was given.
+    pub fn optional(&mut self, name: &str) -> Option<Arg<'a>> {
+        let index = self.param;
+        self.param = self.param.saturating_add(1);
+
+        let mut rest = self.args.clone();
+        match rest.next() {
+            Some(arg) if arg.key.is_none() => {
+                self.args = rest;
+                return Some(arg);
+            }
+            _ => {}
+        }
+
+        // Only keyword arguments are left.
+        for (position, arg) in self.args.clone().enumerate().take(u32::BITS as usize) {
+            let bit = 1 << position;
+            if arg.key == Some(name) && self.used_keywords & bit == 0 {
+                self.used_keywords |= bit;
+                // Report errors against the parameter, wherever the argument was written.
+                return Some(Arg { index, ..arg });
+            }
+        }
+        None
+    }
+
+    /// Take the argument for the next parameter, or report it as missing.
+    pub fn required(&mut self, name: &str) -> Result<Arg<'a>, ParsedTelecommandErr> {
+        let index = self.param;
+        self.optional(name)
+            .ok_or(ParsedTelecommandErr::MissingArgument(index))
+    }
+
+    /// Check that every argument has been bound to a parameter.
+    pub fn finish(self) -> Result<(), ParsedTelecommandErr> {
+        for (position, arg) in self.args.enumerate() {
+            if arg.key.is_none() {
+                return Err(ParsedTelecommandErr::ExceededArgumentCount);
+            }
+            let used = position < u32::BITS as usize && self.used_keywords & (1 << position) != 0;
+            if !used {
+                return Err(ParsedTelecommandErr::UnexpectedKeyword(arg.index));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One argument, as written. Its methods interpret it as a particular type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Arg<'a> {
+    /// Name of a keyword argument.
+    key: Option<&'a str>,
+    /// Value of the argument.
+    text: &'a str,
+    offset: usize,
+    index: IndexInvalid,
+}
+
+impl<'a> Arg<'a> {
+    pub fn key(&self) -> Option<&'a str> {
+        self.key
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Offset of the argument's value in the whole telecommand.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn invalid(&self) -> ParsedTelecommandErr {
+        ParsedTelecommandErr::InvalidArgument(self.index)
+    }
+
+    /// A decimal, `0x` hex or `0b` binary `u32`.
+    pub fn u32(&self) -> Result<u32, ParsedTelecommandErr> {
+        parse_u32(self.text).ok_or(self.invalid())
+    }
+
+    pub fn u8(&self) -> Result<u8, ParsedTelecommandErr> {
+        u8::try_from(self.u32()?).map_err(|_| self.invalid())
+    }
+
+    /// A quoted string with its escapes resolved, or a bare word as written.
+    pub fn string<const N: usize>(&self) -> Result<heapless::String<N>, ParsedTelecommandErr> {
+        match self
+            .text
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+        {
+            Some(quoted) => unescape(quoted).ok_or(self.invalid()),
+            None => heapless::String::try_from(self.text).map_err(|_| self.invalid()),
+        }
+    }
+
+    /// Bytes, as a list of numbers (`[0xde, 173]`) or as hex digit pairs, bare or quoted
+    /// (`dead`, `"dead"`).
+    pub fn bytes<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, ParsedTelecommandErr> {
+        if self.text.starts_with('[') {
+            let mut bytes = heapless::Vec::new();
+            for element in self.list()? {
+                let byte = u8::try_from(parse_u32(element.text).ok_or(self.invalid())?)
+                    .map_err(|_| self.invalid())?;
+                bytes.push(byte).map_err(|_| self.invalid())?;
+            }
+            return Ok(bytes);
+        }
+
+        let hex = self.text.trim_matches('"');
+        parse_hex_bytes::<N>(hex).ok_or(self.invalid())
+    }
+
+    /// The elements of a list such as `[a, b]`.
+    pub fn list(&self) -> Result<Args<'a>, ParsedTelecommandErr> {
+        self.text
+            .strip_prefix('[')
+            .and_then(|s| s.strip_suffix(']'))
+            .map(|inner| Args::new(inner, self.offset + 1, false))
+            .ok_or(self.invalid())
+    }
+}
+
+/// Resolve the escapes in the contents of a quoted string.
+fn unescape<const N: usize>(s: &str) -> Option<heapless::String<N>> {
+    let mut out = heapless::String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                '0' => '\0',
+                'b' => '\u{8}',
+                'f' => '\u{c}',
+                'x' => {
+                    let hex = [chars.next()?, chars.next()?];
+                    let value = hex[0].to_digit(16)? * 16 + hex[1].to_digit(16)?;
+                    char::from_u32(value)?
+                }
+                'u' => {
+                    let mut value = 0;
+                    for _ in 0..4 {
+                        value = value * 16 + chars.next()?.to_digit(16)?;
+                    }
+                    char::from_u32(value)?
+                }
+                other => other,
+            }
+        } else {
+            c
+        };
+        out.push(c).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn args_of(input: &str) -> Vec<&str> {
+        let (_, args) = parse_command(input).unwrap();
+        args.map(|arg| arg.text()).collect()
+    }
+
+    fn error_of(input: &str) -> (usize, SyntaxErrorKind) {
+        let error = parse_command(input).unwrap_err();
+        (error.offset, error.kind)
+    }
+
+    #[test]
+    fn test_splits_only_top_level_commas() {
+        assert_eq!(args_of("f"), Vec::<&str>::new());
+        assert_eq!(args_of(" f ( ) "), Vec::<&str>::new());
+        assert_eq!(
+            args_of(r#"f(1, "a, \"b\"", [x, y], {"k": [1, 2]}, g(h(1, 2)))"#),
+            [
+                "1",
+                r#""a, \"b\"""#,
+                "[x, y]",
+                r#"{"k": [1, 2]}"#,
+                "g(h(1, 2))"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_error_offsets() {
+        use SyntaxErrorKind::*;
+        assert_eq!(error_of("hello_world(foo"), (15, UnexpectedEnd));
+        assert_eq!(error_of("hello_world)"), (11, UnexpectedCharacter));
+        assert_eq!(error_of("hello_world() x"), (14, UnexpectedCharacter));
+        assert_eq!(error_of(r#"f("abc)"#), (2, UnterminatedString));
+        assert_eq!(error_of(r#"f("a\qb")"#), (4, InvalidEscape));
+        assert_eq!(error_of("f([1, 2)"), (7, MismatchedBracket));
+        assert_eq!(error_of("f(1, , 2)"), (5, EmptyArgument));
+        assert_eq!(error_of("f(1,)"), (4, EmptyArgument));
+
+        // Offsets count characters, not bytes.
+        assert_eq!(error_of(r#"f("é€", x) y"#), (11, UnexpectedCharacter));
+        assert_eq!(error_of(r#"f("日本\q")"#), (5, InvalidEscape));
+    }
+
+    #[test]
+    fn test_arg_types() {
+        let (_, args) =
+            parse_command(r#"f(42, 0x2A, 0b101010, "a\tb\x41é", [0xde, 173], "beef")"#).unwrap();
+        let args: Vec<Arg> = args.collect();
+
+        for arg in &args[..3] {
+            assert_eq!(arg.u32(), Ok(42));
+        }
+        assert_eq!(args[3].string::<16>().unwrap(), "a\tbAé");
+        assert_eq!(args[4].bytes::<4>().unwrap(), [0xde, 0xad]);
+        assert_eq!(args[5].bytes::<4>().unwrap(), [0xbe, 0xef]);
+        assert_eq!(args[4].offset(), 36);
+
+        assert_eq!(args[3].u32(), Err(ParsedTelecommandErr::InvalidArgument(3)));
+        assert_eq!(
+            args[3].bytes::<4>(),
+            Err(ParsedTelecommandErr::InvalidArgument(3))
+        );
+    }
+
+    #[test]
+    fn test_list_and_split_call() {
+        let (_, args) = parse_command("f([a, \"b\"])").unwrap();
+        let mut params = Params::new(args);
+        let list: Vec<&str> = params
+            .required("names")
+            .unwrap()
+            .list()
+            .unwrap()
+            .map(|a| a.text())
+            .collect();
+        assert_eq!(list, ["a", "\"b\""]);
+        assert_eq!(params.finish(), Ok(()));
+
+        assert_eq!(split_call("outside(5..100)"), Ok(("outside", "5..100")));
+        assert_eq!(split_call("u32( 7 )"), Ok(("u32", "7")));
+        assert!(split_call("above(5").is_err());
+    }
+
+    fn bind<'a>(
+        input: &'a str,
+        names: &[&str],
+    ) -> Result<Vec<Option<&'a str>>, ParsedTelecommandErr> {
+        let (_, args) = parse_command(input).unwrap();
+        let mut params = Params::new(args);
+        let values = names
+            .iter()
+            .map(|name| params.optional(name).map(|arg| arg.text()))
+            .collect();
+        params.finish()?;
+        Ok(values)
+    }
+
+    #[test]
+    fn test_keyword_arguments() {
+        let names = ["a", "b", "c"];
+        assert_eq!(
+            bind("f(1, c = \"x, y\", b=[2])", &names),
+            Ok(std::vec![Some("1"), Some("[2]"), Some("\"x, y\"")])
+        );
+        assert_eq!(bind("f(b=2)", &names), Ok(std::vec![None, Some("2"), None]));
+        assert_eq!(
+            bind("f(1, 2, 3, 4)", &names),
+            Err(ParsedTelecommandErr::ExceededArgumentCount)
+        );
+        assert_eq!(
+            bind("f(1, d=4)", &names),
+            Err(ParsedTelecommandErr::UnexpectedKeyword(1))
+        );
+        assert_eq!(
+            bind("f(1, a=1)", &names),
+            Err(ParsedTelecommandErr::UnexpectedKeyword(1))
+        );
+
+        // Errors in a keyword argument are reported against its parameter.
+        let (_, args) = parse_command("f(b=x)").unwrap();
+        let mut params = Params::new(args);
+        params.optional("a");
+        assert_eq!(
+            params.optional("b").unwrap().u32(),
+            Err(ParsedTelecommandErr::InvalidArgument(1))
+        );
+
+        // `==` and `=` inside brackets don't make a keyword argument.
+        assert_eq!(
+            bind("f(a == 1)", &names),
+            Ok(std::vec![Some("a == 1"), None, None])
+        );
+        assert_eq!(
+            bind("f(g(a=1))", &names),
+            Ok(std::vec![Some("g(a=1)"), None, None])
+        );
+
+        use SyntaxErrorKind::*;
+        assert_eq!(error_of("f(a=1, 2)"), (7, PositionalAfterKeyword));
+        assert_eq!(error_of("f(a=, 2)"), (4, EmptyArgument));
+    }
+}
@@ -6,14 +6,34 @@
 #[cfg(test)]
 extern crate std;
 
+pub mod batch;
+
 pub mod config;
 use config::{ConfigStore, ConfigValue, ConfigVariableName};
 
 pub mod error;
+
+pub mod fdir;
+use fdir::FdirRuleArgs;
+
+pub mod format;
+
+pub mod mode;
+use mode::OperatingMode;
+
 use error::{ConfigError, ParsedTelecommandErr};
 
+pub mod de;
+
+pub mod dictionary;
+use dictionary::CommandInfo;
+
+pub mod grammar;
+use grammar::{Arg, Params, parse_command};
+
+pub mod response;
+
 mod shared;
-use shared::extract_function_and_args;
 
 use core::str::FromStr;
 use serde::{Deserialize, Serialize};
@@ -28,7 +48,7 @@ pub fn get_config_store() -> &'static ConfigStore {
 }
 
 // --- Existing Telecommand Code ---
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct DemoCommandWithArgumentsArgs {
     pub arg_u32: u32,
     pub arg_u64: u64,
@@ -40,72 +60,371 @@ pub struct DemoCommandWithArgumentsArgs {
 
 // TODO:Add more args for other telecommands as needed
 
-#[derive(Debug, PartialEq)]
-#[allow(non_camel_case_types)] // Allow telecommand names that align with their function names.
+/// Largest number of bytes that a single `mem_write` can carry.
+pub const MAX_MEM_WRITE_LEN: usize = 64;
+
+/// Bytes to be written by `mem_write`, given as a hex string (e.g. `deadbeef`).
+pub type MemWriteBytes = heapless::Vec<u8, MAX_MEM_WRITE_LEN>;
+
+/// Longest config blob that `config_import` accepts, in bytes.
+pub const MAX_CONFIG_BLOB_LEN: usize = 96;
+
+/// A config blob from `config_export`, given as a hex string.
+pub type ConfigBlob = heapless::Vec<u8, MAX_CONFIG_BLOB_LEN>;
+
+/// How `mem_read` returns the memory contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemDumpFormat {
+    /// Human-readable hex dump, with addresses and an ASCII column (`hex`, the default).
+    Hex,
+    /// Raw bytes, after a one-line header (`bin`).
+    Binary,
+}
+
+impl MemDumpFormat {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            MemDumpFormat::Hex => "hex",
+            MemDumpFormat::Binary => "bin",
+        }
+    }
+}
+
+impl FromStr for MemDumpFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(MemDumpFormat::Hex),
+            "bin" => Ok(MemDumpFormat::Binary),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What the memory telecommands may do in a memory region. Ordered from least to most access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryAccess {
+    /// Neither read nor write (`none`).
+    NoAccess,
+    /// Read only (`ro`).
+    ReadOnly,
+    /// Read and write (`rw`).
+    ReadWrite,
+}
+
+impl MemoryAccess {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            MemoryAccess::NoAccess => "none",
+            MemoryAccess::ReadOnly => "ro",
+            MemoryAccess::ReadWrite => "rw",
+        }
+    }
+}
+
+impl FromStr for MemoryAccess {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(MemoryAccess::NoAccess),
+            "ro" => Ok(MemoryAccess::ReadOnly),
+            "rw" => Ok(MemoryAccess::ReadWrite),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Longest telemetry point name accepted by the telemetry commands.
+pub const MAX_TELEMETRY_NAME_LEN: usize = 32;
+
+/// Most telemetry points that one `get_telemetry_many` can ask for.
+pub const MAX_TELEMETRY_POINTS_PER_REQUEST: usize = 16;
+
+/// Name of a telemetry point (e.g. `uptime_ms`).
+pub type TelemetryName = heapless::String<MAX_TELEMETRY_NAME_LEN>;
+
+/// Names given to `get_telemetry_many`, as a list (e.g. `[uptime_ms, uart_rx_bytes]`).
+pub type TelemetryNames = heapless::Vec<TelemetryName, MAX_TELEMETRY_POINTS_PER_REQUEST>;
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(non_camel_case_types)]
+// Allow telecommand names that align with their function names.
+// A `Telecommand` is 656 bytes on the host, almost all of it `get_telemetry_many`'s names; the
+// next largest variant is under 160 bytes. There's no allocator to box with, so keep as few
+// alive at once as possible: `parse_batch` holds a batch as text, not as `Telecommand`s.
+#[allow(clippy::large_enum_variant)]
 pub enum Telecommand {
     hello_world, // telecommand with no args
     get_sys_uptime,
     demo_command_with_arguments(DemoCommandWithArgumentsArgs),
     get_config(ConfigVariableName),
     set_config(ConfigVariableName, ConfigValue),
+    verify_staged_firmware,
+    mem_read(u32, u32, MemDumpFormat), // address, length, format
+    mem_write(u32, MemWriteBytes),     // address, bytes
+    mem_crc(u32, u32),                 // address, length
+    mem_list_regions,
+    mem_set_region(u32, MemoryAccess), // address in the region, access
+    get_image_crc_status,
+    get_events(u32), // number of most recent events
+    get_seu_stats,
+    get_telemetry(TelemetryName),
+    list_telemetry,
+    get_telemetry_many(TelemetryNames),
+    subscribe(TelemetryName, u32), // channel, period in ms
+    unsubscribe(TelemetryName),
+    list_subscriptions,
+    set_mode(OperatingMode),
+    fdir_list_rules,
+    fdir_set_rule(FdirRuleArgs),
+    fdir_enable_rule(u8), // slot
+    fdir_disable_rule(u8),
+    fdir_delete_rule(u8),
+    tx_disable,
+    tx_enable,
+    tx_disable_for(u32), // duration in seconds
+    get_command_dictionary,
+    get_cmd_history(u32), // number of most recent telecommands
+    get_cmd_stats,
+    arm(&'static CommandInfo, u32), // hazardous telecommand, nonce
+    get_config_all,
+    config_export,
+    config_import(ConfigBlob),
+    config_reset(Option<ConfigVariableName>), // `None` resets every variable
+    config_diff,
+    set_config_tentative(ConfigVariableName, ConfigValue, u32), // name, value, timeout in seconds
+    config_confirm,
+    get_config_audit(u32), // number of most recent config changes
 }
 
 // TODO: Replace with meaningful telecommands
 #[allow(clippy::result_unit_err)] // TODO: Fix the () error type to be enum or string
 pub fn parse_telecommand(input: &str) -> Result<Telecommand, ParsedTelecommandErr> {
-    // Extract string before the first '(' to identify the command.
-    let (command_name, command_args_str) = extract_function_and_args(input);
+    // Check the syntax of the whole telecommand, then interpret its arguments one by one.
+    let (command_name, args) = parse_command(input)?;
+    let mut params = Params::new(args);
 
-    let mut parts = command_args_str.split(',').map(|s| s.trim());
-    match command_name {
-        "hello_world" => Ok(Telecommand::hello_world),
+    let telecommand = match command_name {
+        "hello_world" => Telecommand::hello_world,
         "demo_command_with_arguments" => {
-            let (args, _rest) =
-                from_slice::<DemoCommandWithArgumentsArgs>(command_args_str.as_bytes())
-                    .map_err(ParsedTelecommandErr::DeserializationError)?;
-            Ok(Telecommand::demo_command_with_arguments(args))
+            // The arguments may also be one JSON object, as before keyword arguments.
+            let mut json_params = params.clone();
+            let parsed = match json_params.optional("json") {
+                Some(arg) if arg.key().is_none() && arg.text().starts_with('{') => {
+                    params = json_params;
+                    from_slice::<DemoCommandWithArgumentsArgs>(arg.text().as_bytes())
+                        .map_err(ParsedTelecommandErr::DeserializationError)?
+                        .0
+                }
+                _ => de::from_params(&mut params)?,
+            };
+            Telecommand::demo_command_with_arguments(parsed)
         }
-        "get_sys_uptime" => Ok(Telecommand::get_sys_uptime),
+        "get_sys_uptime" => Telecommand::get_sys_uptime,
         "get_config" => {
-            let name_str = parts
-                .next()
-                .ok_or(ParsedTelecommandErr::MissingArgument(0))?;
-            let name_enum = ConfigVariableName::from_str(name_str).map_err(|_| {
-                ParsedTelecommandErr::ConfigError(ConfigError::ConfigVariableNotFound)
-            })?;
-            if parts.next().is_some() {
-                return Err(ParsedTelecommandErr::ExceededArgumentCount);
-            }
-
-            Ok(Telecommand::get_config(name_enum))
+            let name_enum =
+                ConfigVariableName::from_str(params.required("name")?.text()).map_err(|_| {
+                    ParsedTelecommandErr::ConfigError(ConfigError::ConfigVariableNotFound)
+                })?;
+            Telecommand::get_config(name_enum)
         }
         "set_config" => {
-            let name_str = parts
-                .next()
-                .ok_or(ParsedTelecommandErr::MissingArgument(0))?;
-            let name_enum = ConfigVariableName::from_str(name_str)
+            let name_enum = ConfigVariableName::from_str(params.required("name")?.text())
                 .map_err(ParsedTelecommandErr::ConfigError)?;
-
-            let value_str = parts
-                .next()
-                .ok_or(ParsedTelecommandErr::MissingArgument(1))?;
-            let value_enum =
-                ConfigValue::from_str(value_str).map_err(ParsedTelecommandErr::ConfigError)?;
-
-            if parts.next().is_some() {
-                return Err(ParsedTelecommandErr::ExceededArgumentCount);
+            let value_enum = ConfigValue::from_str(params.required("value")?.text())
+                .map_err(ParsedTelecommandErr::ConfigError)?;
+            Telecommand::set_config(name_enum, value_enum)
+        }
+        "verify_staged_firmware" => Telecommand::verify_staged_firmware,
+        "mem_read" => {
+            let addr = params.required("address")?.u32()?;
+            let len = params.required("length")?.u32()?;
+            let format = match params.optional("format") {
+                Some(arg) => MemDumpFormat::from_str(arg.text()).map_err(|_| arg.invalid())?,
+                None => MemDumpFormat::Hex,
+            };
+            Telecommand::mem_read(addr, len, format)
+        }
+        "mem_list_regions" => Telecommand::mem_list_regions,
+        "mem_set_region" => {
+            let addr = params.required("address")?.u32()?;
+            let access_arg = params.required("access")?;
+            let access =
+                MemoryAccess::from_str(access_arg.text()).map_err(|_| access_arg.invalid())?;
+            Telecommand::mem_set_region(addr, access)
+        }
+        "mem_write" => {
+            let addr = params.required("address")?.u32()?;
+            let bytes_arg = params.required("bytes")?;
+            let bytes = bytes_arg
+                .bytes::<MAX_MEM_WRITE_LEN>()
+                .ok()
+                .filter(|b| !b.is_empty())
+                .ok_or(bytes_arg.invalid())?;
+            Telecommand::mem_write(addr, bytes)
+        }
+        "mem_crc" => {
+            let addr = params.required("address")?.u32()?;
+            let len = params.required("length")?.u32()?;
+            Telecommand::mem_crc(addr, len)
+        }
+        "get_image_crc_status" => Telecommand::get_image_crc_status,
+        "get_events" => Telecommand::get_events(params.required("count")?.u32()?),
+        "get_seu_stats" => Telecommand::get_seu_stats,
+        "get_telemetry" => {
+            Telecommand::get_telemetry(parse_telemetry_name(&params.required("name")?)?)
+        }
+        "list_telemetry" => Telecommand::list_telemetry,
+        "get_telemetry_many" => {
+            let mut names = TelemetryNames::new();
+            for name in params.required("names")?.list()? {
+                names
+                    .push(parse_telemetry_name(&name)?)
+                    .map_err(|_| ParsedTelecommandErr::ExceededArgumentCount)?;
+            }
+            Telecommand::get_telemetry_many(names)
+        }
+        "subscribe" => {
+            let name = parse_telemetry_name(&params.required("name")?)?;
+            let period_ms = params.required("period_ms")?.u32()?;
+            Telecommand::subscribe(name, period_ms)
+        }
+        "unsubscribe" => Telecommand::unsubscribe(parse_telemetry_name(&params.required("name")?)?),
+        "list_subscriptions" => Telecommand::list_subscriptions,
+        "set_mode" => {
+            let arg = params.required("mode")?;
+            Telecommand::set_mode(arg.text().parse().map_err(|_| arg.invalid())?)
+        }
+        "fdir_list_rules" => Telecommand::fdir_list_rules,
+        "fdir_set_rule" => {
+            let slot = params.required("slot")?.u8()?;
+            let channel = parse_telemetry_name(&params.required("channel")?)?;
+            let check_arg = params.required("check")?;
+            let check = check_arg.text().parse().map_err(|_| check_arg.invalid())?;
+            let persistence_arg = params.required("persistence")?;
+            let persistence = persistence_arg.u8()?;
+            if persistence == 0 {
+                return Err(persistence_arg.invalid());
             }
+            let action_arg = params.required("action")?;
+            let action = action_arg
+                .text()
+                .parse()
+                .map_err(|_| action_arg.invalid())?;
 
-            Ok(Telecommand::set_config(name_enum, value_enum))
+            Telecommand::fdir_set_rule(FdirRuleArgs {
+                slot,
+                channel,
+                check,
+                persistence,
+                action,
+            })
+        }
+        "fdir_enable_rule" => Telecommand::fdir_enable_rule(params.required("slot")?.u8()?),
+        "fdir_disable_rule" => Telecommand::fdir_disable_rule(params.required("slot")?.u8()?),
+        "fdir_delete_rule" => Telecommand::fdir_delete_rule(params.required("slot")?.u8()?),
+        "tx_disable" => Telecommand::tx_disable,
+        "tx_enable" => Telecommand::tx_enable,
+        "tx_disable_for" => {
+            let duration_arg = params.required("duration_s")?;
+            let duration_s = duration_arg.u32()?;
+            if duration_s == 0 {
+                return Err(duration_arg.invalid());
+            }
+            Telecommand::tx_disable_for(duration_s)
+        }
+        "get_command_dictionary" => Telecommand::get_command_dictionary,
+        "get_cmd_history" => Telecommand::get_cmd_history(params.required("count")?.u32()?),
+        "get_cmd_stats" => Telecommand::get_cmd_stats,
+        "arm" => {
+            let command_arg = params.required("command")?;
+            let command = dictionary::find_command(command_arg.text())
+                .filter(|command| command.hazardous)
+                .ok_or(command_arg.invalid())?;
+            Telecommand::arm(command, params.required("nonce")?.u32()?)
+        }
+        "get_config_all" => Telecommand::get_config_all,
+        "config_export" => Telecommand::config_export,
+        "config_import" => {
+            let blob_arg = params.required("blob")?;
+            let blob = blob_arg
+                .bytes::<MAX_CONFIG_BLOB_LEN>()
+                .ok()
+                .filter(|b| !b.is_empty())
+                .ok_or(blob_arg.invalid())?;
+            Telecommand::config_import(blob)
         }
-        _ => Err(ParsedTelecommandErr::UnknownCommand),
+        "config_reset" => match params.required("name")?.text() {
+            "all" => Telecommand::config_reset(None),
+            name => Telecommand::config_reset(Some(
+                ConfigVariableName::from_str(name).map_err(ParsedTelecommandErr::ConfigError)?,
+            )),
+        },
+        "config_diff" => Telecommand::config_diff,
+        "set_config_tentative" => {
+            let name_enum = ConfigVariableName::from_str(params.required("name")?.text())
+                .map_err(ParsedTelecommandErr::ConfigError)?;
+            let value_enum = ConfigValue::from_str(params.required("value")?.text())
+                .map_err(ParsedTelecommandErr::ConfigError)?;
+            let timeout_arg = params.required("timeout_s")?;
+            let timeout_s = timeout_arg.u32()?;
+            if timeout_s == 0 {
+                return Err(timeout_arg.invalid());
+            }
+            Telecommand::set_config_tentative(name_enum, value_enum, timeout_s)
+        }
+        "config_confirm" => Telecommand::config_confirm,
+        "get_config_audit" => Telecommand::get_config_audit(params.required("count")?.u32()?),
+        _ => return Err(ParsedTelecommandErr::UnknownCommand),
+    };
+
+    params.finish()?;
+    Ok(telecommand)
+}
+
+/// Check that the argument looks like a telemetry point name (`snake_case`, optionally in
+/// quotes).
+///
+/// Whether the point exists is only known to the firmware.
+fn parse_telemetry_name(arg: &Arg) -> Result<TelemetryName, ParsedTelecommandErr> {
+    let name = arg.string::<MAX_TELEMETRY_NAME_LEN>()?;
+    let valid = !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    if !valid {
+        return Err(arg.invalid());
     }
+    Ok(name)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_config_store_reset_to_defaults() {
+        let store = ConfigStore::new();
+        store
+            .set(ConfigVariableName::HeartbeatMs, ConfigValue::U32(5))
+            .unwrap();
+        store
+            .set(ConfigVariableName::ConfigDemoVariable1, ConfigValue::U32(6))
+            .unwrap();
+
+        store.reset_to_defaults();
+        assert_eq!(
+            store.get(ConfigVariableName::HeartbeatMs),
+            ConfigValue::U32(1000)
+        );
+        assert_eq!(
+            store.get(ConfigVariableName::ConfigDemoVariable1),
+            ConfigValue::U32(123)
+        );
+    }
+
     #[test]
     fn test_config_store_get_set() {
         let store = ConfigStore::new();
@@ -157,6 +476,148 @@ mod tests {
         assert_eq!(result, Err(ConfigError::ConfigVariableNotThisType));
     }
 
+    #[test]
+    fn test_config_store_reset_and_diff() {
+        let store = ConfigStore::new();
+        assert_eq!(store.diff().count(), 0);
+
+        store
+            .set(ConfigVariableName::HeartbeatMs, ConfigValue::U32(5))
+            .unwrap();
+        store
+            .set(ConfigVariableName::ArmWindowS, ConfigValue::U32(10))
+            .unwrap();
+        let diff: std::vec::Vec<_> = store.diff().collect();
+        assert_eq!(
+            diff,
+            [
+                (
+                    ConfigVariableName::HeartbeatMs,
+                    ConfigValue::U32(5),
+                    ConfigValue::U32(1000)
+                ),
+                (
+                    ConfigVariableName::ArmWindowS,
+                    ConfigValue::U32(10),
+                    ConfigValue::U32(60)
+                ),
+            ]
+        );
+
+        store.reset(ConfigVariableName::HeartbeatMs);
+        assert_eq!(
+            store.get(ConfigVariableName::HeartbeatMs),
+            ConfigValue::U32(1000)
+        );
+        assert_eq!(store.diff().count(), 1);
+        assert_eq!(store.values().count(), ConfigVariableName::ALL.len());
+    }
+
+    #[test]
+    fn test_config_store_change_counts() {
+        let store = ConfigStore::new();
+        let name = ConfigVariableName::HeartbeatMs;
+        assert_eq!(store.change_count(name), 0);
+
+        store.set(name, ConfigValue::U32(250)).unwrap();
+        assert_eq!(store.change_count(name), 1);
+        // Setting the same value again isn't a change, and nor is a failed set.
+        store.set(name, ConfigValue::U32(250)).unwrap();
+        assert!(store.set(name, ConfigValue::Bool(true)).is_err());
+        assert_eq!(store.change_count(name), 1);
+
+        store.reset_to_defaults();
+        assert_eq!(store.change_count(name), 2);
+        assert_eq!(store.change_count(ConfigVariableName::ArmWindowS), 0);
+
+        for (index, name) in ConfigVariableName::ALL.into_iter().enumerate() {
+            assert_eq!(name.index(), index);
+        }
+    }
+
+    #[test]
+    fn test_config_store_audit_hook() {
+        use config::ConfigAuditHook;
+        use std::sync::Mutex;
+        use std::vec::Vec;
+
+        static CHANGES: Mutex<Vec<(ConfigVariableName, ConfigValue, ConfigValue)>> =
+            Mutex::new(Vec::new());
+        static HOOK: ConfigAuditHook =
+            ConfigAuditHook(|name, old, new| CHANGES.lock().unwrap().push((name, old, new)));
+
+        let store = ConfigStore::new();
+        store.set_audit_hook(&HOOK);
+        let name = ConfigVariableName::HeartbeatMs;
+        store.set(name, ConfigValue::U32(250)).unwrap();
+        store.set(name, ConfigValue::U32(250)).unwrap();
+        assert!(store.set(name, ConfigValue::Bool(true)).is_err());
+        store.reset_to_defaults();
+        assert_eq!(
+            *CHANGES.lock().unwrap(),
+            [
+                (name, ConfigValue::U32(1000), ConfigValue::U32(250)),
+                (name, ConfigValue::U32(250), ConfigValue::U32(1000)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_store_set_all_is_atomic() {
+        let store = ConfigStore::new();
+        let result = store.set_all(&[
+            (ConfigVariableName::HeartbeatMs, ConfigValue::U32(5)),
+            (ConfigVariableName::ArmWindowS, ConfigValue::Bool(true)),
+        ]);
+        assert_eq!(result, Err(ConfigError::ConfigVariableNotThisType));
+        assert_eq!(store.diff().count(), 0);
+
+        store
+            .set_all(&[
+                (ConfigVariableName::HeartbeatMs, ConfigValue::U32(5)),
+                (ConfigVariableName::ArmWindowS, ConfigValue::U32(10)),
+            ])
+            .unwrap();
+        assert_eq!(store.diff().count(), 2);
+    }
+
+    #[test]
+    fn test_parse_set_config_tentative() {
+        assert_eq!(
+            parse_telecommand("set_config_tentative(heartbeat_ms, u32(500), 60)"),
+            Ok(Telecommand::set_config_tentative(
+                ConfigVariableName::HeartbeatMs,
+                ConfigValue::U32(500),
+                60
+            ))
+        );
+        assert_eq!(
+            parse_telecommand("set_config_tentative(heartbeat_ms, u32(500), 0)"),
+            Err(ParsedTelecommandErr::InvalidArgument(2))
+        );
+    }
+
+    #[test]
+    fn test_parse_config_reset() {
+        assert_eq!(
+            parse_telecommand("config_reset(all)"),
+            Ok(Telecommand::config_reset(None))
+        );
+        assert_eq!(
+            parse_telecommand("config_reset(heartbeat_ms)"),
+            Ok(Telecommand::config_reset(Some(
+                ConfigVariableName::HeartbeatMs
+            )))
+        );
+        assert_eq!(
+            parse_telecommand("config_reset(nope)"),
+            Err(ParsedTelecommandErr::ConfigError(
+                ConfigError::ConfigVariableNotFound
+            ))
+        );
+        assert!(parse_telecommand("config_import()").is_err());
+    }
+
     #[test]
     fn test_config_store_parse_unknown_variable() {
         let result = ConfigVariableName::from_str("unknown_variable");
@@ -211,6 +672,421 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_verify_staged_firmware() {
+        assert_eq!(
+            parse_telecommand("verify_staged_firmware()"),
+            Ok(Telecommand::verify_staged_firmware)
+        );
+    }
+
+    #[test]
+    fn test_parse_mem_read() {
+        assert_eq!(
+            parse_telecommand("mem_read(0x20000000, 16)"),
+            Ok(Telecommand::mem_read(0x2000_0000, 16, MemDumpFormat::Hex))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0x08000000, 256, bin)"),
+            Ok(Telecommand::mem_read(
+                0x0800_0000,
+                256,
+                MemDumpFormat::Binary
+            ))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0x20000000)"),
+            Err(ParsedTelecommandErr::MissingArgument(1))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0x2000000g, 16)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0x20000000, 16, octal)"),
+            Err(ParsedTelecommandErr::InvalidArgument(2))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0x20000000, 16, hex, 1)"),
+            Err(ParsedTelecommandErr::ExceededArgumentCount)
+        );
+    }
+
+    #[test]
+    fn test_parse_mem_write() {
+        let expected = MemWriteBytes::from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]).unwrap();
+        assert_eq!(
+            parse_telecommand("mem_write(0x20000100, DEADbeef)"),
+            Ok(Telecommand::mem_write(0x2000_0100, expected))
+        );
+
+        // Odd number of digits, non-hex, and empty byte strings are all invalid.
+        for bad in ["abc", "zz", "\"\""] {
+            let cmd = std::format!("mem_write(0x20000100, {})", bad);
+            assert_eq!(
+                parse_telecommand(&cmd),
+                Err(ParsedTelecommandErr::InvalidArgument(1))
+            );
+        }
+
+        let too_long = std::format!(
+            "mem_write(0x20000100, {})",
+            "00".repeat(MAX_MEM_WRITE_LEN + 1)
+        );
+        assert_eq!(
+            parse_telecommand(&too_long),
+            Err(ParsedTelecommandErr::InvalidArgument(1))
+        );
+    }
+
+    #[test]
+    fn test_parse_mem_crc() {
+        assert_eq!(
+            parse_telecommand("mem_crc(134217728, 0x1000)"),
+            Ok(Telecommand::mem_crc(0x0800_0000, 0x1000))
+        );
+        assert_eq!(
+            parse_telecommand("mem_crc()"),
+            Err(ParsedTelecommandErr::MissingArgument(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_mem_regions() {
+        assert_eq!(
+            parse_telecommand("mem_set_region(0x20000000, none)"),
+            Ok(Telecommand::mem_set_region(
+                0x2000_0000,
+                MemoryAccess::NoAccess
+            ))
+        );
+        assert_eq!(
+            parse_telecommand("mem_set_region(access=rw, address=0x40000000)"),
+            Ok(Telecommand::mem_set_region(
+                0x4000_0000,
+                MemoryAccess::ReadWrite
+            ))
+        );
+        assert_eq!(
+            parse_telecommand("mem_set_region(0x20000000, wo)"),
+            Err(ParsedTelecommandErr::InvalidArgument(1))
+        );
+        assert_eq!(
+            parse_telecommand("mem_list_regions"),
+            Ok(Telecommand::mem_list_regions)
+        );
+    }
+
+    #[test]
+    fn test_parse_arm() {
+        let set_config = dictionary::find_command("set_config").unwrap();
+        assert_eq!(
+            parse_telecommand("arm(set_config, 42)"),
+            Ok(Telecommand::arm(set_config, 42))
+        );
+        assert_eq!(
+            parse_telecommand("arm(nonce=7, command=set_config)"),
+            Ok(Telecommand::arm(set_config, 7))
+        );
+        // Only hazardous telecommands can be armed.
+        assert_eq!(
+            parse_telecommand("arm(hello_world, 1)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("arm(no_such_command, 1)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("arm(set_config)"),
+            Err(ParsedTelecommandErr::MissingArgument(1))
+        );
+    }
+
+    #[test]
+    fn test_parse_health_commands() {
+        assert_eq!(
+            parse_telecommand("get_image_crc_status()"),
+            Ok(Telecommand::get_image_crc_status)
+        );
+        assert_eq!(
+            parse_telecommand("get_events(5)"),
+            Ok(Telecommand::get_events(5))
+        );
+        assert_eq!(
+            parse_telecommand("get_events()"),
+            Err(ParsedTelecommandErr::MissingArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("get_seu_stats()"),
+            Ok(Telecommand::get_seu_stats)
+        );
+        assert_eq!(
+            parse_telecommand("get_cmd_history(count=8)"),
+            Ok(Telecommand::get_cmd_history(8))
+        );
+        assert_eq!(
+            parse_telecommand("get_cmd_stats"),
+            Ok(Telecommand::get_cmd_stats)
+        );
+    }
+
+    #[test]
+    fn test_parse_telemetry_commands() {
+        assert_eq!(
+            parse_telecommand("get_telemetry(uptime_ms)"),
+            Ok(Telecommand::get_telemetry("uptime_ms".try_into().unwrap()))
+        );
+        assert_eq!(
+            parse_telecommand("get_telemetry(\"uptime_ms\")"),
+            Ok(Telecommand::get_telemetry("uptime_ms".try_into().unwrap()))
+        );
+        assert_eq!(
+            parse_telecommand("get_telemetry()"),
+            Err(ParsedTelecommandErr::MissingArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("get_telemetry(Bad-Name)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("list_telemetry()"),
+            Ok(Telecommand::list_telemetry)
+        );
+    }
+
+    #[test]
+    fn test_parse_get_telemetry_many() {
+        let Ok(Telecommand::get_telemetry_many(names)) =
+            parse_telecommand("get_telemetry_many([uptime_ms, \"uart_rx_bytes\"])")
+        else {
+            panic!("expected get_telemetry_many");
+        };
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0], "uptime_ms");
+        assert_eq!(names[1], "uart_rx_bytes");
+
+        assert_eq!(
+            parse_telecommand("get_telemetry_many(uptime_ms)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("get_telemetry_many([uptime_ms, ])"),
+            Err(ParsedTelecommandErr::InvalidArgument(1))
+        );
+
+        let too_many = std::format!("get_telemetry_many([{}a])", "a, ".repeat(16));
+        assert_eq!(
+            parse_telecommand(&too_many),
+            Err(ParsedTelecommandErr::ExceededArgumentCount)
+        );
+    }
+
+    #[test]
+    fn test_parse_subscription_commands() {
+        assert_eq!(
+            parse_telecommand("subscribe(uptime_ms, 1000)"),
+            Ok(Telecommand::subscribe(
+                "uptime_ms".try_into().unwrap(),
+                1000
+            ))
+        );
+        assert_eq!(
+            parse_telecommand("subscribe(uptime_ms)"),
+            Err(ParsedTelecommandErr::MissingArgument(1))
+        );
+        assert_eq!(
+            parse_telecommand("subscribe(uptime_ms, fast)"),
+            Err(ParsedTelecommandErr::InvalidArgument(1))
+        );
+        assert_eq!(
+            parse_telecommand("unsubscribe(uptime_ms)"),
+            Ok(Telecommand::unsubscribe("uptime_ms".try_into().unwrap()))
+        );
+        assert_eq!(
+            parse_telecommand("list_subscriptions()"),
+            Ok(Telecommand::list_subscriptions)
+        );
+    }
+
+    #[test]
+    fn test_parse_fdir_commands() {
+        use fdir::{FdirAction, LimitCheck};
+
+        assert_eq!(
+            parse_telecommand("fdir_set_rule(2, uart_rx_overflow_bytes, above(1000), 3, event)"),
+            Ok(Telecommand::fdir_set_rule(FdirRuleArgs {
+                slot: 2,
+                channel: "uart_rx_overflow_bytes".try_into().unwrap(),
+                check: LimitCheck::Above(1000.0),
+                persistence: 3,
+                action: FdirAction::EventOnly,
+            }))
+        );
+
+        let Ok(Telecommand::fdir_set_rule(args)) = parse_telecommand(
+            "fdir_set_rule(0, uptime_ms, outside(0..10), 1, command(subscribe(uptime_ms, 1000)))",
+        ) else {
+            panic!("expected fdir_set_rule");
+        };
+        assert_eq!(
+            args.action,
+            FdirAction::RunCommand("subscribe(uptime_ms, 1000)".try_into().unwrap())
+        );
+
+        assert_eq!(
+            parse_telecommand("fdir_set_rule(0, uptime_ms, above(1), 0, event)"),
+            Err(ParsedTelecommandErr::InvalidArgument(3))
+        );
+        assert_eq!(
+            parse_telecommand("fdir_set_rule(0, uptime_ms, above(1), 1)"),
+            Err(ParsedTelecommandErr::MissingArgument(4))
+        );
+        assert_eq!(
+            parse_telecommand("fdir_set_rule(300, uptime_ms, above(1), 1, event)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("fdir_disable_rule(4)"),
+            Ok(Telecommand::fdir_disable_rule(4))
+        );
+        assert_eq!(
+            parse_telecommand("fdir_list_rules()"),
+            Ok(Telecommand::fdir_list_rules)
+        );
+    }
+
+    #[test]
+    fn test_parse_set_mode() {
+        assert_eq!(
+            parse_telecommand("set_mode(safe)"),
+            Ok(Telecommand::set_mode(OperatingMode::Safe))
+        );
+        assert_eq!(
+            parse_telecommand("set_mode(party)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_tx_inhibit_commands() {
+        assert_eq!(parse_telecommand("tx_disable"), Ok(Telecommand::tx_disable));
+        assert_eq!(parse_telecommand("tx_enable()"), Ok(Telecommand::tx_enable));
+        assert_eq!(
+            parse_telecommand("tx_disable_for(3600)"),
+            Ok(Telecommand::tx_disable_for(3600))
+        );
+        assert_eq!(
+            parse_telecommand("tx_disable_for(0)"),
+            Err(ParsedTelecommandErr::InvalidArgument(0))
+        );
+        assert_eq!(
+            parse_telecommand("tx_disable_for()"),
+            Err(ParsedTelecommandErr::MissingArgument(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_syntax_errors() {
+        use error::{SyntaxError, SyntaxErrorKind};
+
+        assert_eq!(
+            parse_telecommand("hello_world(foo"),
+            Err(ParsedTelecommandErr::Syntax(SyntaxError {
+                offset: 15,
+                kind: SyntaxErrorKind::UnexpectedEnd,
+            }))
+        );
+        assert_eq!(
+            parse_telecommand("hello_world)"),
+            Err(ParsedTelecommandErr::Syntax(SyntaxError {
+                offset: 11,
+                kind: SyntaxErrorKind::UnexpectedCharacter,
+            }))
+        );
+        assert_eq!(
+            parse_telecommand("hello_world(1)"),
+            Err(ParsedTelecommandErr::ExceededArgumentCount)
+        );
+    }
+
+    #[test]
+    fn test_parse_arguments_containing_commas() {
+        let expected = MemWriteBytes::from_slice(&[0xDE, 0xAD]).unwrap();
+        assert_eq!(
+            parse_telecommand("mem_write(0x20000100, [0xde, 173])"),
+            Ok(Telecommand::mem_write(0x2000_0100, expected))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0b100000, 16)"),
+            Ok(Telecommand::mem_read(0x20, 16, MemDumpFormat::Hex))
+        );
+
+        let Ok(Telecommand::fdir_set_rule(args)) = parse_telecommand(
+            "fdir_set_rule(0, uptime_ms, above(1), 1, command(get_telemetry_many([mode, uptime_ms])))",
+        ) else {
+            panic!("expected fdir_set_rule");
+        };
+        assert_eq!(
+            args.action,
+            fdir::FdirAction::RunCommand(
+                "get_telemetry_many([mode, uptime_ms])".try_into().unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_keyword_arguments() {
+        assert_eq!(
+            parse_telecommand("mem_read(length=16, address=0x20000000)"),
+            parse_telecommand("mem_read(0x20000000, 16)")
+        );
+        assert_eq!(
+            parse_telecommand("set_config(value=u32(8386), name=config_demo_variable1)"),
+            Ok(Telecommand::set_config(
+                ConfigVariableName::ConfigDemoVariable1,
+                ConfigValue::U32(8386)
+            ))
+        );
+        assert_eq!(
+            parse_telecommand("mem_read(0x20000000, format=bin)"),
+            Err(ParsedTelecommandErr::MissingArgument(1))
+        );
+        assert_eq!(
+            parse_telecommand("get_events(5, number=5)"),
+            Err(ParsedTelecommandErr::UnexpectedKeyword(1))
+        );
+
+        let expected = DemoCommandWithArgumentsArgs {
+            arg_u32: 1,
+            arg_u64: 2,
+            arg_bool: true,
+            arg_f32: 3.0,
+            arg_f64: 4.0,
+            arg_nullable_u32: None,
+        };
+        assert_eq!(
+            parse_telecommand(
+                "demo_command_with_arguments(arg_f64=4.0, arg_u32=1, arg_u64=2, arg_bool=true, arg_f32=3.0)"
+            ),
+            Ok(Telecommand::demo_command_with_arguments(expected))
+        );
+        let Ok(Telecommand::demo_command_with_arguments(args)) =
+            parse_telecommand("demo_command_with_arguments(1, 2, true, 3.0, 4.0, 5)")
+        else {
+            panic!("expected demo_command_with_arguments");
+        };
+        assert_eq!(args.arg_nullable_u32, Some(5));
+        assert_eq!(
+            parse_telecommand("demo_command_with_arguments(1, 2, maybe, 3.0, 4.0)"),
+            Err(ParsedTelecommandErr::InvalidArgument(2))
+        );
+        assert_eq!(
+            parse_telecommand("demo_command_with_arguments(1, 2)"),
+            Err(ParsedTelecommandErr::MissingArgument(2))
+        );
+    }
+
     #[test]
     fn test_placeholder() {
         assert_eq!(42, 42);
@@ -329,3 +1205,152 @@ mod tests {
         );
     }
 }
+
+/// Property tests over inputs from the outside world. A panic in the parser halts the OBC, so
+/// every input must give a result, and the same one each time. `fuzz/` runs the same checks
+/// under libFuzzer.
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use fdir::{FdirAction, LimitCheck};
+    use proptest::prelude::*;
+    use std::format;
+    use std::string::{String, ToString};
+
+    /// Valid telecommands to mutate. `fuzz/corpus/parse_telecommand` starts from the same ones.
+    const SEED_COMMANDS: &[&str] = &[
+        "hello_world()",
+        "demo_command_with_arguments({\"arg_u32\": 1, \"arg_u64\": 2, \"arg_bool\": true, \"arg_f32\": 3.0, \"arg_f64\": 4.0, \"arg_nullable_u32\": null})",
+        "demo_command_with_arguments(arg_u32=1, arg_u64=0x2, arg_bool=false, arg_f32=-3.5, arg_f64=4e3)",
+        "set_config(value=f32(1.5), name=heartbeat_ms)",
+        "mem_read(0x20000000, 16, hex)",
+        "mem_write(address=0b100000, bytes=[0xde, 173])",
+        "get_telemetry_many([uptime_ms, \"uart_rx_bytes\", mode])",
+        "fdir_set_rule(2, uart_rx_overflow_bytes, outside(-1.5..1000), 3, command(subscribe(uptime_ms, 1000)))",
+        "tx_disable_for(3600)",
+    ];
+
+    /// Pieces of the grammar, to build inputs that get further than random text does.
+    const TOKENS: &[&str] = &[
+        "mem_write",
+        "set_config",
+        "get_telemetry_many",
+        "fdir_set_rule",
+        "demo_command_with_arguments",
+        "u32",
+        "f32",
+        "above",
+        "outside",
+        "command",
+        "mode",
+        "(",
+        ")",
+        "[",
+        "]",
+        "{",
+        "}",
+        ",",
+        "\"",
+        "\\",
+        "\\u",
+        "=",
+        "==",
+        " ",
+        "\t",
+        "\x0b",
+        "0x",
+        "0b",
+        "1",
+        "-",
+        ".",
+        "..",
+        "e",
+        "a",
+        "é",
+        "null",
+        "true",
+    ];
+
+    fn token_soup() -> impl Strategy<Value = String> {
+        prop::collection::vec(prop::sample::select(TOKENS), 0..40)
+            .prop_map(|tokens| tokens.concat())
+    }
+
+    /// Insert a character into a valid telecommand, or remove one from it.
+    fn mutated_command() -> impl Strategy<Value = String> {
+        (
+            prop::sample::select(SEED_COMMANDS),
+            any::<prop::sample::Index>(),
+            any::<Option<char>>(),
+        )
+            .prop_map(|(seed, index, insert)| {
+                let mut input = seed.to_string();
+                let position = index.index(input.len());
+                match insert {
+                    Some(c) => input.insert(position, c),
+                    None => {
+                        input.remove(position);
+                    }
+                }
+                input
+            })
+    }
+
+    fn check_parse(input: &str) -> Result<(), TestCaseError> {
+        // Compare the debug text, as `NaN != NaN`.
+        let result = parse_telecommand(input);
+        prop_assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", parse_telecommand(input))
+        );
+
+        match result {
+            Err(ParsedTelecommandErr::Syntax(e)) => prop_assert!(e.offset <= input.chars().count()),
+            Ok(telecommand) => {
+                if let Ok(text) = telecommand.to_canonical() {
+                    let reparsed = parse_telecommand(&text);
+                    prop_assert!(reparsed.is_ok(), "{} does not parse", text);
+                    prop_assert_eq!(reparsed.unwrap().to_canonical(), Ok(text));
+                }
+            }
+            Err(_) => {}
+        }
+        Ok(())
+    }
+
+    /// If `input` parses, the value formats to text that parses to the same value.
+    fn check_value<T: FromStr + ToString>(input: &str) -> Result<(), TestCaseError> {
+        if let Ok(value) = input.parse::<T>() {
+            let text = value.to_string();
+            let reparsed = text.parse::<T>().ok().map(|value| value.to_string());
+            prop_assert_eq!(reparsed, Some(text));
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn test_parse_any_text(input in any::<String>()) {
+            check_parse(&input)?;
+        }
+
+        #[test]
+        fn test_parse_token_soup(input in token_soup()) {
+            check_parse(&input)?;
+        }
+
+        #[test]
+        fn test_parse_mutated_commands(input in mutated_command()) {
+            check_parse(&input)?;
+        }
+
+        #[test]
+        fn test_parse_values(input in prop_oneof![token_soup(), any::<String>()]) {
+            check_value::<ConfigValue>(&input)?;
+            check_value::<LimitCheck>(&input)?;
+            check_value::<FdirAction>(&input)?;
+        }
+    }
+}
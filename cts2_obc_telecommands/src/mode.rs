@@ -0,0 +1,40 @@
+use core::str::FromStr;
+
+/// Overall operating mode of the OBC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OperatingMode {
+    /// Normal operations.
+    Nominal = 0,
+    /// Minimal operations after a fault, until ground intervenes.
+    Safe = 1,
+}
+
+impl OperatingMode {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            OperatingMode::Nominal => "nominal",
+            OperatingMode::Safe => "safe",
+        }
+    }
+
+    pub const fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(OperatingMode::Nominal),
+            1 => Some(OperatingMode::Safe),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for OperatingMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nominal" => Ok(OperatingMode::Nominal),
+            "safe" => Ok(OperatingMode::Safe),
+            _ => Err(()),
+        }
+    }
+}
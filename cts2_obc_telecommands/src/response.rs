@@ -0,0 +1,134 @@
+//! Response lines for telecommands that were rejected, and for lines of several telecommands.
+//!
+//! The firmware sends these, and ground software decodes them, so the text is kept here. Each
+//! line ends with `\r\n`.
+
+use core::fmt::{self, Write};
+
+use crate::batch::MAX_BATCH_LEN;
+use crate::error::{ConfigError, ParsedTelecommandErr};
+
+/// Start of every error line.
+pub const ERROR_PREFIX: &str = "ERR: ";
+
+/// Start of the result line of each telecommand in a line of several.
+pub const BATCH_PREFIX: &str = "BATCH: ";
+
+/// Text of a config error, without the prefix.
+pub const fn config_error_text(e: &ConfigError) -> &'static str {
+    match e {
+        ConfigError::ConfigVariableNotFound => "configuration variable not found",
+        ConfigError::ConfigVariableNotThisType => "configuration variable is not this type",
+        ConfigError::ConfigVariableUnknownType => "unknown type for configuration variable",
+        ConfigError::ConfigParseValueTypeError => "cannot parse the type with the value string",
+    }
+}
+
+/// Write why a telecommand didn't parse, e.g. `ERR: invalid argument at index 1`.
+///
+/// Config errors take two lines: `ERR: configuration error`, then the specific error.
+pub fn write_parse_error<W: Write>(out: &mut W, e: &ParsedTelecommandErr) -> fmt::Result {
+    out.write_str(ERROR_PREFIX)?;
+    match e {
+        ParsedTelecommandErr::UnknownCommand => out.write_str("unknown command")?,
+        ParsedTelecommandErr::DeserializationError(_) => {
+            out.write_str("failed to deserialize command arguments")?
+        }
+        ParsedTelecommandErr::MissingArgument(idx) => {
+            write!(out, "missing required argument at index {}", idx)?
+        }
+        ParsedTelecommandErr::ExceededArgumentCount => {
+            out.write_str("too many arguments provided")?
+        }
+        ParsedTelecommandErr::InvalidArgument(idx) => {
+            write!(out, "invalid argument at index {}", idx)?
+        }
+        ParsedTelecommandErr::UnexpectedKeyword(idx) => {
+            write!(out, "unexpected keyword argument at index {}", idx)?
+        }
+        ParsedTelecommandErr::Syntax(e_syntax) => write!(out, "syntax error: {}", e_syntax)?,
+        ParsedTelecommandErr::ConfigError(e_conf) => write!(
+            out,
+            "configuration error\r\n{}{}",
+            ERROR_PREFIX,
+            config_error_text(e_conf)
+        )?,
+    }
+    out.write_str("\r\n")
+}
+
+/// Write that telecommand `index` (from 0) of a line of `count` didn't parse, so none ran.
+/// The parse error follows.
+pub fn write_batch_invalid<W: Write>(out: &mut W, index: usize, count: usize) -> fmt::Result {
+    write!(
+        out,
+        "{}telecommand {}/{} is invalid, none were run\r\n",
+        ERROR_PREFIX,
+        index + 1,
+        count
+    )
+}
+
+/// Write that a line had more than [`MAX_BATCH_LEN`] telecommands.
+pub fn write_batch_too_long<W: Write>(out: &mut W) -> fmt::Result {
+    write!(
+        out,
+        "{}more than {} telecommands in one line\r\n",
+        ERROR_PREFIX, MAX_BATCH_LEN
+    )
+}
+
+/// Write the result of telecommand `index` (from 0) of a line of `count`, e.g.
+/// `BATCH: 2/3 failed, 1 not run`.
+pub fn write_batch_result<W: Write>(
+    out: &mut W,
+    index: usize,
+    count: usize,
+    ok: bool,
+) -> fmt::Result {
+    write!(out, "{}{}/{} ", BATCH_PREFIX, index + 1, count)?;
+    if ok {
+        out.write_str("ok\r\n")
+    } else {
+        write!(out, "failed, {} not run\r\n", count - index - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    fn text(write: impl Fn(&mut String) -> fmt::Result) -> String {
+        let mut out = String::new();
+        write(&mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_response_text() {
+        assert_eq!(
+            text(|out| write_parse_error(out, &ParsedTelecommandErr::InvalidArgument(2))),
+            "ERR: invalid argument at index 2\r\n"
+        );
+        assert_eq!(
+            text(|out| write_parse_error(
+                out,
+                &ParsedTelecommandErr::ConfigError(ConfigError::ConfigVariableNotFound)
+            )),
+            "ERR: configuration error\r\nERR: configuration variable not found\r\n"
+        );
+        assert_eq!(
+            text(|out| write_batch_invalid(out, 1, 2)),
+            "ERR: telecommand 2/2 is invalid, none were run\r\n"
+        );
+        assert_eq!(
+            text(|out| write_batch_result(out, 1, 3, false)),
+            "BATCH: 2/3 failed, 1 not run\r\n"
+        );
+        assert_eq!(
+            text(|out| write_batch_result(out, 2, 3, true)),
+            "BATCH: 3/3 ok\r\n"
+        );
+    }
+}
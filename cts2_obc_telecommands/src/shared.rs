@@ -1,12 +1,35 @@
-pub fn extract_function_and_args(input: &str) -> (&str, &str) {
-    let command_name = input.trim().split('(').next().unwrap_or("");
-    let command_args_str = input
-        .trim()
-        .strip_prefix(command_name)
-        .and_then(|s| s.strip_prefix('('))
-        .and_then(|s| s.strip_suffix(')'))
-        .unwrap_or("")
-        .trim();
+/// Parse an unsigned integer written in decimal, as `0x`-prefixed hex (e.g. an address), or
+/// as `0b`-prefixed binary (e.g. a bit mask).
+pub fn parse_u32(s: &str) -> Option<u32> {
+    parse_u64(s).and_then(|value| u32::try_from(value).ok())
+}
+
+/// Like [`parse_u32`], for 64-bit values.
+pub fn parse_u64(s: &str) -> Option<u64> {
+    let s = s.trim_ascii();
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")) {
+        u64::from_str_radix(binary, 2).ok()
+    } else {
+        s.parse::<u64>().ok()
+    }
+}
+
+/// Parse a string of hex digit pairs (e.g. `deadbeef`) into bytes.
+///
+/// Returns `None` on odd-length input, non-hex characters, or if the bytes don't fit in `N`.
+pub fn parse_hex_bytes<const N: usize>(s: &str) -> Option<heapless::Vec<u8, N>> {
+    let s = s.trim_ascii().as_bytes();
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
 
-    (command_name, command_args_str)
+    let mut out = heapless::Vec::new();
+    for pair in s.chunks(2) {
+        let hi = (pair[0] as char).to_digit(16)?;
+        let lo = (pair[1] as char).to_digit(16)?;
+        out.push((hi * 16 + lo) as u8).ok()?;
+    }
+    Some(out)
 }
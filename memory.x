@@ -1,5 +1,27 @@
 MEMORY
 {
 RAM : ORIGIN = 0x20000000, LENGTH = 96K
-FLASH : ORIGIN = 0x08000000, LENGTH = 1024K
+FLASH : ORIGIN = 0x08000000, LENGTH = 1004K
+/* Two 4K flash pages for the config audit trail. See config_audit.rs. */
+CONFIG_AUDIT : ORIGIN = 0x080FB000, LENGTH = 8K
+/* Two 4K flash pages for small nonvolatile records, such as the TX inhibit. See nv_store.rs. */
+NV_STORE : ORIGIN = 0x080FD000, LENGTH = 8K
+/* Last 4K of flash bank 1. Holds the image info block used by the image CRC self-check. */
+IMAGE_INFO : ORIGIN = 0x080FF000, LENGTH = 4K
 }
+
+/* End of the application image in flash. The load image of .data is placed last. */
+_image_end = LOADADDR(.data) + SIZEOF(.data);
+
+SECTIONS
+{
+  .image_info ORIGIN(IMAGE_INFO) :
+  {
+    __image_info = .;
+    /* Image length in bytes, counted from ORIGIN(FLASH). Filled in by the linker. */
+    LONG(_image_end - ORIGIN(FLASH));
+    /* Expected CRC-32 of the image. Left erased here and patched in after the build by
+       `cts2_obc_image_tool embed-crc`. */
+    LONG(0xFFFFFFFF);
+  } > IMAGE_INFO
+} INSERT AFTER .gnu.sgstubs;